@@ -0,0 +1,50 @@
+#!/usr/bin/env python3
+# 外部ボットのサンプル: 通れる方向にでたらめに歩き、ときどき爆弾を置く
+#
+#   標準入出力でやりとりする:  cargo run -- --bot 2="python3 res/bot/random_bot.py"
+#   TCP で接続する:            python3 res/bot/random_bot.py --connect <ポート> <プレイヤー番号>
+#
+# 1行ずつ観測（JSON）を読み、1行ずつ行動（JSON）を書く。
+# 書いたらバッファリングせずにすぐ flush すること。ログは標準エラー出力に書く。
+
+import json
+import random
+import socket
+import sys
+
+DIRECTIONS = {"left": (-1, 0), "up": (0, -1), "right": (1, 0), "down": (0, 1)}
+
+
+def play(reader, writer):
+    movement = "none"
+    for line in reader:
+        observation = json.loads(line)
+        if "error" in observation:
+            print(observation["error"], file=sys.stderr)
+            return
+        me = observation["players"][observation["player_number"]]
+        bombs = {(b["tile_x"], b["tile_y"]) for b in observation["bombs"]}
+
+        def passable(dx, dy):
+            x, y = me["tile_x"] + dx, me["tile_y"] + dy
+            return observation["grid"][y][x] == "." and (x, y) not in bombs
+
+        # マスにぴったり合った位置にいるときだけ進む方向を選び直す
+        if me["x"] % 32 == 0 and me["y"] % 32 == 0:
+            choices = [name for name, (dx, dy) in DIRECTIONS.items() if passable(dx, dy)]
+            movement = random.choice(choices) if choices else "none"
+        action = {"tick": observation["tick"], "movement": movement, "fire": random.random() < 0.05}
+        writer.write(json.dumps(action) + "\n")
+        writer.flush()
+
+
+if len(sys.argv) == 4 and sys.argv[1] == "--connect":
+    connection = socket.create_connection(("127.0.0.1", int(sys.argv[2])))
+    connection.setsockopt(socket.IPPROTO_TCP, socket.TCP_NODELAY, 1)
+    stream = connection.makefile("rw")
+    # 最初に操作するプレイヤー枠を伝える
+    stream.write(json.dumps({"player": int(sys.argv[3])}) + "\n")
+    stream.flush()
+    play(stream, stream)
+else:
+    play(sys.stdin, sys.stdout)
@@ -0,0 +1,13 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="bomber" tilewidth="32" tileheight="32" tilecount="3" columns="0">
+ <grid orientation="orthogonal" width="1" height="1"/>
+ <tile id="0" type="hard_wall">
+  <image width="32" height="32" source="../../image/wall.png"/>
+ </tile>
+ <tile id="1" type="soft_wall">
+  <image width="32" height="32" source="../../image/breakable_wall.png"/>
+ </tile>
+ <tile id="2" type="random_soft_wall">
+  <image width="32" height="32" source="../../image/breakable_wall.png"/>
+ </tile>
+</tileset>
@@ -2,4 +2,16 @@ pub mod ai_constants;
 pub mod field_element;
 pub mod field;
 pub mod ai_player;
-pub mod position;
\ No newline at end of file
+pub mod ai_profile;
+pub mod bot_connection;
+pub mod bot_protocol;
+pub mod bot_server;
+pub mod difficulty;
+pub mod external_player;
+pub mod hunter_player;
+pub mod monte_carlo_player;
+pub mod neural_network;
+pub mod neural_player;
+pub mod position;
+pub mod random_walk_player;
+pub mod strategy;
\ No newline at end of file
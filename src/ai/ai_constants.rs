@@ -1,5 +1,17 @@
-// パワーアップアイテムのスコア
-pub const SCORE_OF_POWER_UP_ITEM: i32 = 1000;
+// AI の性格（AIProfile）のデフォルト値と、性格によらない AI の設定
+
+// パワーアップアイテムの種類ごとのスコア
+pub const SCORE_OF_FIRE_UP_ITEM: i32 = 1000;
+pub const SCORE_OF_BOMB_UP_ITEM: i32 = 1000;
+pub const SCORE_OF_SPEED_UP_ITEM: i32 = 800;
+pub const SCORE_OF_FULL_FIRE_ITEM: i32 = 1500;
+pub const SCORE_OF_POWER_DOWN_ITEM: i32 = -1000;
+pub const SCORE_OF_KICK_ITEM: i32 = 300;
+pub const SCORE_OF_REMOTE_ITEM: i32 = 500;
+pub const SCORE_OF_PIERCE_ITEM: i32 = 600;
+
+// パワーダウンアイテムの上を通るときに経路のコストに加える値
+pub const COST_OF_POWER_DOWN_ITEM: i32 = 500;
 
 // 壁を壊すスコア
 pub const SCORE_OF_BREAK_WALL: i32 = 30;
@@ -16,5 +28,39 @@ pub const RISK_OF_BOMB:i32 = 1000;
 // 対戦相手のストレス度に対するスコアの重み
 pub const OPPONENT_STRESS_WEIGHT:i32 = 10;
 
+// リモコン爆弾を置いてから、対戦相手が爆発範囲にいなくても起爆するまでの時間（フレーム数）
+pub const REMOTE_BOMB_DETONATION_WAIT: i32 = 60;
+
 // 自分が爆弾設置後、対戦相手の現在位置を通過不能とみなす時間（フレーム数）
 pub const OPPONENT_NOT_PASSABLE_TIMEOUT:i32 = 60;
+
+// モンテカルロ AI が1回の判断で行うシミュレーションの最大回数
+pub const MONTE_CARLO_ROLLOUTS: i32 = 64;
+
+// モンテカルロ AI が1回のシミュレーションで先読みするフレーム数（爆弾が爆発し終わるまで）
+pub const MONTE_CARLO_ROLLOUT_FRAMES: i32 = 360;
+
+// モンテカルロ AI が1フレームにシミュレーションに使う時間の上限（ミリ秒、0 なら回数だけで止める）
+pub const MONTE_CARLO_TIME_BUDGET_MILLIS: i32 = 5;
+
+// モンテカルロ AI がシミュレーションの途中で時間の上限を過ぎていないか確かめる間隔（フレーム数）
+pub const MONTE_CARLO_DEADLINE_CHECK_FRAMES: i32 = 10;
+
+// モンテカルロ AI のシミュレーションで、対戦相手が爆弾を置くことがあるフレーム数と、マスごとに爆弾を置く確率（パーセント）
+pub const MONTE_CARLO_OPPONENT_FIRE_FRAMES: i32 = 60;
+pub const MONTE_CARLO_OPPONENT_FIRE_PROBABILITY: i32 = 10;
+
+// モンテカルロ AI のシミュレーションで、対戦相手を倒したときに生き残った評価（1.0）に加える値
+pub const MONTE_CARLO_KILL_SCORE: f64 = 0.5;
+
+// モンテカルロ AI が、標準の AI の入力を一番よい候補の代わりに使う評価の差の上限
+pub const MONTE_CARLO_SCORE_MARGIN: f64 = 0.2;
+
+// 目的地を探す範囲を制限しない場合の値
+pub const UNLIMITED_SEARCH_RADIUS:i32 = i32::MAX;
+
+// 追いかける AI の、対戦相手のストレス度に対するスコアの重みの倍率
+pub const HUNTER_OPPONENT_STRESS_WEIGHT_SCALE:i32 = 4;
+
+// でたらめに歩く AI が、マスに着いたときに爆弾を置く確率（パーセント）
+pub const RANDOM_WALK_FIRE_PROBABILITY:i32 = 5;
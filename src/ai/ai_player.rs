@@ -1,39 +1,57 @@
-use sdl2::keyboard::KeyboardState;
-
-use crate::game_screen::{
+use crate::game::{
     constants,
     bomb::Bomb,
     player_input::{PlayerInput, Movement},
-    screen::{self, GameScreen},
-    light_sprite::LightSprite, player_operation::PlayerOperation,
+    world::World,
+    light_sprite::LightSprite,
+    player::{self, Player},
+    power_up_item::PowerUpKind,
 };
 
 use super::{
-    field::{Field, ELEMENT_SIZE},
+    field::Field,
     field_element::{FieldElement, FieldObject},
-    ai_constants,
+    ai_profile::AIProfile,
     position::Position,
+    strategy::{AIStrategy, GameView},
 };
 
 
 pub struct AIPlayerAdditionalInfo {
     field: Field,
+    player_number: usize,
     player_position: Position,
     opponent_position: Position,
     player_power: i32,
+    player_speed: i32,
+    // 1マス移動するのにかかるフレーム数
+    frames_per_tile: i32,
+    player_has_pierce: bool,
+    // 今この場で爆弾を置ける（設置数の上限に達していない）場合 true
+    can_set_bomb: bool,
     opponent_is_dead: bool,
+    // 自分が置いたリモコン爆弾
+    my_remote_bombs: Vec<Bomb>,
 }
 
 impl AIPlayerAdditionalInfo {
-    pub fn new(game_screen: &GameScreen, player_number: usize) -> AIPlayerAdditionalInfo {
-        let player = &game_screen.players[player_number];
-        let opponent = &game_screen.players[1 - player_number];
+    pub fn new(world: &World, player_number: usize, profile: &AIProfile) -> AIPlayerAdditionalInfo {
+        let view = GameView::new(world, player_number);
+        let player = view.me();
+        // 生きている対戦相手のうち最も近いプレイヤーを相手にする
+        let opponent = view.nearest_opponent();
         AIPlayerAdditionalInfo {
-            field: Field::from_game_screen(game_screen),
+            field: Field::from_world(world, profile),
+            player_number,
             player_position: Position { x: player.get_x(), y: player.get_y() },
             opponent_position: Position { x: opponent.get_x(), y: opponent.get_y() },
             player_power: player.power,
+            player_speed: player.speed,
+            frames_per_tile: player.frames_per_tile(),
+            player_has_pierce: player.has_pierce,
+            can_set_bomb: world.rule.unlimited_bombs || Player::count_active_bombs(&world.bombs, player_number) < player.bomb_capacity,
             opponent_is_dead: opponent.is_dead(),
+            my_remote_bombs: world.bombs.iter().filter(|bomb| bomb.owner == player_number && bomb.remote).cloned().collect(),
         }
     }
 }
@@ -42,26 +60,36 @@ pub struct AIPlayer {
     previous_my_position: Position,
     // 前フレームで移動しようとした場合は true
     previous_want_to_mode: bool,
+    // 前フレームの移動方向
+    previous_movement: Movement,
     // 対戦相手のストレス度に対するスコアの重みにプラスする値
     opponent_stress_weight_plus: i32,
     // 対戦相手の位置を通過できないと認識するタイマー（1以上だと対戦相手の現在位置を「通過不能」と判断する）
     opponent_position_is_not_passable_timer: i32,
+    // スコアやリスクの重み
+    profile: AIProfile,
 }
 
-impl PlayerOperation for AIPlayer {
-    fn get_player_input(&mut self, _: &KeyboardState, ai_additional_info: Option<AIPlayerAdditionalInfo>) -> PlayerInput {
-        let mut info = ai_additional_info.unwrap();
-        self.get_player_input(&mut info)
+impl Default for AIPlayer {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl AIPlayer {
     pub fn new() -> AIPlayer {
+        Self::with_profile(AIProfile::default())
+    }
+
+    /// 性格を指定して AI を作る（opponent_stress_weight を 0 にすると対戦相手へのいやがらせをしない）
+    pub fn with_profile(profile: AIProfile) -> AIPlayer {
         AIPlayer {
             previous_my_position: Position {x: 0, y: 0},
             previous_want_to_mode: false,
+            previous_movement: Movement::NONE,
             opponent_stress_weight_plus: 0,
             opponent_position_is_not_passable_timer: 0,
+            profile,
         }
     }
 
@@ -100,6 +128,8 @@ impl AIPlayer {
         let mut max_score_position = Position { x: my_x, y: my_y };
         let mut max_score_fire = false;
         search_queue.push(Position { x: my_x, y: my_y });
+        // 爆弾を置いた場合のマップ（探索中に使い回す）
+        let mut field_if_bomb_set = additional_info.field.clone();
         while !search_queue.is_empty() {
             let field_position = search_queue.remove(0);
             let x = field_position.x;
@@ -114,37 +144,39 @@ impl AIPlayer {
                 risk = field_element.risk;
 
                 // この場所のスコアと爆弾設置の可否を計算する
-                let mut score = -field_element.risk - distance * ai_constants::SCORE_OF_DISTANCE;
+                let mut score = -field_element.risk - distance * self.profile.score_of_distance;
                 let mut fire = false;
                 match field_element.field_object {
-                    // パワーアップアイテムがある場所には行きたい！
-                    FieldObject::PowerUpItem => {
-                        score += ai_constants::SCORE_OF_POWER_UP_ITEM;
+                    // パワーアップアイテムがある場所には行きたい！（パワーダウンは避けたい）
+                    FieldObject::PowerUpItem(kind) => {
+                        score += Self::score_of_power_up_item(&self.profile, kind, additional_info);
                     }
                     FieldObject::Bomb => (),
                     _ => {
-                        // この場所に爆弾を置いて得られるメリットを計算する
-                        if !(x == opponent_x && y == opponent_y) {
-                            // 爆弾が置かれた状態を再現する
-                            let mut field_if_bomb_set = additional_info.field.clone();
+                        // この場所に爆弾を置いて得られるメリットを計算する（爆弾を置けない場合は計算しない）
+                        if additional_info.can_set_bomb && !(x == opponent_x && y == opponent_y) {
+                            // 爆弾が置かれた状態を再現する（この場所に着いてから置くので、爆発するのはその分だけ先になる）
+                            field_if_bomb_set.clone_from(&additional_info.field);
+                            let arrival_frame = distance * additional_info.frames_per_tile;
                             // この爆弾で破壊できる壁の数
-                            let break_count = field_if_bomb_set.add_bomb(
-                                &Bomb::new(x * constants::CHARACTER_SIZE, y * constants::CHARACTER_SIZE, additional_info.player_power)
-                            );
-                            // 逃げ場があるか確認する
-                            if field_if_bomb_set.check_if_escapable(x, y, opponent_x, opponent_y) {
+                            let mut bomb = Bomb::new(x * constants::CHARACTER_SIZE, y * constants::CHARACTER_SIZE, additional_info.player_power, additional_info.player_number);
+                            bomb.pierce = additional_info.player_has_pierce;
+                            bomb.remain_time += arrival_frame;
+                            let break_count = field_if_bomb_set.add_bomb(&bomb);
+                            // 逃げ場があるか確認する（誘爆で早く爆発する爆弾の炎が上がっている間のマスは通れない）
+                            if field_if_bomb_set.check_if_escapable(x, y, opponent_x, opponent_y, arrival_frame, additional_info.frames_per_tile) {
                                 // 破壊できる壁があればスコア加算
                                 if break_count > 0 {
-                                    score += break_count * ai_constants::SCORE_OF_BREAK_WALL;
+                                    score += break_count * self.profile.score_of_break_wall;
                                     fire = true;
                                 }
                                 // 対戦相手にいやがらせできればスコア加算
-                                if !additional_info.opponent_is_dead {
+                                if !additional_info.opponent_is_dead && self.profile.opponent_stress_weight > 0 {
                                     let opponent_stress_plus = Self::calc_opponent_stress(&field_if_bomb_set, opponent_x, opponent_y) - opponent_stress;
                                     if opponent_stress_plus > 0 {
                                         // スコアに加算する重みの計算
                                         // 「動きたいのに動けない」状況が続くと現在位置に爆弾を置く場合の重みが大きくなる
-                                        let weight = ai_constants::OPPONENT_STRESS_WEIGHT +
+                                        let weight = self.profile.opponent_stress_weight +
                                                 if field_position == my_position { self.opponent_stress_weight_plus } else { 0 };
                                         score += opponent_stress_plus * weight;
                                         fire = true;
@@ -162,6 +194,9 @@ impl AIPlayer {
             }
 
             let next_distance = distance + 1;
+            if next_distance > self.profile.search_radius {
+                continue;
+            }
             let next_positions = [
                 Position { x: x - 1, y },
                 Position { x: x + 1, y },
@@ -182,11 +217,19 @@ impl AIPlayer {
                     continue;
                 }
                 // この場所のリスクが高すぎる場合はここには行かない
-                if next_element.risk > ai_constants::RISK_OF_BOMB * 9 / 10 && next_element.risk > risk {
+                if next_element.risk > self.profile.risk_of_bomb * 9 / 10 && next_element.risk > risk {
+                    continue;
+                }
+                // この場所に移る間に炎が上がっている場合は通れない
+                if next_element.is_on_fire_between(distance * additional_info.frames_per_tile, next_distance * additional_info.frames_per_tile) {
                     continue;
                 }
                 // この場所にたどり着くまでのコストを計算し、すでにそれより低いコストで移動できる経路が計算済みなら何もしない
-                let cost = cost + next_element.risk;
+                let mut cost = cost + next_element.risk;
+                // パワーダウンアイテムの上はなるべく通らない
+                if let FieldObject::PowerUpItem(PowerUpKind::PowerDown) = next_element.field_object {
+                    cost += self.profile.cost_of_power_down_item;
+                }
                 if cost >= next_element.cost {
                     continue;
                 }
@@ -204,7 +247,7 @@ impl AIPlayer {
         let fire_flag = max_score_position == my_position && max_score_fire;
         if fire_flag {
             // 爆弾を設置する場合は「相手位置は通過不能と認識する」タイマーをセットする
-            self.opponent_position_is_not_passable_timer = ai_constants::OPPONENT_NOT_PASSABLE_TIMEOUT;
+            self.opponent_position_is_not_passable_timer = self.profile.opponent_not_passable_timeout;
         } else if self.opponent_position_is_not_passable_timer > 0 {
             self.opponent_position_is_not_passable_timer -= 1;
         }
@@ -219,30 +262,92 @@ impl AIPlayer {
         let fx = f.x * constants::CHARACTER_SIZE;
         let fy = f.y * constants::CHARACTER_SIZE;
         self.previous_want_to_mode = fx != additional_info.player_position.x || fy != additional_info.player_position.y;
-        if fx > additional_info.player_position.x {
+        let mut movement = if fx > additional_info.player_position.x {
             // 右に移動
-            PlayerInput::new(Movement::RIGHT, fire_flag)
+            Movement::RIGHT
         } else if fx < additional_info.player_position.x {
             // 左に移動
-            PlayerInput::new(Movement::LEFT, fire_flag)
+            Movement::LEFT
         } else if fy < additional_info.player_position.y {
             // 上に移動
-            PlayerInput::new(Movement::UP, fire_flag)
+            Movement::UP
         } else if fy > additional_info.player_position.y {
             // 下に移動
-            PlayerInput::new(Movement::DOWN, fire_flag)
+            Movement::DOWN
         } else {
-            PlayerInput::new(Movement::NONE, fire_flag)
+            Movement::NONE
+        };
+        // マスの途中で前フレームと逆向きに動こうとした場合、進んでいた先のマスの方が危険でなければそのまま進む
+        // （マスの境目で現在地の判定が入れ替わり、目的地も入れ替わって往復し続けるのを防ぐ）
+        let (dx, dy) = movement.delta();
+        let (pdx, pdy) = self.previous_movement.delta();
+        let px = additional_info.player_position.x;
+        let py = additional_info.player_position.y;
+        if (dx, dy) != (0, 0) && (dx, dy) == (-pdx, -pdy) &&
+            ((pdx != 0 && px % constants::CHARACTER_SIZE != 0) || (pdy != 0 && py % constants::CHARACTER_SIZE != 0)) {
+            let behind_x = if pdx < 0 { (px + constants::CHARACTER_SIZE - 1) / constants::CHARACTER_SIZE } else { px / constants::CHARACTER_SIZE };
+            let behind_y = if pdy < 0 { (py + constants::CHARACTER_SIZE - 1) / constants::CHARACTER_SIZE } else { py / constants::CHARACTER_SIZE };
+            let behind = additional_info.field.get_element(behind_x, behind_y);
+            let ahead = additional_info.field.get_element(behind_x + pdx, behind_y + pdy);
+            if ahead.is_passable() && ahead.risk <= behind.risk {
+                movement = self.previous_movement;
+            }
+        }
+        self.previous_movement = movement;
+        let mut player_input = PlayerInput::new(movement, fire_flag);
+        player_input.detonate = Self::should_detonate(&self.profile, additional_info, opponent_x, opponent_y);
+        player_input
+    }
+
+    /// リモコン爆弾を起爆するかどうか
+    /// 自分が爆発範囲の外にいて、対戦相手が爆発範囲にいるか置いてからしばらく経っていれば起爆する
+    fn should_detonate(profile: &AIProfile, additional_info: &AIPlayerAdditionalInfo, opponent_x: i32, opponent_y: i32) -> bool {
+        if additional_info.my_remote_bombs.is_empty() {
+            return false;
+        }
+        let cells = additional_info.my_remote_bombs.iter()
+            .flat_map(|bomb| additional_info.field.explosion_cells(
+                bomb.get_tile_x() / constants::CHARACTER_SIZE,
+                bomb.get_tile_y() / constants::CHARACTER_SIZE,
+                bomb.power,
+                bomb.pierce,
+            ))
+            .collect::<Vec<(i32, i32)>>();
+        // 自分がまたがっているマスのどれかが爆発範囲に入っていたら起爆しない
+        let position = additional_info.player_position;
+        let x_range = position.x / constants::CHARACTER_SIZE..=(position.x + constants::CHARACTER_SIZE - 1) / constants::CHARACTER_SIZE;
+        let y_range = position.y / constants::CHARACTER_SIZE..=(position.y + constants::CHARACTER_SIZE - 1) / constants::CHARACTER_SIZE;
+        if cells.iter().any(|(x, y)| x_range.contains(x) && y_range.contains(y)) {
+            return false;
+        }
+        let opponent_is_in_range = !additional_info.opponent_is_dead && cells.contains(&(opponent_x, opponent_y));
+        let waited_enough = additional_info.my_remote_bombs.iter()
+            .any(|bomb| (bomb.move_time * 60.0) as i32 >= profile.remote_bomb_detonation_wait);
+        opponent_is_in_range || waited_enough
+    }
+
+    /// パワーアップアイテムを取ることのスコア（すでに上限に達している効果のアイテムは価値がない）
+    fn score_of_power_up_item(profile: &AIProfile, kind: PowerUpKind, additional_info: &AIPlayerAdditionalInfo) -> i32 {
+        let power_is_max = additional_info.player_power >= player::MAX_POWER;
+        match kind {
+            PowerUpKind::FireUp => if power_is_max { 0 } else { profile.score_of_fire_up_item },
+            PowerUpKind::BombUp => profile.score_of_bomb_up_item,
+            PowerUpKind::SpeedUp => if additional_info.player_speed >= player::MAX_SPEED { 0 } else { profile.score_of_speed_up_item },
+            PowerUpKind::FullFire => if power_is_max { 0 } else { profile.score_of_full_fire_item },
+            PowerUpKind::Kick => profile.score_of_kick_item,
+            PowerUpKind::Remote => profile.score_of_remote_item,
+            PowerUpKind::Pierce => profile.score_of_pierce_item,
+            PowerUpKind::PowerDown => if additional_info.player_power <= 1 { 0 } else { profile.score_of_power_down_item },
         }
     }
 
     /// 対戦相手の移動範囲のうち何パーセントを「いずれ爆発する」状態にしているか
     fn calc_opponent_stress(field: &Field, opponent_x: i32, opponent_y: i32) -> i32 {
         // 到達可能で、かつ距離が5以下の場所を探索する
-        let mut checked = [false; ELEMENT_SIZE];
+        let mut checked = vec![false; field.elements.len()];
         let mut search_queue = Vec::<&FieldElement>::new();
         search_queue.push(field.get_element(opponent_x, opponent_y));
-        checked[(opponent_x + opponent_y * screen::MAP_WIDTH) as usize] = true;
+        checked[field.index(opponent_x, opponent_y)] = true;
         // 移動可能な範囲
         let mut movable_space = 0;
         // 危険な範囲
@@ -262,7 +367,7 @@ impl AIPlayer {
                 field.get_element(ex, ey + 1),
             ];
             for next_element in next_elements {
-                let idx = (next_element.x + next_element.y * screen::MAP_WIDTH) as usize;
+                let idx = field.index(next_element.x, next_element.y);
                 let distance = (opponent_x - next_element.x).abs() + (opponent_y - next_element.y).abs();
                 if distance <= 5 && !checked[idx] && next_element.is_passable() {
                     search_queue.push(next_element);
@@ -272,4 +377,11 @@ impl AIPlayer {
         }
         dangerous_space * 100 / movable_space
     }
-}
\ No newline at end of file
+}
+
+impl AIStrategy for AIPlayer {
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        let mut info = AIPlayerAdditionalInfo::new(view.world, view.player_number, &self.profile);
+        AIPlayer::get_player_input(self, &mut info)
+    }
+}
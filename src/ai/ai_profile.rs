@@ -0,0 +1,182 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::ai_constants;
+
+/// AI の性格（スコアやリスクの重み）
+///
+/// TOML か JSON のファイルから読み込める。ファイルに書かれていない項目はデフォルト値（ai_constants の値）になる。
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AIProfile {
+    // パワーアップアイテムの種類ごとのスコア
+    pub score_of_fire_up_item: i32,
+    pub score_of_bomb_up_item: i32,
+    pub score_of_speed_up_item: i32,
+    pub score_of_full_fire_item: i32,
+    pub score_of_power_down_item: i32,
+    pub score_of_kick_item: i32,
+    pub score_of_remote_item: i32,
+    pub score_of_pierce_item: i32,
+
+    // パワーダウンアイテムの上を通るときに経路のコストに加える値
+    pub cost_of_power_down_item: i32,
+
+    // 壁を壊すスコア
+    pub score_of_break_wall: i32,
+
+    // 目的地までの移動距離のマイナススコア
+    pub score_of_distance: i32,
+
+    // 爆発している場所のリスク値
+    pub risk_of_explosion: i32,
+
+    // 爆弾によっていずれ爆発する場所の最大リスク値
+    pub risk_of_bomb: i32,
+
+    // 対戦相手のストレス度に対するスコアの重み
+    pub opponent_stress_weight: i32,
+
+    // リモコン爆弾を置いてから、対戦相手が爆発範囲にいなくても起爆するまでの時間（フレーム数）
+    pub remote_bomb_detonation_wait: i32,
+
+    // 自分が爆弾設置後、対戦相手の現在位置を通過不能とみなす時間（フレーム数）
+    pub opponent_not_passable_timeout: i32,
+
+    // 目的地を探す範囲（現在地から何マス先まで調べるか）
+    pub search_radius: i32,
+
+    // モンテカルロ AI が1回の判断で行うシミュレーションの最大回数
+    pub monte_carlo_rollouts: i32,
+
+    // モンテカルロ AI が1回のシミュレーションで先読みするフレーム数
+    pub monte_carlo_rollout_frames: i32,
+
+    // モンテカルロ AI が1フレームにシミュレーションに使う時間の上限（ミリ秒、0 なら回数だけで止める）
+    pub monte_carlo_time_budget_millis: i32,
+}
+
+impl Default for AIProfile {
+    fn default() -> Self {
+        AIProfile {
+            score_of_fire_up_item: ai_constants::SCORE_OF_FIRE_UP_ITEM,
+            score_of_bomb_up_item: ai_constants::SCORE_OF_BOMB_UP_ITEM,
+            score_of_speed_up_item: ai_constants::SCORE_OF_SPEED_UP_ITEM,
+            score_of_full_fire_item: ai_constants::SCORE_OF_FULL_FIRE_ITEM,
+            score_of_power_down_item: ai_constants::SCORE_OF_POWER_DOWN_ITEM,
+            score_of_kick_item: ai_constants::SCORE_OF_KICK_ITEM,
+            score_of_remote_item: ai_constants::SCORE_OF_REMOTE_ITEM,
+            score_of_pierce_item: ai_constants::SCORE_OF_PIERCE_ITEM,
+            cost_of_power_down_item: ai_constants::COST_OF_POWER_DOWN_ITEM,
+            score_of_break_wall: ai_constants::SCORE_OF_BREAK_WALL,
+            score_of_distance: ai_constants::SCORE_OF_DISTANCE,
+            risk_of_explosion: ai_constants::RISK_OF_EXPLOSION,
+            risk_of_bomb: ai_constants::RISK_OF_BOMB,
+            opponent_stress_weight: ai_constants::OPPONENT_STRESS_WEIGHT,
+            remote_bomb_detonation_wait: ai_constants::REMOTE_BOMB_DETONATION_WAIT,
+            opponent_not_passable_timeout: ai_constants::OPPONENT_NOT_PASSABLE_TIMEOUT,
+            search_radius: ai_constants::UNLIMITED_SEARCH_RADIUS,
+            monte_carlo_rollouts: ai_constants::MONTE_CARLO_ROLLOUTS,
+            monte_carlo_rollout_frames: ai_constants::MONTE_CARLO_ROLLOUT_FRAMES,
+            monte_carlo_time_budget_millis: ai_constants::MONTE_CARLO_TIME_BUDGET_MILLIS,
+        }
+    }
+}
+
+impl AIProfile {
+    /// ファイルから読み込む（拡張子が .json なら JSON、それ以外は TOML として読む）
+    pub fn load(path: &Path) -> Result<AIProfile, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read AI profile {}: {}", path.display(), e))?;
+        let profile = if Self::is_json(path) {
+            serde_json::from_str::<AIProfile>(&text).map_err(|e| e.to_string())
+        } else {
+            toml::from_str::<AIProfile>(&text).map_err(|e| e.to_string())
+        }.map_err(|e| format!("Invalid AI profile {}: {}", path.display(), e))?;
+        profile.validate().map_err(|e| format!("Invalid AI profile {}: {}", path.display(), e))?;
+        Ok(profile)
+    }
+
+    /// ファイルに保存する（拡張子が .json なら JSON、それ以外は TOML で書く）
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = if Self::is_json(path) {
+            serde_json::to_string_pretty(self).map_err(|e| e.to_string())?
+        } else {
+            toml::to_string(self).map_err(|e| e.to_string())?
+        };
+        fs::write(path, text).map_err(|e| format!("Failed to write AI profile {}: {}", path.display(), e))
+    }
+
+    fn is_json(path: &Path) -> bool {
+        path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+    }
+
+    /// 探索が成り立たなくなる値でないか確認する
+    pub fn validate(&self) -> Result<(), String> {
+        if self.risk_of_bomb < 10 {
+            return Err(format!("risk_of_bomb must be 10 or more (got {})", self.risk_of_bomb));
+        }
+        if self.risk_of_explosion < self.risk_of_bomb {
+            return Err(format!("risk_of_explosion must not be less than risk_of_bomb (got {})", self.risk_of_explosion));
+        }
+        for (name, value) in [
+            ("score_of_distance", self.score_of_distance),
+            ("cost_of_power_down_item", self.cost_of_power_down_item),
+            ("opponent_stress_weight", self.opponent_stress_weight),
+            ("remote_bomb_detonation_wait", self.remote_bomb_detonation_wait),
+            ("opponent_not_passable_timeout", self.opponent_not_passable_timeout),
+            ("monte_carlo_time_budget_millis", self.monte_carlo_time_budget_millis),
+        ] {
+            if value < 0 {
+                return Err(format!("{} must not be negative (got {})", name, value));
+            }
+        }
+        if self.search_radius < 1 {
+            return Err(format!("search_radius must be 1 or more (got {})", self.search_radius));
+        }
+        for (name, value) in [
+            ("monte_carlo_rollouts", self.monte_carlo_rollouts),
+            ("monte_carlo_rollout_frames", self.monte_carlo_rollout_frames),
+        ] {
+            if value < 1 {
+                return Err(format!("{} must be 1 or more (got {})", name, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_profiles_fill_missing_items_with_defaults() {
+        assert!(AIProfile::load(Path::new("res/ai_profile/default.toml")).unwrap() == AIProfile::default());
+        let aggressive = AIProfile::load(Path::new("res/ai_profile/aggressive.json")).unwrap();
+        assert_eq!((aggressive.score_of_break_wall, aggressive.opponent_stress_weight), (20, 25));
+        assert_eq!(aggressive.risk_of_bomb, AIProfile::default().risk_of_bomb);
+    }
+
+    #[test]
+    fn saved_profiles_are_loaded_back() {
+        let profile = AIProfile { score_of_kick_item: 1234, search_radius: 5, ..AIProfile::default() };
+        for extension in ["toml", "json"] {
+            let path = std::env::temp_dir().join(format!("rust-bomber-test-{}.{}", std::process::id(), extension));
+            profile.save(&path).unwrap();
+            let loaded = AIProfile::load(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.unwrap(), profile);
+        }
+    }
+
+    #[test]
+    fn invalid_profiles_are_rejected() {
+        assert!(toml::from_str::<AIProfile>("score_of_kik_item = 1").is_err());
+        let profile = AIProfile { risk_of_bomb: 100, risk_of_explosion: 50, ..AIProfile::default() };
+        assert_eq!(profile.validate().err().unwrap(), "risk_of_explosion must not be less than risk_of_bomb (got 50)");
+        let profile = AIProfile { search_radius: 0, ..AIProfile::default() };
+        assert_eq!(profile.validate().err().unwrap(), "search_radius must be 1 or more (got 0)");
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::game::player_input::{PlayerInput, Movement};
+
+use super::bot_protocol::{BotAction, Observation};
+
+/// ボットから受け取った結果
+pub enum BotReply {
+    Action(PlayerInput),
+    // 時間内に行動が返ってこなかった
+    NoReply,
+    // 通信が切れた
+    Disconnected,
+}
+
+/// 外部のボットとの1行ずつのやりとり（読み書きはそれぞれ別のスレッドで行い、ゲームを止めない）
+pub struct BotConnection {
+    // 観測を書き込むスレッドへの送信口
+    observation_sender: Sender<String>,
+    // 行動を読み込むスレッドからの受信口
+    action_receiver: Receiver<String>,
+}
+
+impl BotConnection {
+    pub fn new<R: BufRead + Send + 'static, W: Write + Send + 'static>(reader: R, mut writer: W) -> BotConnection {
+        let (observation_sender, observation_receiver) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for line in observation_receiver {
+                if writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (action_sender, action_receiver) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else { break };
+                if action_sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        BotConnection { observation_sender, action_receiver }
+    }
+
+    /// 観測を送る（通信が切れていたら false）
+    pub fn send_observation(&self, observation: &Observation) -> bool {
+        self.observation_sender.send(observation.to_line()).is_ok()
+    }
+
+    /// まだ読んでいない行動を捨てる（前の試合の行動を読まないようにする）
+    pub fn discard_actions(&self) {
+        while self.action_receiver.try_recv().is_ok() {}
+    }
+
+    /// tick 番目の観測に対する行動を timeout まで待つ（それより前の観測に対する行動は読み飛ばす）
+    ///
+    /// 読めない行動が返ってきた場合はエラーを表示して何もしない入力にする。
+    pub fn receive_action(&self, tick: u64, timeout: Duration) -> BotReply {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            match self.action_receiver.recv_timeout(wait) {
+                Ok(line) => {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let action = match BotAction::parse(&line) {
+                        Ok(action) => action,
+                        Err(e) => {
+                            println!("{}", e);
+                            return BotReply::Action(PlayerInput::new(Movement::NONE, false));
+                        }
+                    };
+                    if action.tick.is_some_and(|action_tick| action_tick < tick) {
+                        continue;
+                    }
+                    return BotReply::Action(action.to_player_input().unwrap_or_else(|e| {
+                        println!("{}", e);
+                        PlayerInput::new(Movement::NONE, false)
+                    }));
+                }
+                Err(RecvTimeoutError::Timeout) => return BotReply::NoReply,
+                Err(RecvTimeoutError::Disconnected) => return BotReply::Disconnected,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor};
+
+    use super::*;
+
+    #[test]
+    fn actions_for_older_observations_are_skipped() {
+        let replies = "{\"tick\": 1, \"movement\": \"left\"}\n\n{\"tick\": 2, \"movement\": \"up\", \"fire\": true}\n";
+        let connection = BotConnection::new(Cursor::new(replies), io::sink());
+        match connection.receive_action(2, Duration::from_secs(1)) {
+            BotReply::Action(player_input) => assert!(player_input.movement == Movement::UP && player_input.fire),
+            _ => panic!("expected an action"),
+        }
+        assert!(matches!(connection.receive_action(3, Duration::from_secs(1)), BotReply::Disconnected));
+    }
+
+    #[test]
+    fn invalid_actions_become_no_input() {
+        let connection = BotConnection::new(Cursor::new("{\"movement\": \"jump\"}\nnot json\n"), io::sink());
+        for _ in 0..2 {
+            match connection.receive_action(1, Duration::from_secs(1)) {
+                BotReply::Action(player_input) => assert!(player_input.movement == Movement::NONE && !player_input.fire),
+                _ => panic!("expected an action"),
+            }
+        }
+    }
+}
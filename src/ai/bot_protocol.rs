@@ -0,0 +1,224 @@
+// 外部のボット（別のプロセスで動くプログラム）とやりとりするメッセージ
+//
+// ゲームは毎フレーム Observation を1行の JSON で送り、ボットは BotAction を1行の JSON で返す。
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    constants,
+    light_sprite::LightSprite,
+    player_input::{PlayerInput, Movement},
+};
+
+use super::strategy::GameView;
+
+// 地形を表す文字
+pub const GRID_HARD_WALL: char = '#';
+pub const GRID_SOFT_WALL: char = '+';
+pub const GRID_FLOOR: char = '.';
+
+/// ボットに送るゲームの状態
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Observation {
+    // 何回目の観測か（ボットは返す行動に同じ値を付けられる）
+    pub tick: u64,
+    // ボットが操作するプレイヤーの番号
+    pub player_number: usize,
+    pub width: i32,
+    pub height: i32,
+    // 1行ごとの地形（'#' 壊せない壁、'+' 壊せる壁、'.' 床）
+    pub grid: Vec<String>,
+    pub players: Vec<PlayerObservation>,
+    pub bombs: Vec<BombObservation>,
+    pub explosions: Vec<ExplosionObservation>,
+    pub items: Vec<ItemObservation>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerObservation {
+    pub player_number: usize,
+    pub alive: bool,
+    // ピクセル単位の位置
+    pub x: i32,
+    pub y: i32,
+    // 一番重なっているマス
+    pub tile_x: i32,
+    pub tile_y: i32,
+    pub power: i32,
+    pub speed: i32,
+    pub bomb_capacity: i32,
+    pub can_kick: bool,
+    pub has_remote: bool,
+    pub has_pierce: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BombObservation {
+    // 爆発するマス（滑っている途中なら一番近いマス）
+    pub tile_x: i32,
+    pub tile_y: i32,
+    pub owner: usize,
+    pub power: i32,
+    // 爆発するまでのフレーム数
+    pub remain_time: i32,
+    pub remote: bool,
+    pub pierce: bool,
+    // 蹴られて滑っている方向（止まっていれば "none"）
+    pub slide: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExplosionObservation {
+    pub tile_x: i32,
+    pub tile_y: i32,
+    // 爆発が消えるまでのフレーム数
+    pub remain_time: i32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemObservation {
+    pub tile_x: i32,
+    pub tile_y: i32,
+    // PowerUpKind::name() の名前
+    pub kind: String,
+}
+
+impl Observation {
+    pub fn new(view: &GameView, tick: u64) -> Observation {
+        let world = view.world;
+        let width = world.stage.width;
+        let height = world.stage.height;
+
+        let mut cells = vec![vec![GRID_FLOOR; width as usize]; height as usize];
+        for wall in world.walls.iter() {
+            let x = wall.get_x() / constants::CHARACTER_SIZE;
+            let y = wall.get_y() / constants::CHARACTER_SIZE;
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                cells[y as usize][x as usize] = if wall.is_breakable { GRID_SOFT_WALL } else { GRID_HARD_WALL };
+            }
+        }
+
+        Observation {
+            tick,
+            player_number: view.player_number,
+            width,
+            height,
+            grid: cells.into_iter().map(|row| row.into_iter().collect()).collect(),
+            players: world.players.iter().map(|player| {
+                let (tile_x, tile_y) = GameView::tile_of(player);
+                PlayerObservation {
+                    player_number: player.player_number,
+                    alive: !player.is_dead(),
+                    x: player.get_x(),
+                    y: player.get_y(),
+                    tile_x,
+                    tile_y,
+                    power: player.power,
+                    speed: player.speed,
+                    bomb_capacity: player.bomb_capacity,
+                    can_kick: player.can_kick,
+                    has_remote: player.has_remote,
+                    has_pierce: player.has_pierce,
+                }
+            }).collect(),
+            bombs: world.bombs.iter().map(|bomb| BombObservation {
+                tile_x: bomb.get_tile_x() / constants::CHARACTER_SIZE,
+                tile_y: bomb.get_tile_y() / constants::CHARACTER_SIZE,
+                owner: bomb.owner,
+                power: bomb.power,
+                remain_time: bomb.remain_time,
+                remote: bomb.remote,
+                pierce: bomb.pierce,
+                slide: bomb.slide.name().to_string(),
+            }).collect(),
+            explosions: world.explosions.iter().map(|explosion| ExplosionObservation {
+                tile_x: explosion.get_x() / constants::CHARACTER_SIZE,
+                tile_y: explosion.get_y() / constants::CHARACTER_SIZE,
+                remain_time: explosion.remain_time,
+            }).collect(),
+            items: world.power_up_items.iter().map(|item| ItemObservation {
+                tile_x: item.get_x() / constants::CHARACTER_SIZE,
+                tile_y: item.get_y() / constants::CHARACTER_SIZE,
+                kind: item.kind.name().to_string(),
+            }).collect(),
+        }
+    }
+
+    /// 1行の JSON にする
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("observation must be serializable")
+    }
+}
+
+/// ボットから受け取る行動
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotAction {
+    // どの観測に対する行動か（省略すると最新の観測に対する行動とみなす）
+    pub tick: Option<u64>,
+    // "none", "left", "up", "right", "down" のどれか（省略すると "none"）
+    pub movement: Option<String>,
+    // 爆弾を置く
+    pub fire: bool,
+    // リモコン爆弾を爆発させる
+    pub detonate: bool,
+}
+
+impl BotAction {
+    /// 1行の JSON を読む
+    pub fn parse(line: &str) -> Result<BotAction, String> {
+        serde_json::from_str::<BotAction>(line).map_err(|e| format!("Invalid bot action {:?}: {}", line, e))
+    }
+
+    pub fn to_player_input(&self) -> Result<PlayerInput, String> {
+        let movement = match &self.movement {
+            None => Movement::NONE,
+            Some(name) => Movement::from_name(name).ok_or_else(|| format!("Invalid bot movement: {}", name))?,
+        };
+        Ok(PlayerInput { movement, fire: self.fire, detonate: self.detonate })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::bomb::Bomb;
+    use crate::game::power_up_item::{PowerUpItem, PowerUpKind};
+    use crate::game::test_support::new_world;
+
+    #[test]
+    fn observation_describes_the_world() {
+        let mut world = new_world();
+        world.bombs.push(Bomb::new(3 * constants::CHARACTER_SIZE, constants::CHARACTER_SIZE, 2, 1));
+        world.power_up_items.push(PowerUpItem::new(5 * constants::CHARACTER_SIZE, 3 * constants::CHARACTER_SIZE, PowerUpKind::Kick));
+        let observation = Observation::new(&GameView::new(&world, 1), 7);
+        assert_eq!((observation.tick, observation.player_number, observation.width, observation.height), (7, 1, 9, 7));
+        assert_eq!(observation.grid[0], "#########");
+        assert_eq!(observation.grid[2], "#.#.#.#.#");
+        assert_eq!((observation.players[1].tile_x, observation.players[1].tile_y), (1, 4));
+        assert_eq!((observation.bombs[0].tile_x, observation.bombs[0].tile_y, observation.bombs[0].owner, observation.bombs[0].power), (3, 1, 1, 2));
+        assert_eq!(observation.bombs[0].slide, "none");
+        assert_eq!(observation.items[0].kind, "kick");
+
+        // 1行の JSON で送る
+        let line = observation.to_line();
+        assert!(!line.contains('\n'));
+        let parsed: Observation = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.grid, observation.grid);
+    }
+
+    #[test]
+    fn bot_actions_are_parsed() {
+        let action = BotAction::parse(r#"{"tick": 3, "movement": "left", "fire": true}"#).unwrap();
+        assert_eq!(action.tick, Some(3));
+        let player_input = action.to_player_input().unwrap();
+        assert!(player_input.movement == Movement::LEFT && player_input.fire && !player_input.detonate);
+
+        let player_input = BotAction::parse("{}").unwrap().to_player_input().unwrap();
+        assert!(player_input.movement == Movement::NONE && !player_input.fire);
+
+        assert!(BotAction::parse(r#"{"move": "left"}"#).is_err());
+        assert!(BotAction::parse("left").is_err());
+        assert_eq!(BotAction::parse(r#"{"movement": "jump"}"#).unwrap().to_player_input().err().unwrap(), "Invalid bot movement: jump");
+    }
+}
@@ -0,0 +1,265 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::game::player_input::{PlayerInput, Movement};
+use crate::game::world::MAX_PLAYER_COUNT;
+
+use super::{
+    bot_connection::{BotConnection, BotReply},
+    bot_protocol::Observation,
+    strategy::{AIStrategy, GameView},
+};
+
+/// ボットの行動を待つ間、ゲームをどう進めるか
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BotServerMode {
+    // ゲームは止めず、接続していないボットや時間内に行動が返ってこなかったボットは何もしない
+    Realtime,
+    // ボットが接続するまでゲームを止める（接続した後は Realtime と同じ）
+    Pause,
+    // 毎フレーム、ボットの行動が返ってくるまでゲームを止める（デバッガで止めていても進まない）
+    Lockstep,
+}
+
+impl BotServerMode {
+    pub const ALL: [BotServerMode; 3] = [BotServerMode::Realtime, BotServerMode::Pause, BotServerMode::Lockstep];
+
+    /// コマンドラインで指定するときの名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Realtime => "realtime",
+            Self::Pause => "pause",
+            Self::Lockstep => "lockstep",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<BotServerMode> {
+        Self::ALL.iter().copied().find(|mode| mode.name() == name)
+    }
+}
+
+/// 接続してきたボットが最初に送る1行（どのプレイヤー枠を操作するか）
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BotHello {
+    // プレイヤー枠の番号（1から）
+    player: usize,
+}
+
+/// プレイヤー枠に接続しているボット
+struct RemoteConnection {
+    // 接続ごとに振る番号（つなぎ直したことを見分ける）
+    id: u64,
+    connection: BotConnection,
+    stream: TcpStream,
+}
+
+impl Drop for RemoteConnection {
+    fn drop(&mut self) {
+        // 読み込みスレッドを終わらせる
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+type RemoteSlots = Arc<[Mutex<Option<RemoteConnection>>; MAX_PLAYER_COUNT]>;
+
+/// localhost の TCP ポートで待ち受け、接続してきたボットをプレイヤー枠に割り当てる
+///
+/// 接続したボットは最初に {"player": <番号>} の1行を送り、その後は外部のボットと同じく観測を受け取って行動を返す。
+/// 同じプレイヤー枠に新しいボットが接続すると、古い接続は切られる。
+#[derive(Clone)]
+pub struct BotServer {
+    pub port: u16,
+    pub mode: BotServerMode,
+    // Realtime と Pause でボットの行動を待つ時間
+    pub timeout: Duration,
+    slots: RemoteSlots,
+}
+
+impl BotServer {
+    /// 待ち受けを開始する（port が 0 なら空いているポートを使う）
+    pub fn start(port: u16, mode: BotServerMode, timeout: Duration) -> Result<BotServer, String> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).map_err(|e| format!("Failed to start bot server on port {}: {}", port, e))?;
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+        let slots: RemoteSlots = Arc::new(Default::default());
+
+        let accept_slots = slots.clone();
+        thread::spawn(move || {
+            for (id, stream) in listener.incoming().enumerate() {
+                let Ok(stream) = stream else { continue };
+                let slots = accept_slots.clone();
+                thread::spawn(move || {
+                    if let Err(e) = Self::accept(stream, id as u64, &slots) {
+                        println!("{}", e);
+                    }
+                });
+            }
+        });
+        println!("Bot server listening on 127.0.0.1:{} ({})", port, mode.name());
+
+        Ok(BotServer { port, mode, timeout, slots })
+    }
+
+    /// 最初の1行を読んで、接続をプレイヤー枠に割り当てる
+    fn accept(stream: TcpStream, id: u64, slots: &RemoteSlots) -> Result<(), String> {
+        let peer = stream.peer_addr().map_err(|e| e.to_string())?;
+        // 1行ずつのやりとりを遅らせないようにする
+        stream.set_nodelay(true).map_err(|e| e.to_string())?;
+        let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(|e| format!("Failed to read hello from bot {}: {}", peer, e))?;
+        let slot = match serde_json::from_str::<BotHello>(&line) {
+            Ok(hello) if (1..=MAX_PLAYER_COUNT).contains(&hello.player) => hello.player - 1,
+            result => {
+                let error = match result {
+                    Ok(hello) => format!("Invalid player number {}", hello.player),
+                    Err(e) => format!("Invalid hello {:?}: {}", line.trim_end(), e),
+                };
+                let _ = writeln!(writer, "{}", serde_json::json!({ "error": error }));
+                return Err(format!("Rejected bot {}: {}", peer, error));
+            }
+        };
+        let connection = BotConnection::new(reader, writer);
+        *slots[slot].lock().unwrap() = Some(RemoteConnection { id, connection, stream });
+        println!("Bot {} connected to player {}", peer, slot + 1);
+        Ok(())
+    }
+
+    /// プレイヤー枠にボットが接続しているか
+    pub fn is_connected(&self, player_number: usize) -> bool {
+        self.slots[player_number].lock().unwrap().is_some()
+    }
+
+    /// プレイヤー枠を操作する AI を作る
+    pub fn remote_player(&self, player_number: usize) -> RemotePlayer {
+        if let Some(remote) = self.slots[player_number].lock().unwrap().as_ref() {
+            remote.connection.discard_actions();
+        }
+        RemotePlayer {
+            server: self.clone(),
+            player_number,
+            tick: 1,
+            observation_sent_to: None,
+            pending_input: None,
+        }
+    }
+}
+
+/// TCP で接続してきたボットが操作するプレイヤー
+pub struct RemotePlayer {
+    server: BotServer,
+    player_number: usize,
+    // 次に送る観測の番号
+    tick: u64,
+    // 今の観測を送った接続の番号
+    observation_sent_to: Option<u64>,
+    // Lockstep で受け取り済みの行動
+    pending_input: Option<PlayerInput>,
+}
+
+impl RemotePlayer {
+    /// 今の観測をまだ送っていなければ送り、行動を待つ（通信が切れたら接続を外す）
+    fn receive_action(&mut self, view: &GameView, timeout: Duration) -> BotReply {
+        let mut slot = self.server.slots[self.player_number].lock().unwrap();
+        let Some(remote) = slot.as_ref() else { return BotReply::Disconnected };
+        let mut reply = BotReply::Disconnected;
+        if self.observation_sent_to == Some(remote.id) || remote.connection.send_observation(&Observation::new(view, self.tick)) {
+            self.observation_sent_to = Some(remote.id);
+            reply = remote.connection.receive_action(self.tick, timeout);
+        }
+        if let BotReply::Disconnected = reply {
+            println!("Bot for player {} disconnected", self.player_number + 1);
+            *slot = None;
+        }
+        reply
+    }
+}
+
+impl AIStrategy for RemotePlayer {
+    fn is_ready(&mut self, view: &GameView) -> bool {
+        match self.server.mode {
+            BotServerMode::Realtime => true,
+            BotServerMode::Pause => self.server.is_connected(self.player_number),
+            BotServerMode::Lockstep => {
+                if self.pending_input.is_none() {
+                    if let BotReply::Action(player_input) = self.receive_action(view, Duration::ZERO) {
+                        self.pending_input = Some(player_input);
+                    }
+                }
+                self.pending_input.is_some()
+            }
+        }
+    }
+
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        let player_input = match self.pending_input.take() {
+            Some(player_input) => player_input,
+            None => match self.receive_action(view, self.server.timeout) {
+                BotReply::Action(player_input) => player_input,
+                _ => PlayerInput::new(Movement::NONE, false),
+            },
+        };
+        self.tick += 1;
+        self.observation_sent_to = None;
+        player_input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::game::test_support::new_world;
+
+    fn wait_until(mut condition: impl FnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn lockstep_waits_for_the_connected_bot() {
+        let server = BotServer::start(0, BotServerMode::Lockstep, Duration::from_millis(10)).unwrap();
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port)).unwrap();
+        writeln!(stream, "{{\"player\": 2}}").unwrap();
+        wait_until(|| server.is_connected(1));
+        assert!(!server.is_connected(0));
+
+        let world = new_world();
+        let view = GameView::new(&world, 1);
+        let mut player = server.remote_player(1);
+        assert!(!player.is_ready(&view));
+
+        // 観測を受け取ってから行動を返すと、次のフレームに進める
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let observation: Observation = serde_json::from_str(&line).unwrap();
+        assert_eq!((observation.tick, observation.player_number), (1, 1));
+        writeln!(stream, "{{\"tick\": 1, \"movement\": \"right\", \"fire\": true}}").unwrap();
+        wait_until(|| player.is_ready(&view));
+        let player_input = player.get_player_input(&view);
+        assert!(player_input.movement == Movement::RIGHT && player_input.fire);
+        assert!(!player.is_ready(&view));
+    }
+
+    #[test]
+    fn invalid_hello_is_rejected() {
+        let server = BotServer::start(0, BotServerMode::Realtime, Duration::from_millis(10)).unwrap();
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port)).unwrap();
+        writeln!(stream, "{{\"player\": 5}}").unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        assert!(line.contains("Invalid player number 5"), "{}", line);
+        assert!((0..MAX_PLAYER_COUNT).all(|i| !server.is_connected(i)));
+    }
+}
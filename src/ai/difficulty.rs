@@ -0,0 +1,195 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::game::{
+    constants,
+    light_sprite::LightSprite,
+    player_input::{PlayerInput, Movement},
+};
+
+use super::{
+    ai_constants,
+    ai_profile::AIProfile,
+    strategy::{AIStrategy, GameView},
+};
+
+/// AI の強さ
+#[derive(Copy, Clone, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    // 毎フレーム判断し、間違えない（難易度を選べるようになる前の AI と同じ強さ）
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard, Difficulty::Expert];
+
+    /// コマンドラインなどで指定するときの名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+            Self::Expert => "expert",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        Self::ALL.iter().copied().find(|difficulty| difficulty.name() == name)
+    }
+
+    /// 何フレームごとに入力を決め直すか（間のフレームは次のマスに着くまで前の移動方向を続け、着いたら立ち止まる）
+    pub fn reaction_interval(&self) -> i32 {
+        match self {
+            Self::Easy => 20,
+            Self::Normal => 10,
+            Self::Hard => 4,
+            Self::Expert => 1,
+        }
+    }
+
+    /// 入力を決めるときに間違えて、でたらめな方向に動いてしまう確率（パーセント）
+    pub fn mistake_probability(&self) -> i32 {
+        match self {
+            Self::Easy => 10,
+            Self::Normal => 4,
+            Self::Hard => 1,
+            Self::Expert => 0,
+        }
+    }
+
+    /// 目的地を探す範囲（現在地から何マス先まで調べるか）
+    pub fn search_radius(&self) -> i32 {
+        match self {
+            Self::Easy => 4,
+            Self::Normal => 7,
+            Self::Hard => 12,
+            Self::Expert => ai_constants::UNLIMITED_SEARCH_RADIUS,
+        }
+    }
+
+    /// 対戦相手のストレス度に対するスコアの重みの倍率（パーセント）
+    pub fn opponent_stress_weight_percent(&self) -> i32 {
+        match self {
+            Self::Easy => 20,
+            Self::Normal => 50,
+            Self::Hard => 100,
+            Self::Expert => 100,
+        }
+    }
+
+    /// AI の性格をこの強さに合わせて弱める（目的地を探す範囲を狭め、対戦相手へのいやがらせを控えめにする）
+    pub fn apply_to_profile(&self, profile: &AIProfile) -> AIProfile {
+        AIProfile {
+            search_radius: profile.search_radius.min(self.search_radius()),
+            opponent_stress_weight: profile.opponent_stress_weight * self.opponent_stress_weight_percent() / 100,
+            ..*profile
+        }
+    }
+}
+
+/// 強さに合わせて、戦略が決めた入力を遅らせたり間違えさせたりする
+pub struct DifficultyFilter {
+    strategy: Box<dyn AIStrategy>,
+    difficulty: Difficulty,
+    // 試合のシードとプレイヤー番号から作る乱数生成器（最初の入力のときに作る）
+    rng: Option<StdRng>,
+    // 次に入力を決め直すまでのフレーム数
+    wait: i32,
+    previous_movement: Movement,
+}
+
+impl DifficultyFilter {
+    pub fn new(strategy: Box<dyn AIStrategy>, difficulty: Difficulty) -> DifficultyFilter {
+        DifficultyFilter {
+            strategy,
+            difficulty,
+            rng: None,
+            wait: 0,
+            previous_movement: Movement::NONE,
+        }
+    }
+}
+
+impl AIStrategy for DifficultyFilter {
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        // 戦略は毎フレームの状態を使うので、入力を決め直さないフレームでも呼ぶ（移動と爆弾の設置は捨て、リモコン爆弾の起爆だけ使う）
+        let mut player_input = self.strategy.get_player_input(view);
+        if self.wait > 0 {
+            self.wait -= 1;
+            let me = view.me();
+            if me.get_x() % constants::CHARACTER_SIZE == 0 && me.get_y() % constants::CHARACTER_SIZE == 0 {
+                self.previous_movement = Movement::NONE;
+            }
+            return PlayerInput { movement: self.previous_movement, fire: false, detonate: player_input.detonate };
+        }
+        self.wait = self.difficulty.reaction_interval() - 1;
+
+        let rng = self.rng.get_or_insert_with(|| StdRng::seed_from_u64(view.world.seed.wrapping_add(view.player_number as u64)));
+        if rng.gen_range(0, 100) < self.difficulty.mistake_probability() {
+            player_input = PlayerInput {
+                movement: Movement::ALL[rng.gen_range(0, Movement::ALL.len())],
+                fire: false,
+                detonate: player_input.detonate,
+            };
+        }
+        self.previous_movement = player_input.movement;
+        player_input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::game::test_support::new_world;
+
+    /// 呼ばれた回数を数え、いつも右に進んで爆弾を置き、リモコン爆弾を起爆する戦略
+    struct CountingStrategy {
+        calls: Rc<Cell<i32>>,
+    }
+
+    impl AIStrategy for CountingStrategy {
+        fn get_player_input(&mut self, _view: &GameView) -> PlayerInput {
+            self.calls.set(self.calls.get() + 1);
+            PlayerInput { movement: Movement::RIGHT, fire: true, detonate: true }
+        }
+    }
+
+    #[test]
+    fn filter_calls_the_strategy_every_frame_and_keeps_detonation() {
+        let world = new_world();
+        let calls = Rc::new(Cell::new(0));
+        let mut filter = DifficultyFilter::new(Box::new(CountingStrategy { calls: calls.clone() }), Difficulty::Easy);
+        let interval = Difficulty::Easy.reaction_interval();
+
+        let mut fire_frames = 0;
+        for frame in 0..interval * 3 {
+            let player_input = filter.get_player_input(&GameView::new(&world, 0));
+            assert_eq!(calls.get(), frame + 1);
+            assert!(player_input.detonate, "frame {}", frame);
+            if player_input.fire {
+                assert_eq!(frame % interval, 0, "fired on a wait frame");
+                fire_frames += 1;
+            }
+        }
+        // 間違えたフレーム以外では、入力を決め直したフレームごとに爆弾を置く
+        assert!(fire_frames >= 2);
+    }
+
+    #[test]
+    fn lower_difficulties_search_less_and_harass_less() {
+        let profile = AIProfile::default();
+        let easy = Difficulty::Easy.apply_to_profile(&profile);
+        assert_eq!(easy.search_radius, 4);
+        assert_eq!(easy.opponent_stress_weight, profile.opponent_stress_weight / 5);
+        assert!(Difficulty::Expert.apply_to_profile(&profile) == profile);
+        for difficulty in Difficulty::ALL {
+            assert!(Difficulty::from_name(difficulty.name()) == Some(difficulty));
+        }
+    }
+}
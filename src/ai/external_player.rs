@@ -0,0 +1,120 @@
+use std::io::BufReader;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use crate::game::player_input::{PlayerInput, Movement};
+
+use super::{
+    bot_connection::{BotConnection, BotReply},
+    bot_protocol::Observation,
+    strategy::{AIStrategy, GameView},
+};
+
+// ボットの行動を待つ時間のデフォルト値（ミリ秒）
+pub const DEFAULT_TIMEOUT_MILLIS: u64 = 15;
+
+/// 別のプロセスで動くボット（標準入力に観測を1行ずつ書き、標準出力から行動を1行ずつ読む）
+///
+/// 時間内に行動が返ってこなかったフレームや、プロセスが終了した後は何もしない入力になる。
+pub struct ExternalPlayer {
+    command: String,
+    timeout: Duration,
+    // 起動できなかった場合や、通信が切れた場合は None
+    process: Option<(Child, BotConnection)>,
+    tick: u64,
+}
+
+impl ExternalPlayer {
+    /// コマンド（空白区切りでプログラムと引数）を起動する（起動できなくてもエラーを表示して何もしないプレイヤーになる）
+    pub fn new(command: &str, timeout: Duration) -> ExternalPlayer {
+        let process = match Self::spawn(command) {
+            Ok(process) => Some(process),
+            Err(e) => {
+                println!("{}", e);
+                None
+            }
+        };
+        ExternalPlayer {
+            command: command.to_string(),
+            timeout,
+            process,
+            tick: 0,
+        }
+    }
+
+    fn spawn(command: &str) -> Result<(Child, BotConnection), String> {
+        let mut args = command.split_whitespace();
+        let program = args.next().ok_or("Bot command is empty")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Failed to start bot {}: {}", command, e))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        Ok((child, BotConnection::new(BufReader::new(stdout), stdin)))
+    }
+
+    fn disconnect(&mut self, reason: &str) {
+        println!("Bot {} disconnected: {}", self.command, reason);
+        self.process = None;
+    }
+}
+
+impl AIStrategy for ExternalPlayer {
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        let none = PlayerInput::new(Movement::NONE, false);
+        self.tick += 1;
+        let Some((_, connection)) = &self.process else { return none };
+
+        if !connection.send_observation(&Observation::new(view, self.tick)) {
+            self.disconnect("failed to write observation");
+            return none;
+        }
+        match connection.receive_action(self.tick, self.timeout) {
+            BotReply::Action(player_input) => player_input,
+            BotReply::NoReply => none,
+            BotReply::Disconnected => {
+                self.disconnect("process exited");
+                none
+            }
+        }
+    }
+}
+
+impl Drop for ExternalPlayer {
+    fn drop(&mut self) {
+        if let Some((child, _)) = &mut self.process {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_support::new_world;
+
+    #[test]
+    fn bot_that_cannot_start_stands_still() {
+        let world = new_world();
+        let mut player = ExternalPlayer::new("rust-bomber-no-such-bot", Duration::from_millis(1));
+        for _ in 0..3 {
+            let player_input = player.get_player_input(&GameView::new(&world, 0));
+            assert!(player_input.movement == Movement::NONE && !player_input.fire);
+        }
+    }
+
+    #[test]
+    fn bot_that_exits_is_disconnected() {
+        let world = new_world();
+        let mut player = ExternalPlayer::new("true", Duration::from_millis(100));
+        for _ in 0..10 {
+            player.get_player_input(&GameView::new(&world, 0));
+        }
+        assert!(player.process.is_none());
+    }
+}
@@ -1,31 +1,103 @@
+use std::collections::VecDeque;
+
 use super::ai_constants;
+use super::ai_profile::AIProfile;
 use super::field_element::FieldElement;
 use super::field_element::FieldObject;
-use crate::game_screen::{bomb::Bomb, constants, screen, screen::GameScreen, light_sprite::LightSprite};
+use crate::game::{bomb::{self, Bomb}, constants, world::World, light_sprite::LightSprite};
 
-pub const ELEMENT_SIZE: usize = (screen::MAP_WIDTH * screen::MAP_HEIGHT) as usize;
+/// マップに置いた爆弾（爆発する位置に置かれているものとして扱う）
+struct FieldBomb {
+    position: (i32, i32),
+    // 滑っている爆弾が爆発するまでに通るマス
+    path: Vec<(i32, i32)>,
+    // 炎が届くマス
+    cells: Vec<(i32, i32)>,
+    // 炎が届いて誘爆する爆弾（Field::bombs の番号）
+    hits: Vec<usize>,
+    // 誘爆を考えて、爆発するまでのフレーム数（誘爆しないリモコン爆弾は None）
+    explode_frame: Option<i32>,
+}
 
-#[derive(Copy, Clone)]
 pub struct Field {
-    pub elements: [FieldElement; ELEMENT_SIZE],
+    pub width: i32,
+    pub height: i32,
+    pub elements: Vec<FieldElement>,
+
+    // 爆発している場所と、爆弾によっていずれ爆発する場所の最大のリスク値
+    pub risk_of_explosion: i32,
+    pub risk_of_bomb: i32,
+
+    // 置かれている爆弾（誘爆を計算するために覚えておく）
+    bombs: Vec<FieldBomb>,
+}
+
+// AI は爆弾を置いた場合のマップを何度も作り直すので、clone_from では確保済みのメモリを使い回す
+impl Clone for FieldBomb {
+    fn clone(&self) -> Self {
+        FieldBomb {
+            position: self.position,
+            path: self.path.clone(),
+            cells: self.cells.clone(),
+            hits: self.hits.clone(),
+            explode_frame: self.explode_frame,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.position = source.position;
+        self.path.clone_from(&source.path);
+        self.cells.clone_from(&source.cells);
+        self.hits.clone_from(&source.hits);
+        self.explode_frame = source.explode_frame;
+    }
+}
+
+impl Clone for Field {
+    fn clone(&self) -> Self {
+        Field {
+            width: self.width,
+            height: self.height,
+            elements: self.elements.clone(),
+            risk_of_explosion: self.risk_of_explosion,
+            risk_of_bomb: self.risk_of_bomb,
+            bombs: self.bombs.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.width = source.width;
+        self.height = source.height;
+        self.elements.clone_from(&source.elements);
+        self.risk_of_explosion = source.risk_of_explosion;
+        self.risk_of_bomb = source.risk_of_bomb;
+        self.bombs.clone_from(&source.bombs);
+    }
 }
 
 impl Field {
-    pub fn new() -> Field {
-        let e = FieldElement::new(0, 0, FieldObject::None);
-        let mut field = Field { elements: [e; ELEMENT_SIZE] };
-        for i in 0..ELEMENT_SIZE {
-            field.elements[i].x = i as i32 % screen::MAP_WIDTH;
-            field.elements[i].y = i as i32 / screen::MAP_WIDTH;
+    pub fn new(width: i32, height: i32) -> Field {
+        let elements = (0..width * height)
+            .map(|i| FieldElement::new(i % width, i / width, FieldObject::None))
+            .collect();
+        Field {
+            width,
+            height,
+            elements,
+            risk_of_explosion: ai_constants::RISK_OF_EXPLOSION,
+            risk_of_bomb: ai_constants::RISK_OF_BOMB,
+            bombs: Vec::new(),
         }
-        field
     }
 
-    pub fn from_game_screen(game_screen: &GameScreen) -> Field {
-        let mut field = Self::new();
+    /// ゲームの状態からマップを作る（リスク値は AI の性格に合わせる）
+    pub fn from_world(world: &World, profile: &AIProfile) -> Field {
+        let mut field = Self::new(world.stage.width, world.stage.height);
+        field.risk_of_explosion = profile.risk_of_explosion;
+        field.risk_of_bomb = profile.risk_of_bomb;
 
         // 壁をマップに追加
-        for wall in game_screen.walls.iter() {
+        for wall in world.walls.iter() {
             let x = wall.get_x() / constants::CHARACTER_SIZE;
             let y = wall.get_y() / constants::CHARACTER_SIZE;
             let e = field.get_element_mut(x, y);
@@ -37,43 +109,58 @@ impl Field {
             e.will_broken = wall.is_melting();
         }
         // パワーアップアイテムをマップに追加
-        for item in game_screen.power_up_items.iter() {
+        for item in world.power_up_items.iter() {
             let x = item.get_x() / constants::CHARACTER_SIZE;
             let y = item.get_y() / constants::CHARACTER_SIZE;
-            field.get_element_mut(x, y).field_object = FieldObject::PowerUpItem;
+            field.get_element_mut(x, y).field_object = FieldObject::PowerUpItem(item.kind);
         }
         // 爆弾をマップに追加
-        for bomb in game_screen.bombs.iter() {
+        for bomb in world.bombs.iter() {
             field.add_bomb(bomb);
         }
         // 爆発を危険領域としてマップに追加
-        for explosion in game_screen.explosions.iter() {
+        for explosion in world.explosions.iter() {
             let x = explosion.get_x() / constants::CHARACTER_SIZE;
             let y = explosion.get_y() / constants::CHARACTER_SIZE;
-            field.get_element_mut(x, y).risk = ai_constants::RISK_OF_EXPLOSION;
+            let risk = field.risk_of_explosion;
+            let element = field.get_element_mut(x, y);
+            element.risk = risk;
+            element.add_explosion(explosion.remain_time);
         }
 
         field
     }
 
+    /// elements の中での位置
+    pub fn index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+
     pub fn get_element(&self, x: i32, y: i32) -> &FieldElement {
-        self.elements.get((y * screen::MAP_WIDTH + x) as usize).unwrap()
+        self.elements.get(self.index(x, y)).unwrap()
     }
 
     pub fn get_element_mut(&mut self, x: i32, y: i32) -> &mut FieldElement {
-        self.elements.get_mut((y * screen::MAP_WIDTH + x) as usize).unwrap()
+        let index = self.index(x, y);
+        self.elements.get_mut(index).unwrap()
     }
 
     /// 指定位置からリスクのない場所へ移動できるか確認する
-    pub fn check_if_escapable(&self, x: i32, y: i32, opponent_x: i32, opponent_y: i32) -> bool {
-        let mut checked = [false; ELEMENT_SIZE];
-        let mut search_queue = Vec::<&FieldElement>::new();
-        search_queue.push(self.get_element(x, y));
-        checked[(x + y * screen::MAP_WIDTH) as usize] = true;
-        while !search_queue.is_empty() {
-            let element = search_queue.remove(0);
+    /// （start_frame フレーム後に指定位置から1マスを frames_per_tile フレームで移動し始め、炎が上がっている間のマスは通らない）
+    pub fn check_if_escapable(&self, x: i32, y: i32, opponent_x: i32, opponent_y: i32, start_frame: i32, frames_per_tile: i32) -> bool {
+        // 指定位置に着くまでか、離れるまでに炎が上がる場合は逃げられない
+        if self.get_element(x, y).is_on_fire_between(0, start_frame + frames_per_tile) {
+            return false;
+        }
+        let mut checked = vec![false; self.elements.len()];
+        let mut search_queue = VecDeque::<(&FieldElement, i32)>::new();
+        search_queue.push_back((self.get_element(x, y), start_frame));
+        checked[self.index(x, y)] = true;
+        while let Some((element, frame)) = search_queue.pop_front() {
             let ex = element.x;
             let ey = element.y;
+            // 隣のマスに入ってから移り終わるまでの間
+            let next_frame = frame + frames_per_tile;
             let next_elements = [
                 self.get_element(ex - 1, ey),
                 self.get_element(ex + 1, ey),
@@ -81,41 +168,171 @@ impl Field {
                 self.get_element(ex, ey + 1),
             ];
             for next_element in next_elements {
-                let idx = (next_element.x + next_element.y * screen::MAP_WIDTH) as usize;
-                if !checked[idx] && next_element.is_passable() {
-                    if next_element.x != opponent_x || next_element.y != opponent_y {
-                        if next_element.risk == 0 {
-                            return true;
-                        }
-                        search_queue.push(next_element);
-                        checked[idx] = true;
+                let idx = self.index(next_element.x, next_element.y);
+                if !checked[idx] && next_element.is_passable() && (next_element.x != opponent_x || next_element.y != opponent_y) &&
+                    !next_element.is_on_fire_between(frame, next_frame) {
+                    if next_element.risk == 0 {
+                        return true;
                     }
+                    search_queue.push_back((next_element, next_frame));
+                    checked[idx] = true;
                 }
             }
         }
         false
     }
 
-    /// 爆弾を配置する
+    /// 爆弾を配置する（戻り値はこの爆弾で壊せる壁の数）
     pub fn add_bomb(&mut self, bomb: &Bomb) -> i32 {
-        let x = bomb.get_x() / constants::CHARACTER_SIZE;
-        let y = bomb.get_y() / constants::CHARACTER_SIZE;
-        let element = self.get_element_mut(x, y);
-        element.field_object = FieldObject::Bomb;
-        // この爆弾のリスク
-        let risk = (constants::BOMB_TIME - bomb.remain_time) * (ai_constants::RISK_OF_BOMB * 9 / 10) / constants::BOMB_TIME + (ai_constants::RISK_OF_BOMB / 10);
-        // リスクのセット
-        element.risk = risk;
-        let break_count = [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().filter(|it|
-            self.add_bomb_risk_to_field_map(bomb, risk, it.0, it.1)
-        ).count();
-        return break_count as i32
-    }
-
-    /// 爆弾が爆発したときのリスクを計算する
-    fn add_bomb_risk_to_field_map(&mut self, bomb: &Bomb, risk: i32, xx: i32, yy: i32) -> bool {
-        let x = bomb.get_x() / constants::CHARACTER_SIZE;
-        let y = bomb.get_y() / constants::CHARACTER_SIZE;
+        // 滑っている爆弾は、爆発するまでに通るマスにもリスクをセットし、爆発する位置に置かれているものとして扱う
+        let path = self.predict_bomb_path(bomb);
+        let (x, y) = *path.last().unwrap();
+        let mut hit_positions = Vec::new();
+        let cells = self.explosion_reach(x, y, bomb.power, bomb.pierce, &mut hit_positions);
+        self.get_element_mut(x, y).field_object = FieldObject::Bomb;
+
+        // 炎が届く爆弾どうしをつなぐ（先に置いた爆弾の炎の範囲は、この爆弾で炎が止まることを考えずにそのままにする）
+        let index = self.bombs.len();
+        let hits = self.bombs.iter().enumerate()
+            .filter(|(_, other)| hit_positions.contains(&other.position))
+            .map(|(i, _)| i)
+            .collect();
+        for other in self.bombs.iter_mut() {
+            if other.cells.contains(&(x, y)) {
+                other.hits.push(index);
+            }
+        }
+        let remain_time = if bomb.remote { None } else { Some(bomb.remain_time) };
+        self.bombs.push(FieldBomb {
+            position: (x, y),
+            path,
+            cells,
+            hits,
+            explode_frame: remain_time,
+        });
+
+        // 誘爆で爆発が早まった爆弾と、この爆弾のリスクと炎が上がる期間をセットする
+        let mut changed = vec![false; self.bombs.len()];
+        changed[index] = true;
+        self.propagate_chain_reaction(&mut changed);
+        for (i, changed) in changed.iter().enumerate() {
+            if *changed {
+                self.apply_bomb_risk(i);
+            }
+        }
+        self.apply_bomb_fire(&changed);
+        [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().map(|it|
+            self.mark_walls_to_break(bomb, x, y, it.0, it.1)
+        ).sum()
+    }
+
+    /// 爆発した爆弾の炎が届いた爆弾は次のフレームで爆発するので、爆発するフレームを早める（早まった爆弾は changed を true にする）
+    fn propagate_chain_reaction(&mut self, changed: &mut [bool]) {
+        let mut updated = true;
+        while updated {
+            updated = false;
+            for i in 0..self.bombs.len() {
+                let Some(frame) = self.bombs[i].explode_frame else { continue };
+                for k in 0..self.bombs[i].hits.len() {
+                    let j = self.bombs[i].hits[k];
+                    if self.bombs[j].explode_frame.is_none_or(|other| other > frame + 1) {
+                        self.bombs[j].explode_frame = Some(frame + 1);
+                        changed[j] = true;
+                        updated = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 爆弾の炎が届くマスにリスクをセットする（誘爆しないリモコン爆弾はいつ爆発するかわからないので、リスクを最大にする）
+    fn apply_bomb_risk(&mut self, index: usize) {
+        let bomb = &self.bombs[index];
+        let risk = match bomb.explode_frame {
+            Some(frame) => (constants::BOMB_TIME - frame.min(constants::BOMB_TIME)) * (self.risk_of_bomb * 9 / 10) / constants::BOMB_TIME + (self.risk_of_bomb / 10),
+            None => self.risk_of_bomb,
+        };
+        for &(px, py) in bomb.path.iter().chain(bomb.cells.iter()) {
+            let element = &mut self.elements[(py * self.width + px) as usize];
+            if risk > element.risk {
+                element.risk = risk;
+            }
+        }
+    }
+
+    /// 爆発するフレームが決まった（早まった）爆弾の炎が届くマスの、炎が上がる期間をセットし直す
+    /// （古い期間を残さないように、そのマスは一度消してからすべての爆弾の期間を追加する。誘爆しないリモコン爆弾の期間はセットしない）
+    fn apply_bomb_fire(&mut self, changed: &[bool]) {
+        for (bomb, _) in self.bombs.iter().zip(changed).filter(|(_, changed)| **changed) {
+            for &(px, py) in bomb.cells.iter() {
+                self.elements[(py * self.width + px) as usize].clear_bomb_fire();
+            }
+        }
+        // 消さなかったマスでは、追加する期間は今ある期間に含まれているので変わらない
+        for bomb in self.bombs.iter() {
+            let Some(frame) = bomb.explode_frame else { continue };
+            for &(px, py) in bomb.cells.iter() {
+                self.elements[(py * self.width + px) as usize].add_fire(frame, frame + constants::EXPLOSION_TIME);
+            }
+        }
+    }
+
+    /// 指定位置で爆発が起きたときに炎が届くマス（壁や爆弾、アイテムがあるとそこで止まる。貫通する場合は壊せる壁を通り抜ける）
+    pub fn explosion_cells(&self, x: i32, y: i32, power: i32, pierce: bool) -> Vec<(i32, i32)> {
+        self.explosion_reach(x, y, power, pierce, &mut Vec::new())
+    }
+
+    /// 炎が届くマス（炎が届いて誘爆する爆弾の位置を hit_bombs に追加する）
+    fn explosion_reach(&self, x: i32, y: i32, power: i32, pierce: bool, hit_bombs: &mut Vec<(i32, i32)>) -> Vec<(i32, i32)> {
+        let mut cells = vec![(x, y)];
+        for (xx, yy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            for i in 1..(power + 1) {
+                let px = x + xx * i;
+                let py = y + yy * i;
+                match self.get_element(px, py).field_object {
+                    FieldObject::None => cells.push((px, py)),
+                    FieldObject::BreakableWall if pierce => (),
+                    FieldObject::Bomb => {
+                        hit_bombs.push((px, py));
+                        break;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        cells
+    }
+
+    /// 爆弾が爆発するまでに通るマスを予測する（最後の要素が爆発する位置）
+    fn predict_bomb_path(&self, bomb: &Bomb) -> Vec<(i32, i32)> {
+        let mut path = vec![(bomb.get_tile_x() / constants::CHARACTER_SIZE, bomb.get_tile_y() / constants::CHARACTER_SIZE)];
+        if !bomb.is_sliding() {
+            return path;
+        }
+        // 1フレームずつ動かしてみる（マスの境目で次のマスに何かあれば止まる）
+        let (dx, dy) = bomb.slide.delta();
+        let mut px = bomb.get_x();
+        let mut py = bomb.get_y();
+        for _ in 0..bomb.remain_time {
+            if px % constants::CHARACTER_SIZE == 0 && py % constants::CHARACTER_SIZE == 0 {
+                let next = self.get_element(px / constants::CHARACTER_SIZE + dx, py / constants::CHARACTER_SIZE + dy);
+                if !matches!(next.field_object, FieldObject::None) {
+                    break;
+                }
+            }
+            px += dx * bomb::SLIDE_SPEED;
+            py += dy * bomb::SLIDE_SPEED;
+            let tile = ((px + constants::CHARACTER_SIZE / 2) / constants::CHARACTER_SIZE, (py + constants::CHARACTER_SIZE / 2) / constants::CHARACTER_SIZE);
+            if path.last() != Some(&tile) {
+                path.push(tile);
+            }
+        }
+        path
+    }
+
+    /// 爆弾が爆発したときに壊れる壁に印をつける（戻り値はこの方向で壊せる壁の数）
+    fn mark_walls_to_break(&mut self, bomb: &Bomb, x: i32, y: i32, xx: i32, yy: i32) -> i32 {
+        let mut break_count = 0;
         for i in 1..(bomb.power + 1) {
             let px = x + xx * i;
             let py = y + yy * i;
@@ -124,19 +341,122 @@ impl Field {
                 FieldObject::BreakableWall => {
                     if !field_element.will_broken {
                         field_element.will_broken = true;
-                        return true;
-                    }
-                }
-                FieldObject::None => {
-                    if risk > field_element.risk {
-                        field_element.risk = risk;
+                        break_count += 1;
+                        // 貫通する爆弾は範囲内の壁をすべて壊す
+                        if !bomb.pierce {
+                            return break_count;
+                        }
                     }
                 }
+                FieldObject::None => (),
                 _ => {
-                    return false;
+                    return break_count;
                 }
             }
         }
-        return false
+        break_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 外周と指定したマスが壁の、何もないマップ
+    fn field_with_walls(width: i32, height: i32, walls: &[(i32, i32, FieldObject)]) -> Field {
+        let mut field = Field::new(width, height);
+        for element in field.elements.iter_mut() {
+            if element.x == 0 || element.y == 0 || element.x == width - 1 || element.y == height - 1 {
+                element.field_object = FieldObject::UnbreakableWall;
+            }
+        }
+        for &(x, y, field_object) in walls {
+            field.get_element_mut(x, y).field_object = field_object;
+        }
+        field
+    }
+
+    fn bomb(x: i32, y: i32, power: i32, remain_time: i32) -> Bomb {
+        let mut bomb = Bomb::new(x * constants::CHARACTER_SIZE, y * constants::CHARACTER_SIZE, power, 0);
+        bomb.remain_time = remain_time;
+        bomb
     }
-}
\ No newline at end of file
+
+    fn fire(frame: i32) -> (i32, i32) {
+        (frame, frame + constants::EXPLOSION_TIME)
+    }
+
+    #[test]
+    fn chain_reaction_brings_fire_windows_forward() {
+        // 爆発が遅い順に置いても、先に爆発する爆弾からの誘爆で期間が早まり、古い期間は残らない
+        let mut field = field_with_walls(9, 5, &[]);
+        field.add_bomb(&bomb(6, 2, 1, 250));
+        field.add_bomb(&bomb(4, 2, 2, 200));
+        field.add_bomb(&bomb(2, 2, 2, 100));
+
+        assert_eq!(field.get_element(1, 2).fire_windows(), [fire(100)]);
+        assert_eq!(field.get_element(3, 2).fire_windows(), [(100, 101 + constants::EXPLOSION_TIME)]);
+        assert_eq!(field.get_element(4, 1).fire_windows(), [fire(101)]);
+        assert_eq!(field.get_element(5, 2).fire_windows(), [(101, 102 + constants::EXPLOSION_TIME)]);
+        assert_eq!(field.get_element(6, 2).fire_windows(), [fire(102)]);
+        assert_eq!(field.get_element(7, 2).fire_windows(), [fire(102)]);
+        assert!(!field.get_element(7, 2).is_on_fire_between(200, 300));
+    }
+
+    #[test]
+    fn pierce_bomb_chains_through_soft_walls() {
+        let walls = [(2, 2, FieldObject::BreakableWall), (3, 2, FieldObject::BreakableWall)];
+
+        let mut field = field_with_walls(9, 5, &walls);
+        field.add_bomb(&bomb(5, 2, 1, 250));
+        let mut pierce_bomb = bomb(1, 2, 4, 60);
+        pierce_bomb.pierce = true;
+        assert_eq!(field.add_bomb(&pierce_bomb), 2);
+        assert_eq!(field.get_element(4, 2).fire_windows(), [(60, 61 + constants::EXPLOSION_TIME)]);
+        assert_eq!(field.get_element(6, 2).fire_windows(), [fire(61)]);
+        assert_eq!(field.get_element(5, 1).fire_windows(), [fire(61)]);
+
+        // 貫通しない爆弾の炎は壁で止まるので誘爆しない
+        let mut field = field_with_walls(9, 5, &walls);
+        field.add_bomb(&bomb(5, 2, 1, 250));
+        assert_eq!(field.add_bomb(&bomb(1, 2, 4, 60)), 1);
+        assert_eq!(field.get_element(4, 2).fire_windows(), [fire(250)]);
+        assert_eq!(field.get_element(6, 2).fire_windows(), [fire(250)]);
+    }
+
+    #[test]
+    fn bomb_four_seconds_away_can_explode_soon_by_chain() {
+        // 横一列の通路（x = 1〜9）。4秒後に爆発する爆弾の横にいて、右の端まで逃げるには3マス歩く
+        let walls: Vec<(i32, i32, FieldObject)> = (1..10)
+            .flat_map(|x| [(x, 1, FieldObject::UnbreakableWall), (x, 3, FieldObject::UnbreakableWall)])
+            .collect();
+        let frames_per_tile = 16;
+
+        let mut field = field_with_walls(11, 5, &walls);
+        field.add_bomb(&bomb(4, 2, 3, 240));
+        assert_eq!(field.get_element(5, 2).fire_windows(), [fire(240)]);
+        assert!(field.check_if_escapable(5, 2, 0, 0, 0, frames_per_tile));
+
+        // 30フレーム後に爆発する爆弾の炎が届くと、逃げ切る前に誘爆する
+        field.add_bomb(&bomb(2, 2, 2, 30));
+        assert_eq!(field.get_element(5, 2).fire_windows(), [fire(31)]);
+        assert_eq!(field.get_element(7, 2).fire_windows(), [fire(31)]);
+        assert!(!field.check_if_escapable(5, 2, 0, 0, 0, frames_per_tile));
+        // 着く前に燃えてしまうマスからは逃げられない
+        assert!(!field.check_if_escapable(7, 2, 0, 0, 40, frames_per_tile));
+        assert!(field.check_if_escapable(7, 2, 0, 0, 0, frames_per_tile));
+    }
+
+    #[test]
+    fn separate_fire_windows_are_kept_apart() {
+        let mut field = field_with_walls(9, 5, &[]);
+        field.add_bomb(&bomb(4, 2, 1, 300));
+        field.get_element_mut(4, 2).add_explosion(constants::EXPLOSION_TIME);
+
+        let element = field.get_element(4, 2);
+        assert_eq!(element.fire_windows(), [fire(0), fire(300)]);
+        assert!(element.is_on_fire_between(10, 20));
+        assert!(!element.is_on_fire_between(40, 290));
+        assert!(element.is_on_fire_between(290, 310));
+    }
+}
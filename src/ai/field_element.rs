@@ -1,6 +1,8 @@
+use crate::game::power_up_item::PowerUpKind;
+
 #[derive(Copy, Clone)]
 pub enum FieldObject {
-    None, UnbreakableWall, BreakableWall, Bomb, PowerUpItem
+    None, UnbreakableWall, BreakableWall, Bomb, PowerUpItem(PowerUpKind)
 }
 
 #[derive(Copy, Clone)]
@@ -24,8 +26,18 @@ pub struct FieldElement {
 
     // いずれ壊されることが確定している場合true
     pub will_broken: bool,
+
+    // 今燃えている爆発の炎が消えるまでのフレーム数（燃えていなければ 0）
+    pub explosion_remain_time: i32,
+
+    // 炎が上がる期間（今を 0 とするフレーム数の開始と終了。重ならないように早い順に fire_window_count 個並べる）
+    fire_windows: [(i32, i32); MAX_FIRE_WINDOWS],
+    fire_window_count: u8,
 }
 
+// 1つのマスで覚えておく炎が上がる期間の最大数（これより多くなる場合は間の一番短い期間どうしをつなげる）
+pub const MAX_FIRE_WINDOWS: usize = 4;
+
 impl FieldElement {
     pub fn new(x: i32, y: i32, field_object: FieldObject) -> FieldElement {
         FieldElement {
@@ -38,13 +50,75 @@ impl FieldElement {
             previous_element_x: -1,
             previous_element_y: -1,
             will_broken: false,
+            explosion_remain_time: 0,
+            fire_windows: [(0, 0); MAX_FIRE_WINDOWS],
+            fire_window_count: 0,
         }
     }
 
-    pub fn is_passable(&self) -> bool {
-        match self.field_object {
-            FieldObject::None | FieldObject::PowerUpItem => true,
-            _ => false,
+    /// 炎が上がる期間（開始の早い順）
+    pub fn fire_windows(&self) -> &[(i32, i32)] {
+        &self.fire_windows[..self.fire_window_count as usize]
+    }
+
+    /// 指定した期間（from〜to のフレーム）に炎が上がっているか
+    pub fn is_on_fire_between(&self, from: i32, to: i32) -> bool {
+        self.fire_windows().iter().any(|&(start, end)| start <= to && from <= end)
+    }
+
+    /// 今燃えている爆発の炎を追加する
+    pub fn add_explosion(&mut self, remain_time: i32) {
+        self.explosion_remain_time = self.explosion_remain_time.max(remain_time);
+        self.add_fire(0, remain_time);
+    }
+
+    /// 炎が上がる期間を追加する（重なる期間とはつなげる）
+    pub fn add_fire(&mut self, start: i32, end: i32) {
+        let (mut start, mut end) = (start, end);
+        let mut windows = [(0, 0); MAX_FIRE_WINDOWS + 1];
+        let mut count = 0;
+        let mut inserted = false;
+        for &(window_start, window_end) in self.fire_windows() {
+            if window_end < start {
+                windows[count] = (window_start, window_end);
+                count += 1;
+            } else if end < window_start {
+                if !inserted {
+                    windows[count] = (start, end);
+                    count += 1;
+                    inserted = true;
+                }
+                windows[count] = (window_start, window_end);
+                count += 1;
+            } else {
+                start = start.min(window_start);
+                end = end.max(window_end);
+            }
+        }
+        if !inserted {
+            windows[count] = (start, end);
+            count += 1;
+        }
+        // 覚えきれない場合は、燃えていないと判断する期間が短くなるほうの隣どうしをつなげる
+        if count > MAX_FIRE_WINDOWS {
+            let i = (1..count).min_by_key(|i| windows[*i].0 - windows[*i - 1].1).unwrap();
+            windows[i - 1].1 = windows[i].1;
+            windows.copy_within(i + 1..count, i);
+            count -= 1;
         }
+        self.fire_windows[..count].copy_from_slice(&windows[..count]);
+        self.fire_window_count = count as u8;
+    }
+
+    /// 爆弾による炎が上がる期間を消す（今燃えている爆発の炎は残す）
+    pub fn clear_bomb_fire(&mut self) {
+        self.fire_window_count = 0;
+        if self.explosion_remain_time > 0 {
+            self.add_fire(0, self.explosion_remain_time);
+        }
+    }
+
+    pub fn is_passable(&self) -> bool {
+        matches!(self.field_object, FieldObject::None | FieldObject::PowerUpItem(_))
     }
 }
\ No newline at end of file
@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+use crate::game::player_input::{PlayerInput, Movement};
+
+use super::{
+    ai_constants,
+    ai_player::AIPlayer,
+    ai_profile::AIProfile,
+    field::Field,
+    strategy::{self, AIStrategy, GameView},
+};
+
+/// 対戦相手を追いかけて爆弾で追い詰める AI
+///
+/// 爆弾を置く場所や逃げ方は AIPlayer と同じように決めるが、対戦相手へのいやがらせを重視し、
+/// することがなくて立ち止まる場合は安全なマスを通って対戦相手に近づく。
+pub struct HunterPlayer {
+    ai_player: AIPlayer,
+    profile: AIProfile,
+}
+
+impl Default for HunterPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HunterPlayer {
+    pub fn new() -> HunterPlayer {
+        Self::with_profile(AIProfile::default())
+    }
+
+    /// 性格を指定して AI を作る（対戦相手のストレス度に対するスコアの重みは HUNTER_OPPONENT_STRESS_WEIGHT_SCALE 倍にする）
+    pub fn with_profile(profile: AIProfile) -> HunterPlayer {
+        let hunter_profile = AIProfile {
+            opponent_stress_weight: profile.opponent_stress_weight * ai_constants::HUNTER_OPPONENT_STRESS_WEIGHT_SCALE,
+            ..profile
+        };
+        HunterPlayer {
+            ai_player: AIPlayer::with_profile(hunter_profile),
+            profile: hunter_profile,
+        }
+    }
+
+    /// 安全なマスだけを通って、対戦相手に最も近づけるマスへ向かう
+    fn chase(&self, view: &GameView) -> Movement {
+        let field = Field::from_world(view.world, &self.profile);
+        let me = view.me();
+        let (my_x, my_y) = GameView::tile_of(me);
+        let (opponent_x, opponent_y) = GameView::tile_of(view.nearest_opponent());
+        if field.get_element(my_x, my_y).risk > 0 {
+            return Movement::NONE;
+        }
+
+        // 広さ優先で、対戦相手とのマンハッタン距離が最も小さいマスを探す（同じなら近いマス）
+        let mut previous: Vec<Option<(i32, i32)>> = vec![None; field.elements.len()];
+        let mut checked = vec![false; field.elements.len()];
+        let mut search_queue = VecDeque::new();
+        search_queue.push_back((my_x, my_y));
+        checked[field.index(my_x, my_y)] = true;
+        let mut best = (my_x, my_y);
+        let mut best_distance = (opponent_x - my_x).abs() + (opponent_y - my_y).abs();
+        while let Some((x, y)) = search_queue.pop_front() {
+            let distance = (opponent_x - x).abs() + (opponent_y - y).abs();
+            if distance < best_distance {
+                best = (x, y);
+                best_distance = distance;
+            }
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                let element = field.get_element(nx, ny);
+                let index = field.index(nx, ny);
+                if checked[index] || !element.is_passable() || element.risk > 0 || (nx, ny) == (opponent_x, opponent_y) {
+                    continue;
+                }
+                checked[index] = true;
+                previous[index] = Some((x, y));
+                search_queue.push_back((nx, ny));
+            }
+        }
+
+        // 目的地への経路のうち、現在地の次のマスへ向かう
+        let mut next = best;
+        while let Some(position) = previous[field.index(next.0, next.1)] {
+            if position == (my_x, my_y) {
+                break;
+            }
+            next = position;
+        }
+        strategy::movement_toward(me, next.0, next.1)
+    }
+}
+
+impl AIStrategy for HunterPlayer {
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        let mut player_input = AIStrategy::get_player_input(&mut self.ai_player, view);
+        if let (Movement::NONE, false) = (player_input.movement, player_input.fire) {
+            player_input.movement = self.chase(view);
+        }
+        player_input
+    }
+}
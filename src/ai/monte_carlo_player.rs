@@ -0,0 +1,276 @@
+use std::time::{Duration, Instant};
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::game::{
+    constants,
+    light_sprite::LightSprite,
+    player::Player,
+    player_input::{PlayerInput, Movement},
+    world::{State, World},
+};
+
+use super::{
+    ai_constants,
+    ai_player::AIPlayer,
+    ai_profile::AIProfile,
+    field::Field,
+    strategy::{AIStrategy, GameView},
+};
+
+/// 行動の候補ごとにゲームの続きを何度もシミュレーションして、生き残りやすく対戦相手を倒しやすい行動を選ぶ AI
+///
+/// マスにぴったり合った位置に着くたびに判断する。候補の評価が同じくらいなら標準の AI（AIPlayer）の入力を使うので、
+/// ふだんは標準の AI と同じように動き、追い詰められる行動だけを避ける。
+pub struct MonteCarloPlayer {
+    base: AIPlayer,
+    profile: AIProfile,
+    // 試合のシードとプレイヤー番号から作る乱数生成器（最初の入力のときに作る）
+    rng: Option<StdRng>,
+    // 最後に選んだ移動方向（マスの途中ではこの方向に進み続ける）
+    movement: Movement,
+}
+
+/// シミュレーションの中で各プレイヤーを動かす簡単な方策
+struct Playout {
+    // プレイヤーごとの移動方向
+    movements: Vec<Movement>,
+}
+
+impl MonteCarloPlayer {
+    pub fn with_profile(profile: AIProfile) -> MonteCarloPlayer {
+        MonteCarloPlayer {
+            base: AIPlayer::with_profile(profile),
+            profile,
+            rng: None,
+            movement: Movement::NONE,
+        }
+    }
+
+    /// 今いるマスで選べる行動（通れない方向への移動と、置けない爆弾を除く）
+    fn candidates(world: &World, player_number: usize) -> Vec<PlayerInput> {
+        let me = &world.players[player_number];
+        let field = Field::from_world(world, &AIProfile::default());
+        let (x, y) = GameView::tile_of(me);
+        let can_fire = (world.rule.unlimited_bombs || world.bombs.iter().filter(|bomb| bomb.owner == player_number).count() < me.bomb_capacity as usize) &&
+            !world.bombs.iter().any(|bomb| bomb.get_tile_x() / constants::CHARACTER_SIZE == x && bomb.get_tile_y() / constants::CHARACTER_SIZE == y);
+        let mut candidates = Vec::new();
+        for movement in Movement::ALL {
+            let (dx, dy) = movement.delta();
+            if (dx, dy) != (0, 0) && !field.get_element(x + dx, y + dy).is_passable() {
+                continue;
+            }
+            candidates.push(PlayerInput::new(movement, false));
+            if can_fire {
+                candidates.push(PlayerInput::new(movement, true));
+            }
+        }
+        candidates
+    }
+
+    /// 候補の行動をとった後のゲームの続きをシミュレーションし、評価値（生き残れば 1、倒した対戦相手1人につき加点）を返す
+    ///
+    /// 途中で期限を過ぎた場合は None を返す。
+    fn rollout(&self, world: &World, player_number: usize, candidate: PlayerInput, deadline: Option<Instant>, rng: &mut StdRng) -> Option<f64> {
+        let mut world = world.clone_for_simulation(rng.gen());
+        let mut playout = Playout { movements: vec![Movement::NONE; world.players.len()] };
+        // 候補の移動は次のマスに着くまで続ける
+        let mut committed = candidate.movement != Movement::NONE;
+        for frame in 0..self.profile.monte_carlo_rollout_frames {
+            if frame % ai_constants::MONTE_CARLO_DEADLINE_CHECK_FRAMES == 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return None;
+            }
+            if !matches!(world.state, State::Playing) || world.players[player_number].is_dead() {
+                break;
+            }
+            // 対戦相手が新しく爆弾を置かなくなった後、爆弾も爆発も残っていなければこの先は安全とみなす
+            if frame >= ai_constants::MONTE_CARLO_OPPONENT_FIRE_FRAMES && world.bombs.is_empty() && world.explosions.is_empty() {
+                break;
+            }
+            let mut player_inputs = vec![PlayerInput::new(Movement::NONE, false); world.players.len()];
+            for (i, player_input) in player_inputs.iter_mut().enumerate() {
+                if world.players[i].is_dead() {
+                    continue;
+                }
+                *player_input = if i == player_number {
+                    if frame == 0 {
+                        candidate
+                    } else if committed && !Self::is_aligned(&world.players[i]) {
+                        PlayerInput::new(candidate.movement, false)
+                    } else {
+                        committed = false;
+                        playout.next_input(&world, i, false, rng)
+                    }
+                } else {
+                    playout.next_input(&world, i, frame < ai_constants::MONTE_CARLO_OPPONENT_FIRE_FRAMES, rng)
+                };
+            }
+            world.on_next_frame(&player_inputs);
+        }
+
+        if world.players[player_number].is_dead() {
+            return Some(0.0);
+        }
+        let kills = world.players.iter().filter(|player| player.player_number != player_number && player.killed_by == Some(player_number)).count();
+        Some(1.0 + kills as f64 * ai_constants::MONTE_CARLO_KILL_SCORE)
+    }
+
+    fn is_aligned(player: &Player) -> bool {
+        player.get_x() % constants::CHARACTER_SIZE == 0 && player.get_y() % constants::CHARACTER_SIZE == 0
+    }
+}
+
+impl Playout {
+    /// マスにぴったり合った位置に着くたびに、通れて爆弾の炎が届かないマスを優先してでたらめに次の方向を選ぶ
+    fn next_input(&mut self, world: &World, player_number: usize, can_fire: bool, rng: &mut StdRng) -> PlayerInput {
+        let player = &world.players[player_number];
+        if !MonteCarloPlayer::is_aligned(player) {
+            return PlayerInput::new(self.movements[player_number], false);
+        }
+        let (x, y) = GameView::tile_of(player);
+        let movements: Vec<(Movement, bool)> = Movement::ALL.iter()
+            .map(|movement| (*movement, movement.delta()))
+            .filter(|(movement, (dx, dy))| *movement == Movement::NONE || Self::is_passable(world, x + dx, y + dy))
+            .map(|(movement, (dx, dy))| (movement, Self::is_safe(world, x + dx, y + dy)))
+            .collect();
+        let safe: Vec<Movement> = movements.iter().filter(|(_, safe)| *safe).map(|(movement, _)| *movement).collect();
+        let choices: Vec<Movement> = if safe.is_empty() { movements.iter().map(|(movement, _)| *movement).collect() } else { safe };
+        self.movements[player_number] = choices[rng.gen_range(0, choices.len())];
+        let fire = can_fire && rng.gen_range(0, 100) < ai_constants::MONTE_CARLO_OPPONENT_FIRE_PROBABILITY;
+        PlayerInput::new(self.movements[player_number], fire)
+    }
+
+    /// 壁も爆弾もないマス
+    fn is_passable(world: &World, x: i32, y: i32) -> bool {
+        let tile = |sprite_x: i32, sprite_y: i32| sprite_x / constants::CHARACTER_SIZE == x && sprite_y / constants::CHARACTER_SIZE == y;
+        !world.walls.iter().any(|wall| tile(wall.get_x(), wall.get_y())) &&
+            !world.bombs.iter().any(|bomb| tile(bomb.get_tile_x(), bomb.get_tile_y()))
+    }
+
+    /// 爆発しておらず、どの爆弾の炎も届かないマス（壁で炎が止まることは考えない）
+    fn is_safe(world: &World, x: i32, y: i32) -> bool {
+        let tile = |sprite_x: i32| sprite_x / constants::CHARACTER_SIZE;
+        !world.explosions.iter().any(|explosion| tile(explosion.get_x()) == x && tile(explosion.get_y()) == y) &&
+            !world.bombs.iter().any(|bomb| {
+                let (bx, by) = (tile(bomb.get_tile_x()), tile(bomb.get_tile_y()));
+                (bx == x && (by - y).abs() <= bomb.power) || (by == y && (bx - x).abs() <= bomb.power)
+            })
+    }
+}
+
+impl AIStrategy for MonteCarloPlayer {
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        // 時間の上限は標準の AI の処理も含めて1フレームあたりで数える（0 なら回数だけで止める）
+        let deadline = match self.profile.monte_carlo_time_budget_millis {
+            0 => None,
+            millis => Some(Instant::now() + Duration::from_millis(millis as u64)),
+        };
+        // 標準の AI は毎フレームの状態を使うので、判断しないフレームでも呼ぶ
+        let proposal = AIStrategy::get_player_input(&mut self.base, view);
+        let me = view.me();
+        if !Self::is_aligned(me) {
+            return PlayerInput { movement: self.movement, fire: false, detonate: proposal.detonate };
+        }
+
+        // 時間切れで途中までしか調べられなくても比べられるように、標準の AI の入力を最初に調べる
+        let mut candidates = Self::candidates(view.world, view.player_number);
+        candidates.sort_by_key(|candidate| !(candidate.movement == proposal.movement && candidate.fire == proposal.fire));
+        let mut rng = self.rng.take().unwrap_or_else(|| StdRng::seed_from_u64(view.world.seed.wrapping_add(view.player_number as u64)));
+        let mut scores = vec![0.0; candidates.len()];
+        let mut counts = vec![0; candidates.len()];
+        let mut rollouts = 0;
+        // 回数の上限に達するまで、すべての候補を同じ回数ずつシミュレーションする（時間切れになったらそこまでの結果を使う）
+        'simulation: while rollouts < self.profile.monte_carlo_rollouts {
+            for (i, candidate) in candidates.iter().enumerate() {
+                match self.rollout(view.world, view.player_number, *candidate, deadline, &mut rng) {
+                    Some(score) => {
+                        scores[i] += score;
+                        counts[i] += 1;
+                        rollouts += 1;
+                    }
+                    None => break 'simulation,
+                }
+            }
+        }
+        self.rng = Some(rng);
+
+        // 評価は候補ごとのシミュレーション回数で平均する
+        let average = |i: usize| scores[i] / counts[i] as f64;
+        let best = (0..candidates.len())
+            .filter(|i| counts[*i] > 0)
+            .fold(None, |best: Option<usize>, i| match best {
+                Some(best) if average(best) >= average(i) => Some(best),
+                _ => Some(i),
+            });
+        // 標準の AI の入力が一番よい候補とほとんど変わらなければ、標準の AI の入力を使う
+        let proposed = candidates.iter().position(|candidate| candidate.movement == proposal.movement && candidate.fire == proposal.fire);
+        let chosen = match (best, proposed) {
+            (None, _) => proposal,
+            (Some(best), Some(i)) if counts[i] > 0 && average(best) - average(i) <= ai_constants::MONTE_CARLO_SCORE_MARGIN => proposal,
+            (Some(best), _) => PlayerInput { detonate: proposal.detonate, ..candidates[best] },
+        };
+        self.movement = chosen.movement;
+        chosen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{rule::Rule, stage::Stage, test_support::{new_world, snapshot}};
+    use crate::gym::action;
+
+    /// 時間ではなく回数だけでシミュレーションを止める、軽い設定
+    fn count_only_profile() -> AIProfile {
+        AIProfile {
+            monte_carlo_rollouts: 6,
+            monte_carlo_rollout_frames: 90,
+            monte_carlo_time_budget_millis: 0,
+            ..AIProfile::default()
+        }
+    }
+
+    /// モンテカルロ AI 同士で試合を進め、最後の状態を返す
+    fn play(seed: u64, frames: usize) -> String {
+        let mut world = World::new(seed, 2, Rule::default(), Stage::classic());
+        let mut players: Vec<MonteCarloPlayer> = (0..2).map(|_| MonteCarloPlayer::with_profile(count_only_profile())).collect();
+        for _ in 0..frames {
+            let player_inputs: Vec<PlayerInput> = players.iter_mut().enumerate()
+                .map(|(i, player)| player.get_player_input(&GameView::new(&world, i)))
+                .collect();
+            world.on_next_frame(&player_inputs);
+        }
+        snapshot(&world)
+    }
+
+    #[test]
+    fn same_seed_makes_the_same_decisions_without_a_time_budget() {
+        assert_eq!(play(3, 180), play(3, 180));
+    }
+
+    #[test]
+    fn candidates_exclude_walls_and_bombs_that_cannot_be_placed() {
+        let mut world = new_world();
+        // 1P は左上の角にいるので、左と上には動けない
+        let actions = |world: &World| MonteCarloPlayer::candidates(world, 0).iter().map(action::input_to_action).collect::<Vec<_>>();
+        let expected: Vec<usize> = [Movement::NONE, Movement::RIGHT, Movement::DOWN].iter()
+            .flat_map(|movement| [false, true].map(|fire| action::input_to_action(&PlayerInput::new(*movement, fire))))
+            .collect();
+        assert_eq!(actions(&world), expected);
+
+        // 爆弾を置いた後は、置ける数が残っていないので爆弾を置く候補はない
+        world.on_next_frame(&[PlayerInput::new(Movement::NONE, true), PlayerInput::new(Movement::NONE, false)]);
+        assert_eq!(actions(&world), expected.into_iter().step_by(2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn rollout_stops_after_the_deadline() {
+        let world = new_world();
+        let player = MonteCarloPlayer::with_profile(count_only_profile());
+        let mut rng = StdRng::seed_from_u64(0);
+        let candidate = PlayerInput::new(Movement::NONE, false);
+        assert_eq!(player.rollout(&world, 0, candidate, Some(Instant::now()), &mut rng), None);
+        assert_eq!(player.rollout(&world, 0, candidate, None, &mut rng), Some(1.0));
+    }
+}
@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::gym::observation::{Observation, CHANNEL_COUNT};
+
+/// 層（重みの並び順は PyTorch の Conv2d、Linear の weight と同じ）
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Layer {
+    // 畳み込み（ストライド 1、出力が入力と同じ大きさになるように周りを 0 で埋める）
+    // weights は [out_channels][in_channels][kernel_size][kernel_size]
+    Conv2d { in_channels: usize, out_channels: usize, kernel_size: usize, weights: Vec<f32>, bias: Vec<f32> },
+    // 全結合（weights は [outputs][inputs]）
+    Dense { inputs: usize, outputs: usize, weights: Vec<f32>, bias: Vec<f32> },
+    Relu,
+    // マスごとの値を [チャンネル][y][x] の順に1列に並べ、後ろに Observation::features をつなげる
+    Flatten,
+}
+
+/// 層の途中の値
+enum Tensor {
+    Grid { channels: usize, height: usize, width: usize, data: Vec<f32> },
+    Vector(Vec<f32>),
+}
+
+/// 観測から行動ごとのスコアを計算する小さなニューラルネットワーク（層を順に適用する）
+///
+/// 入力は Observation::grid（CHANNEL_COUNT × 高さ × 幅）で、最後の層の出力は行動の数と同じ長さにする。
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NeuralNetwork {
+    pub layers: Vec<Layer>,
+}
+
+impl NeuralNetwork {
+    /// JSON のファイルから読み込む
+    pub fn load(path: &Path) -> Result<NeuralNetwork, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read neural network {}: {}", path.display(), e))?;
+        let network = serde_json::from_str::<NeuralNetwork>(&text).map_err(|e| format!("Invalid neural network {}: {}", path.display(), e))?;
+        network.validate().map_err(|e| format!("Invalid neural network {}: {}", path.display(), e))?;
+        Ok(network)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("Failed to write neural network {}: {}", path.display(), e))
+    }
+
+    /// 重みの数が層の大きさと合っているか確認する（マップの大きさによる確認は forward で行う）
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, layer) in self.layers.iter().enumerate() {
+            match layer {
+                Layer::Conv2d { in_channels, out_channels, kernel_size, weights, bias } => {
+                    if kernel_size % 2 == 0 {
+                        return Err(format!("layer {}: kernel_size must be odd (got {})", i, kernel_size));
+                    }
+                    Self::check_length(i, "weights", weights.len(), out_channels * in_channels * kernel_size * kernel_size)?;
+                    Self::check_length(i, "bias", bias.len(), *out_channels)?;
+                }
+                Layer::Dense { inputs, outputs, weights, bias } => {
+                    Self::check_length(i, "weights", weights.len(), inputs * outputs)?;
+                    Self::check_length(i, "bias", bias.len(), *outputs)?;
+                }
+                Layer::Relu | Layer::Flatten => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn check_length(layer: usize, name: &str, actual: usize, expected: usize) -> Result<(), String> {
+        if actual != expected {
+            return Err(format!("layer {}: {} must have {} values (got {})", layer, name, expected, actual));
+        }
+        Ok(())
+    }
+
+    /// 観測から最後の層の出力を計算する
+    pub fn forward(&self, observation: &Observation) -> Result<Vec<f32>, String> {
+        let mut tensor = Tensor::Grid {
+            channels: CHANNEL_COUNT,
+            height: observation.height,
+            width: observation.width,
+            data: observation.grid.clone(),
+        };
+        for (i, layer) in self.layers.iter().enumerate() {
+            tensor = match (layer, tensor) {
+                (Layer::Conv2d { in_channels, out_channels, kernel_size, weights, bias }, Tensor::Grid { channels, height, width, data }) => {
+                    if channels != *in_channels {
+                        return Err(format!("layer {}: expected {} input channels (got {})", i, in_channels, channels));
+                    }
+                    let data = Self::conv2d(&data, height, width, *in_channels, *out_channels, *kernel_size, weights, bias);
+                    Tensor::Grid { channels: *out_channels, height, width, data }
+                }
+                (Layer::Dense { inputs, outputs, weights, bias }, Tensor::Vector(data)) => {
+                    if data.len() != *inputs {
+                        return Err(format!("layer {}: expected {} inputs (got {})", i, inputs, data.len()));
+                    }
+                    let data = (0..*outputs).map(|o| {
+                        bias[o] + weights[o * inputs..(o + 1) * inputs].iter().zip(data.iter()).map(|(w, x)| w * x).sum::<f32>()
+                    }).collect();
+                    Tensor::Vector(data)
+                }
+                (Layer::Relu, Tensor::Grid { channels, height, width, data }) => {
+                    Tensor::Grid { channels, height, width, data: data.into_iter().map(|x| x.max(0.0)).collect() }
+                }
+                (Layer::Relu, Tensor::Vector(data)) => Tensor::Vector(data.into_iter().map(|x| x.max(0.0)).collect()),
+                (Layer::Flatten, Tensor::Grid { mut data, .. }) => {
+                    data.extend_from_slice(&observation.features);
+                    Tensor::Vector(data)
+                }
+                (Layer::Conv2d { .. }, Tensor::Vector(_)) => return Err(format!("layer {}: conv2d must come before flatten", i)),
+                (Layer::Dense { .. }, Tensor::Grid { .. }) => return Err(format!("layer {}: dense must come after flatten", i)),
+                (Layer::Flatten, Tensor::Vector(_)) => return Err(format!("layer {}: flatten is applied twice", i)),
+            };
+        }
+        match tensor {
+            Tensor::Vector(data) => Ok(data),
+            Tensor::Grid { .. } => Err("the last layer must come after flatten".to_string()),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn conv2d(input: &[f32], height: usize, width: usize, in_channels: usize, out_channels: usize, kernel_size: usize, weights: &[f32], bias: &[f32]) -> Vec<f32> {
+        let half = (kernel_size / 2) as isize;
+        let mut output = vec![0.0; out_channels * height * width];
+        for o in 0..out_channels {
+            for y in 0..height {
+                for x in 0..width {
+                    let mut sum = bias[o];
+                    for c in 0..in_channels {
+                        for ky in 0..kernel_size {
+                            let iy = y as isize + ky as isize - half;
+                            if iy < 0 || iy >= height as isize {
+                                continue;
+                            }
+                            for kx in 0..kernel_size {
+                                let ix = x as isize + kx as isize - half;
+                                if ix < 0 || ix >= width as isize {
+                                    continue;
+                                }
+                                let weight = weights[((o * in_channels + c) * kernel_size + ky) * kernel_size + kx];
+                                sum += weight * input[(c * height + iy as usize) * width + ix as usize];
+                            }
+                        }
+                    }
+                    output[(o * height + y) * width + x] = sum;
+                }
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gym::observation::FEATURE_COUNT;
+
+    /// チャンネル 0 だけに値のある観測
+    fn observation(width: usize, height: usize, values: &[f32]) -> Observation {
+        let mut grid = vec![0.0; CHANNEL_COUNT * height * width];
+        grid[..values.len()].copy_from_slice(values);
+        let mut features = vec![0.0; FEATURE_COUNT];
+        features[0] = 1.0;
+        Observation { width, height, grid, features }
+    }
+
+    /// チャンネル 0 だけを weight 倍して bias を足す 1x1 の畳み込み
+    fn select_channel_0(weight: f32, bias: f32) -> Layer {
+        let mut weights = vec![0.0; CHANNEL_COUNT];
+        weights[0] = weight;
+        Layer::Conv2d { in_channels: CHANNEL_COUNT, out_channels: 1, kernel_size: 1, weights, bias: vec![bias] }
+    }
+
+    #[test]
+    fn conv2d_keeps_the_size_and_pads_with_zeros() {
+        let input: Vec<f32> = (1..=9).map(|x| x as f32).collect();
+        let output = NeuralNetwork::conv2d(&input, 3, 3, 1, 1, 3, &[1.0; 9], &[0.0]);
+        assert_eq!(output, vec![12.0, 21.0, 16.0, 27.0, 45.0, 33.0, 24.0, 39.0, 28.0]);
+
+        // 出力チャンネルごとに重みとバイアスを使い分け、入力チャンネルは足し合わせる
+        let input = vec![1.0, 2.0, 10.0, 20.0];
+        let output = NeuralNetwork::conv2d(&input, 1, 2, 2, 2, 1, &[1.0, 1.0, 0.0, -1.0], &[0.0, 0.5]);
+        assert_eq!(output, vec![11.0, 22.0, -9.5, -19.5]);
+    }
+
+    #[test]
+    fn forward_flattens_the_grid_and_appends_the_features() {
+        let network = NeuralNetwork {
+            layers: vec![
+                select_channel_0(2.0, 0.5),
+                Layer::Relu,
+                Layer::Flatten,
+                Layer::Dense { inputs: 2 + FEATURE_COUNT, outputs: 2, weights: [vec![1.0; 2 + FEATURE_COUNT], vec![-1.0; 2 + FEATURE_COUNT]].concat(), bias: vec![0.0, 1.0] },
+            ],
+        };
+        network.validate().unwrap();
+        // 畳み込みで [2.5, -5.5]、Relu で [2.5, 0.0]、features の 1.0 を足す
+        assert_eq!(network.forward(&observation(2, 1, &[1.0, -3.0])).unwrap(), vec![3.5, -2.5]);
+        // Dense の入力の数はマップの大きさで決まる
+        assert_eq!(
+            network.forward(&observation(3, 1, &[1.0, 1.0, 1.0])).unwrap_err(),
+            format!("layer 3: expected {} inputs (got {})", 2 + FEATURE_COUNT, 3 + FEATURE_COUNT),
+        );
+    }
+
+    #[test]
+    fn invalid_layers_are_rejected() {
+        let conv = |kernel_size: usize, weights: usize| Layer::Conv2d { in_channels: 1, out_channels: 1, kernel_size, weights: vec![0.0; weights], bias: vec![0.0] };
+        assert_eq!(NeuralNetwork { layers: vec![conv(2, 4)] }.validate().unwrap_err(), "layer 0: kernel_size must be odd (got 2)");
+        assert_eq!(NeuralNetwork { layers: vec![Layer::Relu, conv(3, 8)] }.validate().unwrap_err(), "layer 1: weights must have 9 values (got 8)");
+
+        let observation = observation(2, 2, &[]);
+        let forward = |layers: Vec<Layer>| NeuralNetwork { layers }.forward(&observation).unwrap_err();
+        assert_eq!(forward(vec![conv(3, 9)]), format!("layer 0: expected 1 input channels (got {})", CHANNEL_COUNT));
+        assert_eq!(forward(vec![Layer::Dense { inputs: 1, outputs: 1, weights: vec![0.0], bias: vec![0.0] }]), "layer 0: dense must come after flatten");
+        assert_eq!(forward(vec![Layer::Flatten, select_channel_0(1.0, 0.0)]), "layer 1: conv2d must come before flatten");
+        assert_eq!(forward(vec![Layer::Flatten, Layer::Flatten]), "layer 1: flatten is applied twice");
+        assert_eq!(forward(vec![select_channel_0(1.0, 0.0)]), "the last layer must come after flatten");
+    }
+
+    #[test]
+    fn saved_network_is_loaded_back() {
+        let path = std::env::temp_dir().join(format!("rust-bomber-test-{}.json", std::process::id()));
+        let network = NeuralNetwork { layers: vec![select_channel_0(2.0, 0.5), Layer::Flatten] };
+        network.save(&path).unwrap();
+        let loaded = NeuralNetwork::load(&path);
+        fs::remove_file(&path).unwrap();
+        let observation = observation(2, 1, &[1.0, -3.0]);
+        assert_eq!(loaded.unwrap().forward(&observation), network.forward(&observation));
+    }
+}
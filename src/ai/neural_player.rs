@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use crate::game::player_input::{PlayerInput, Movement};
+use crate::gym::{
+    action::{self, ACTION_COUNT},
+    observation::Observation,
+};
+
+use super::{
+    ai_profile::AIProfile,
+    field::Field,
+    neural_network::NeuralNetwork,
+    strategy::{AIStrategy, GameView},
+};
+
+/// 学習済みのニューラルネットワークで毎フレーム行動を選ぶ AI（スコアが最大の行動を選ぶ）
+pub struct NeuralPlayer {
+    network: Arc<NeuralNetwork>,
+    // 計算できなかったことを表示済みなら true（毎フレーム表示しないようにする）
+    error_reported: bool,
+}
+
+impl NeuralPlayer {
+    pub fn new(network: Arc<NeuralNetwork>) -> NeuralPlayer {
+        NeuralPlayer {
+            network,
+            error_reported: false,
+        }
+    }
+}
+
+impl AIStrategy for NeuralPlayer {
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        let field = Field::from_world(view.world, &AIProfile::default());
+        let observation = Observation::new(view.world, &field, view.player_number);
+        let scores = match self.network.forward(&observation) {
+            Ok(scores) if scores.len() == ACTION_COUNT => scores,
+            result => {
+                if !self.error_reported {
+                    let error = result.err().unwrap_or_else(|| format!("the last layer must have {} outputs", ACTION_COUNT));
+                    println!("Failed to run neural network: {}", error);
+                    self.error_reported = true;
+                }
+                return PlayerInput::new(Movement::NONE, false);
+            }
+        };
+        let best = scores.iter().enumerate().fold(0, |best, (i, score)| if *score > scores[best] { i } else { best });
+        action::action_to_input(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_support::new_world;
+    use crate::gym::observation::{CHANNEL_COUNT, FEATURE_COUNT};
+    use crate::ai::neural_network::Layer;
+
+    /// 観測によらず、バイアスがそのまま行動ごとのスコアになるネットワーク
+    fn constant_network(world_size: usize, bias: Vec<f32>) -> Arc<NeuralNetwork> {
+        let inputs = CHANNEL_COUNT * world_size + FEATURE_COUNT;
+        let outputs = bias.len();
+        Arc::new(NeuralNetwork {
+            layers: vec![Layer::Flatten, Layer::Dense { inputs, outputs, weights: vec![0.0; inputs * outputs], bias }],
+        })
+    }
+
+    #[test]
+    fn action_with_the_highest_score_is_chosen() {
+        let world = new_world();
+        let size = (world.stage.width * world.stage.height) as usize;
+        let mut bias = vec![0.0; ACTION_COUNT];
+        bias[7] = 1.0;
+        let mut player = NeuralPlayer::new(constant_network(size, bias));
+        let player_input = player.get_player_input(&GameView::new(&world, 0));
+        assert_eq!(action::input_to_action(&player_input), 7);
+    }
+
+    #[test]
+    fn network_with_the_wrong_output_count_stands_still() {
+        let world = new_world();
+        let size = (world.stage.width * world.stage.height) as usize;
+        let mut bias = vec![0.0; ACTION_COUNT - 1];
+        bias[3] = 1.0;
+        let mut player = NeuralPlayer::new(constant_network(size, bias));
+        let player_input = player.get_player_input(&GameView::new(&world, 0));
+        assert_eq!(action::input_to_action(&player_input), action::input_to_action(&PlayerInput::new(Movement::NONE, false)));
+        assert!(player.error_reported);
+    }
+}
@@ -0,0 +1,64 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::game::{
+    constants,
+    light_sprite::LightSprite,
+    player_input::{PlayerInput, Movement},
+};
+
+use super::{
+    ai_constants,
+    ai_profile::AIProfile,
+    field::Field,
+    strategy::{AIStrategy, GameView},
+};
+
+/// でたらめに歩き回り、ときどき爆弾を置く AI（ほかの AI の強さを比べるときの基準）
+///
+/// マスにぴったり合った位置に着くたびに、通れる方向から次に進む方向を選ぶ。
+pub struct RandomWalkPlayer {
+    // 試合のシードとプレイヤー番号から作る乱数生成器（最初の入力のときに作る）
+    rng: Option<StdRng>,
+    movement: Movement,
+}
+
+impl Default for RandomWalkPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandomWalkPlayer {
+    pub fn new() -> RandomWalkPlayer {
+        RandomWalkPlayer {
+            rng: None,
+            movement: Movement::NONE,
+        }
+    }
+}
+
+impl AIStrategy for RandomWalkPlayer {
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput {
+        let me = view.me();
+        let rng = self.rng.get_or_insert_with(|| StdRng::seed_from_u64(view.world.seed.wrapping_add(view.player_number as u64)));
+
+        // マスの途中なら同じ方向に進み続ける
+        if me.get_x() % constants::CHARACTER_SIZE != 0 || me.get_y() % constants::CHARACTER_SIZE != 0 {
+            return PlayerInput::new(self.movement, false);
+        }
+
+        let field = Field::from_world(view.world, &AIProfile::default());
+        let (x, y) = GameView::tile_of(me);
+        let movements = [Movement::LEFT, Movement::RIGHT, Movement::UP, Movement::DOWN].iter()
+            .copied()
+            .filter(|movement| {
+                let (dx, dy) = movement.delta();
+                field.get_element(x + dx, y + dy).is_passable()
+            })
+            .collect::<Vec<Movement>>();
+        self.movement = if movements.is_empty() { Movement::NONE } else { movements[rng.gen_range(0, movements.len())] };
+        let fire = rng.gen_range(0, 100) < ai_constants::RANDOM_WALK_FIRE_PROBABILITY;
+        PlayerInput::new(self.movement, fire)
+    }
+}
@@ -0,0 +1,189 @@
+use crate::game::{
+    constants,
+    light_sprite::LightSprite,
+    player::Player,
+    player_input::{PlayerInput, Movement},
+    world::World,
+};
+
+use super::{
+    ai_player::AIPlayer,
+    ai_profile::AIProfile,
+    difficulty::{Difficulty, DifficultyFilter},
+    hunter_player::HunterPlayer,
+    monte_carlo_player::MonteCarloPlayer,
+    random_walk_player::RandomWalkPlayer,
+};
+
+/// AI が入力を決めるときに見るゲームの状態（読み取り専用）
+pub struct GameView<'a> {
+    pub world: &'a World,
+    // 操作するプレイヤーの番号
+    pub player_number: usize,
+}
+
+impl<'a> GameView<'a> {
+    pub fn new(world: &'a World, player_number: usize) -> GameView<'a> {
+        GameView { world, player_number }
+    }
+
+    /// 操作するプレイヤー
+    pub fn me(&self) -> &'a Player {
+        &self.world.players[self.player_number]
+    }
+
+    /// 生きている対戦相手のうち最も近いプレイヤー（全員死んでいる場合は最も近いプレイヤー）
+    pub fn nearest_opponent(&self) -> &'a Player {
+        let me = self.me();
+        self.world.players.iter()
+            .filter(|p| p.player_number != self.player_number)
+            .min_by_key(|p| (p.is_dead(), (p.get_x() - me.get_x()).abs() + (p.get_y() - me.get_y()).abs()))
+            .unwrap()
+    }
+
+    /// プレイヤーがいるマス（一番重なっているマス）
+    pub fn tile_of(player: &Player) -> (i32, i32) {
+        (
+            (player.get_x() + constants::CHARACTER_SIZE / 2) / constants::CHARACTER_SIZE,
+            (player.get_y() + constants::CHARACTER_SIZE / 2) / constants::CHARACTER_SIZE,
+        )
+    }
+}
+
+/// AI の戦略（毎フレーム、ゲームの状態から入力を決める）
+pub trait AIStrategy {
+    /// 入力を決める準備ができているか（false の間、ゲームは進まずに待つ）
+    fn is_ready(&mut self, _view: &GameView) -> bool {
+        true
+    }
+
+    fn get_player_input(&mut self, view: &GameView) -> PlayerInput;
+}
+
+/// 組み込みの AI の戦略の一覧
+#[derive(Copy, Clone, PartialEq)]
+pub enum StrategyKind {
+    // 広さ優先探索でマスごとのスコアを計算して目的地を決める
+    Standard,
+    // 壁を壊してアイテムを集めることに専念し、対戦相手にいやがらせをしない
+    Passive,
+    // 対戦相手を追いかけて爆弾で追い詰める
+    Hunter,
+    // でたらめに歩き回り、ときどき爆弾を置く（強さを比べるときの基準）
+    RandomWalk,
+    // 行動の候補ごとにゲームの続きをシミュレーションして、追い詰められない行動を選ぶ
+    MonteCarlo,
+}
+
+impl StrategyKind {
+    pub const ALL: [StrategyKind; 5] = [StrategyKind::Standard, StrategyKind::Passive, StrategyKind::Hunter, StrategyKind::RandomWalk, StrategyKind::MonteCarlo];
+
+    /// コマンドラインなどで指定するときの名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Passive => "passive",
+            Self::Hunter => "hunter",
+            Self::RandomWalk => "random_walk",
+            Self::MonteCarlo => "monte_carlo",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<StrategyKind> {
+        Self::ALL.iter().copied().find(|kind| kind.name() == name)
+    }
+
+    /// 指定した強さと性格の AI を作る（性格を使わない戦略もある）
+    pub fn create(&self, difficulty: Difficulty, profile: &AIProfile) -> Box<dyn AIStrategy> {
+        let profile = difficulty.apply_to_profile(profile);
+        let strategy: Box<dyn AIStrategy> = match self {
+            Self::Standard => Box::new(AIPlayer::with_profile(profile)),
+            Self::Passive => Box::new(AIPlayer::with_profile(AIProfile { opponent_stress_weight: 0, ..profile })),
+            Self::Hunter => Box::new(HunterPlayer::with_profile(profile)),
+            Self::RandomWalk => Box::new(RandomWalkPlayer::new()),
+            Self::MonteCarlo => Box::new(MonteCarloPlayer::with_profile(profile)),
+        };
+        if let Difficulty::Expert = difficulty {
+            strategy
+        } else {
+            Box::new(DifficultyFilter::new(strategy, difficulty))
+        }
+    }
+}
+
+/// 目的のマスに向かうための移動方向（マスの途中にいる場合は横方向を先に合わせる）
+pub fn movement_toward(player: &Player, x: i32, y: i32) -> Movement {
+    let tx = x * constants::CHARACTER_SIZE;
+    let ty = y * constants::CHARACTER_SIZE;
+    if tx > player.get_x() {
+        Movement::RIGHT
+    } else if tx < player.get_x() {
+        Movement::LEFT
+    } else if ty < player.get_y() {
+        Movement::UP
+    } else if ty > player.get_y() {
+        Movement::DOWN
+    } else {
+        Movement::NONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{rule::Rule, stage::Stage};
+
+    #[test]
+    fn strategy_kinds_are_found_by_name() {
+        for kind in StrategyKind::ALL {
+            assert!(StrategyKind::from_name(kind.name()) == Some(kind));
+        }
+        assert!(StrategyKind::from_name("human").is_none());
+    }
+
+    #[test]
+    fn nearest_opponent_prefers_living_players() {
+        let mut world = World::new(0, 4, Rule::default(), Stage::classic());
+        // 1P は左上、2P は右下、3P は右上、4P は左下
+        let view = GameView::new(&world, 0);
+        assert_eq!(GameView::tile_of(view.me()), (1, 1));
+        assert_eq!(view.nearest_opponent().player_number, 3);
+
+        world.players[3].death_state = 1;
+        assert_eq!(GameView::new(&world, 0).nearest_opponent().player_number, 2);
+        world.players[1].death_state = 1;
+        world.players[2].death_state = 1;
+        assert_eq!(GameView::new(&world, 0).nearest_opponent().player_number, 3);
+    }
+
+    /// 指定した戦略の 1P と、でたらめに歩く 2P で試合を進める
+    fn play(kind: StrategyKind, seed: u64, frames: usize) -> World {
+        let mut world = World::new(seed, 2, Rule::default(), Stage::classic());
+        let mut strategies: Vec<Box<dyn AIStrategy>> = vec![kind.create(Difficulty::Expert, &AIProfile::default()), Box::new(RandomWalkPlayer::new())];
+        for _ in 0..frames {
+            let player_inputs: Vec<PlayerInput> = strategies.iter_mut().enumerate()
+                .map(|(i, strategy)| strategy.get_player_input(&GameView::new(&world, i)))
+                .collect();
+            world.on_next_frame(&player_inputs);
+        }
+        world
+    }
+
+    #[test]
+    fn strategies_break_walls_without_blowing_themselves_up() {
+        for kind in [StrategyKind::Standard, StrategyKind::Passive, StrategyKind::Hunter] {
+            for seed in 0..3 {
+                let walls = World::new(seed, 2, Rule::default(), Stage::classic()).walls.len();
+                let world = play(kind, seed, 600);
+                assert!(world.walls.len() < walls, "{} seed {} broke no walls", kind.name(), seed);
+                assert_ne!(world.players[0].killed_by, Some(0), "{} seed {} blew itself up", kind.name(), seed);
+            }
+        }
+    }
+
+    #[test]
+    fn random_walk_leaves_its_spawn_position() {
+        let world = play(StrategyKind::RandomWalk, 1, 120);
+        assert_ne!(GameView::tile_of(&world.players[0]), world.stage.spawn_positions[0]);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod entrant;
+pub mod match_runner;
+pub mod report;
+pub mod tuner;
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use crate::ai::{
+    ai_profile::AIProfile,
+    difficulty::Difficulty,
+    strategy::{AIStrategy, StrategyKind},
+};
+
+/// 対戦に参加する AI（戦略、強さ、性格の組み合わせ）
+#[derive(Clone)]
+pub struct Entrant {
+    // 結果の表示に使う名前
+    pub name: String,
+    pub strategy_kind: StrategyKind,
+    pub difficulty: Difficulty,
+    pub profile: AIProfile,
+}
+
+impl Entrant {
+    pub fn new(strategy_kind: StrategyKind, difficulty: Difficulty, profile: AIProfile) -> Entrant {
+        Entrant {
+            name: format!("{}:{}", strategy_kind.name(), difficulty.name()),
+            strategy_kind,
+            difficulty,
+            profile,
+        }
+    }
+
+    /// "<戦略>[:<強さ>][@<性格ファイル>]" の形式から作る（強さを省略すると expert、名前は指定した文字列になる）
+    pub fn parse(spec: &str) -> Result<Entrant, String> {
+        let (strategy, profile_file) = match spec.split_once('@') {
+            Some((strategy, file)) => (strategy, Some(file)),
+            None => (spec, None),
+        };
+        let (strategy_name, level) = strategy.split_once(':').unwrap_or((strategy, Difficulty::Expert.name()));
+        let strategy_kind = StrategyKind::from_name(strategy_name).ok_or(format!("Unknown strategy '{}' in '{}'", strategy_name, spec))?;
+        let difficulty = Difficulty::from_name(level).ok_or(format!("Unknown AI level '{}' in '{}'", level, spec))?;
+        let profile = match profile_file {
+            Some(file) => AIProfile::load(Path::new(file))?,
+            None => AIProfile::default(),
+        };
+        Ok(Entrant {
+            name: spec.to_string(),
+            strategy_kind,
+            difficulty,
+            profile,
+        })
+    }
+
+    /// 描画せずに対戦させる AI を作る（実行するマシンによらず同じ結果になるように、時間の上限を使わず回数だけで先読みを止める）
+    pub fn create_strategy(&self) -> Box<dyn AIStrategy> {
+        let profile = AIProfile { monte_carlo_time_budget_millis: 0, ..self.profile };
+        self.strategy_kind.create(self.difficulty, &profile)
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::ai::strategy::{AIStrategy, GameView};
+use crate::game::{
+    player_input::{PlayerInput, Movement},
+    rule::Rule,
+    stage::Stage,
+    world::{State, World},
+};
+
+use super::entrant::Entrant;
+
+// 決着がつかなければ時間切れにするまでのフレーム数のデフォルト値（3分）
+pub const DEFAULT_MAX_FRAMES: i32 = 60 * 180;
+
+/// 描画せずに試合を進めるときの設定
+#[derive(Clone)]
+pub struct MatchSettings {
+    pub rule: Rule,
+    pub stage: Stage,
+    // 決着がつかなければ時間切れにするまでのフレーム数
+    pub max_frames: i32,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        MatchSettings {
+            rule: Rule::default(),
+            stage: Stage::classic(),
+            max_frames: DEFAULT_MAX_FRAMES,
+        }
+    }
+}
+
+/// 1試合の組み合わせ
+#[derive(Clone, Debug)]
+pub struct Pairing {
+    pub seed: u64,
+    // 各プレイヤーを操作する参加者の番号（プレイヤー番号の順）
+    pub entrants: Vec<usize>,
+}
+
+/// 1試合の結果
+#[derive(Clone, Debug)]
+pub struct MatchResult {
+    pub pairing: Pairing,
+    // 勝ったプレイヤーの番号（引き分けや時間切れは None）
+    pub winner: Option<usize>,
+    pub timed_out: bool,
+    pub frames: i32,
+    // 各プレイヤーが自分の爆発に巻き込まれて死んだか
+    pub self_kills: Vec<bool>,
+}
+
+impl MatchResult {
+    /// 勝った参加者の番号
+    pub fn winning_entrant(&self) -> Option<usize> {
+        self.winner.map(|player_number| self.pairing.entrants[player_number])
+    }
+}
+
+/// 描画せずに1試合を最後まで進める
+pub fn play_match(settings: &MatchSettings, pairing: &Pairing, strategies: &mut [Box<dyn AIStrategy>]) -> MatchResult {
+    let mut world = World::new(pairing.seed, strategies.len(), settings.rule, settings.stage.clone());
+    let mut frames = 0;
+    while let State::Playing = world.state {
+        if frames >= settings.max_frames {
+            break;
+        }
+        let mut player_inputs = vec![PlayerInput::new(Movement::NONE, false); strategies.len()];
+        for (i, strategy) in strategies.iter_mut().enumerate() {
+            if !world.players[i].is_dead() {
+                player_inputs[i] = strategy.get_player_input(&GameView::new(&world, i));
+            }
+        }
+        world.on_next_frame(&player_inputs);
+        frames += 1;
+    }
+    MatchResult {
+        pairing: pairing.clone(),
+        winner: match world.state {
+            State::Won(player_number) => Some(player_number),
+            _ => None,
+        },
+        timed_out: matches!(world.state, State::Playing),
+        frames,
+        self_kills: world.players.iter().map(|player| player.killed_by == Some(player.player_number)).collect(),
+    }
+}
+
+/// すべての2人の組み合わせを、シードごとに出現位置を入れ替えて2試合ずつ対戦させる組み合わせ
+pub fn round_robin(entrant_count: usize, seeds: &[u64]) -> Vec<Pairing> {
+    let mut pairings = Vec::new();
+    for seed in seeds {
+        for a in 0..entrant_count {
+            for b in (a + 1)..entrant_count {
+                pairings.push(Pairing { seed: *seed, entrants: vec![a, b] });
+                pairings.push(Pairing { seed: *seed, entrants: vec![b, a] });
+            }
+        }
+    }
+    pairings
+}
+
+/// 複数のスレッドで試合を進める（結果は組み合わせと同じ順に並ぶ）
+pub fn play_matches(settings: &MatchSettings, entrants: &[Entrant], pairings: &[Pairing], thread_count: usize) -> Vec<MatchResult> {
+    let next_index = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<MatchResult>>> = Mutex::new(vec![None; pairings.len()]);
+    thread::scope(|scope| {
+        for _ in 0..thread_count.max(1) {
+            scope.spawn(|| loop {
+                let index = next_index.fetch_add(1, Ordering::Relaxed);
+                let Some(pairing) = pairings.get(index) else { break };
+                let mut strategies: Vec<Box<dyn AIStrategy>> = pairing.entrants.iter().map(|i| entrants[*i].create_strategy()).collect();
+                let result = play_match(settings, pairing, &mut strategies);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().map(|result| result.unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{ai_profile::AIProfile, difficulty::Difficulty, strategy::StrategyKind};
+
+    #[test]
+    fn round_robin_swaps_spawn_positions() {
+        let pairings = round_robin(3, &[5, 6]);
+        assert_eq!(pairings.len(), 2 * 3 * 2);
+        assert_eq!(pairings[0].entrants, vec![0, 1]);
+        assert_eq!(pairings[1].entrants, vec![1, 0]);
+        assert_eq!(pairings[6].seed, 6);
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_thread_count() {
+        let settings = MatchSettings { max_frames: 900, ..MatchSettings::default() };
+        let entrants = [
+            Entrant::new(StrategyKind::Standard, Difficulty::Expert, AIProfile::default()),
+            Entrant::new(StrategyKind::RandomWalk, Difficulty::Expert, AIProfile::default()),
+        ];
+        let pairings = round_robin(entrants.len(), &[0, 1]);
+        let summary = |results: Vec<MatchResult>| results.iter()
+            .map(|result| (result.pairing.seed, result.winning_entrant(), result.timed_out, result.frames))
+            .collect::<Vec<_>>();
+        let single = summary(play_matches(&settings, &entrants, &pairings, 1));
+        assert_eq!(single, summary(play_matches(&settings, &entrants, &pairings, 3)));
+        assert!(single.iter().all(|(_, _, timed_out, frames)| *timed_out == (*frames == 900)));
+    }
+}
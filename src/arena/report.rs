@@ -0,0 +1,260 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::{
+    entrant::Entrant,
+    match_runner::MatchResult,
+};
+
+// イロレーティングの初期値と、1試合で動く最大の値
+pub const INITIAL_ELO: f64 = 1500.0;
+pub const ELO_K_FACTOR: f64 = 16.0;
+
+/// 2人の参加者の間の対戦成績（行の参加者から見た勝ち負け）
+#[derive(Clone, Default, Serialize)]
+pub struct PairRecord {
+    pub wins: u32,
+    pub losses: u32,
+    // 同時に死んだ
+    pub draws: u32,
+    // 決着がつかなかった
+    pub timeouts: u32,
+}
+
+impl PairRecord {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws + self.timeouts
+    }
+}
+
+/// 参加者ごとの成績
+#[derive(Clone, Serialize)]
+pub struct EntrantSummary {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub timeouts: u32,
+    pub win_rate: f64,
+    // 試合の平均の長さ（フレーム数）
+    pub average_frames: f64,
+    // 自分の爆発に巻き込まれて死んだ回数と、試合数に対する割合
+    pub self_kills: u32,
+    pub self_kill_rate: f64,
+    pub elo: f64,
+}
+
+/// 総当たり戦の結果
+#[derive(Serialize)]
+pub struct TournamentReport {
+    pub matches: usize,
+    pub average_frames: f64,
+    pub entrants: Vec<EntrantSummary>,
+    // matrix[i][j] は参加者 i の参加者 j に対する成績
+    pub matrix: Vec<Vec<PairRecord>>,
+}
+
+impl TournamentReport {
+    pub fn new(entrants: &[Entrant], results: &[MatchResult]) -> TournamentReport {
+        let count = entrants.len();
+        let mut matrix = vec![vec![PairRecord::default(); count]; count];
+        let mut total_frames = vec![0u64; count];
+        let mut games = vec![0u32; count];
+        let mut self_kills = vec![0u32; count];
+        let mut elo = vec![INITIAL_ELO; count];
+
+        for result in results {
+            let players = &result.pairing.entrants;
+            for (player_number, entrant) in players.iter().enumerate() {
+                games[*entrant] += 1;
+                total_frames[*entrant] += result.frames as u64;
+                if result.self_kills[player_number] {
+                    self_kills[*entrant] += 1;
+                }
+            }
+            // 同じ試合に出たプレイヤーの組ごとに成績とレーティングを更新する
+            let mut elo_changes = vec![0.0; count];
+            for a in 0..players.len() {
+                for b in 0..players.len() {
+                    if a == b {
+                        continue;
+                    }
+                    let record = &mut matrix[players[a]][players[b]];
+                    let score = match result.winner {
+                        _ if result.timed_out => { record.timeouts += 1; 0.5 }
+                        Some(winner) if winner == a => { record.wins += 1; 1.0 }
+                        Some(winner) if winner == b => { record.losses += 1; 0.0 }
+                        _ => { record.draws += 1; 0.5 }
+                    };
+                    let expected = 1.0 / (1.0 + 10f64.powf((elo[players[b]] - elo[players[a]]) / 400.0));
+                    elo_changes[players[a]] += ELO_K_FACTOR * (score - expected);
+                }
+            }
+            for (rating, change) in elo.iter_mut().zip(elo_changes) {
+                *rating += change;
+            }
+        }
+
+        let ratio = |numerator: f64, denominator: u32| if denominator == 0 { 0.0 } else { numerator / denominator as f64 };
+        let summaries = entrants.iter().enumerate().map(|(i, entrant)| {
+            let mut total = PairRecord::default();
+            for record in matrix[i].iter() {
+                total.wins += record.wins;
+                total.losses += record.losses;
+                total.draws += record.draws;
+                total.timeouts += record.timeouts;
+            }
+            EntrantSummary {
+                name: entrant.name.clone(),
+                games: games[i],
+                wins: total.wins,
+                losses: total.losses,
+                draws: total.draws,
+                timeouts: total.timeouts,
+                win_rate: ratio(total.wins as f64, total.games()),
+                average_frames: ratio(total_frames[i] as f64, games[i]),
+                self_kills: self_kills[i],
+                self_kill_rate: ratio(self_kills[i] as f64, games[i]),
+                elo: elo[i],
+            }
+        }).collect();
+
+        TournamentReport {
+            matches: results.len(),
+            average_frames: if results.is_empty() { 0.0 } else { results.iter().map(|result| result.frames as f64).sum::<f64>() / results.len() as f64 },
+            entrants: summaries,
+            matrix,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report must be serializable")
+    }
+
+    /// 参加者ごとの成績の CSV
+    pub fn summary_csv(&self) -> String {
+        let mut csv = String::from("entrant,games,wins,losses,draws,timeouts,win_rate,average_frames,self_kills,self_kill_rate,elo\n");
+        for summary in self.entrants.iter() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{:.4},{:.1},{},{:.4},{:.1}\n",
+                csv_field(&summary.name), summary.games, summary.wins, summary.losses, summary.draws, summary.timeouts,
+                summary.win_rate, summary.average_frames, summary.self_kills, summary.self_kill_rate, summary.elo,
+            ));
+        }
+        csv
+    }
+
+    /// 参加者の組ごとの対戦成績の CSV（1行に1組）
+    pub fn matrix_csv(&self) -> String {
+        let mut csv = String::from("entrant,opponent,games,wins,losses,draws,timeouts\n");
+        for (i, row) in self.matrix.iter().enumerate() {
+            for (j, record) in row.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    csv_field(&self.entrants[i].name), csv_field(&self.entrants[j].name),
+                    record.games(), record.wins, record.losses, record.draws, record.timeouts,
+                ));
+            }
+        }
+        csv
+    }
+
+    /// ディレクトリに report.json、summary.csv、matrix.csv を書く
+    pub fn save(&self, directory: &Path) -> Result<(), String> {
+        fs::create_dir_all(directory).map_err(|e| format!("Failed to create {}: {}", directory.display(), e))?;
+        for (file, text) in [("report.json", self.to_json()), ("summary.csv", self.summary_csv()), ("matrix.csv", self.matrix_csv())] {
+            let path = directory.join(file);
+            fs::write(&path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+/// CSV の1項目（カンマや引用符を含む場合は引用符で囲む）
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{ai_profile::AIProfile, difficulty::Difficulty, strategy::StrategyKind};
+    use crate::arena::match_runner::Pairing;
+
+    fn result(entrants: [usize; 2], winner: Option<usize>, timed_out: bool, self_kills: [bool; 2]) -> MatchResult {
+        MatchResult {
+            pairing: Pairing { seed: 0, entrants: entrants.to_vec() },
+            winner,
+            timed_out,
+            frames: 600,
+            self_kills: self_kills.to_vec(),
+        }
+    }
+
+    fn report() -> TournamentReport {
+        let entrants: Vec<Entrant> = ["a", "b,c", "say \"hi\""].iter()
+            .map(|name| Entrant { name: name.to_string(), ..Entrant::new(StrategyKind::Standard, Difficulty::Expert, AIProfile::default()) })
+            .collect();
+        TournamentReport::new(&entrants, &[
+            result([0, 1], Some(0), false, [false, false]),
+            result([1, 0], Some(1), false, [true, false]),
+            result([0, 2], None, true, [false, false]),
+            result([2, 0], None, false, [true, true]),
+        ])
+    }
+
+    #[test]
+    fn records_are_counted_from_both_sides() {
+        let report = report();
+        assert_eq!(report.matches, 4);
+        let a_b = &report.matrix[0][1];
+        let b_a = &report.matrix[1][0];
+        assert_eq!((a_b.wins, a_b.losses, b_a.wins, b_a.losses), (2, 0, 0, 2));
+        let a_c = &report.matrix[0][2];
+        assert_eq!((a_c.games(), a_c.timeouts, a_c.draws), (2, 1, 1));
+
+        let a = &report.entrants[0];
+        assert_eq!((a.games, a.wins, a.losses, a.draws, a.timeouts, a.self_kills), (4, 2, 0, 1, 1, 1));
+        assert_eq!(a.win_rate, 0.5);
+        assert_eq!(a.self_kill_rate, 0.25);
+        let b = &report.entrants[1];
+        assert_eq!((b.games, b.wins, b.losses, b.self_kills), (2, 0, 2, 1));
+    }
+
+    #[test]
+    fn elo_is_updated_after_every_match() {
+        let report = report();
+        let elo: Vec<f64> = report.entrants.iter().map(|summary| summary.elo).collect();
+        for (actual, expected) in elo.iter().zip([1514.9289913594657, 1484.368153396761, 1500.7028552437732]) {
+            assert!((actual - expected).abs() < 1e-9, "{:?}", elo);
+        }
+        // レーティングの合計は変わらない
+        assert!((elo.iter().sum::<f64>() - INITIAL_ELO * 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn csv_fields_with_commas_and_quotes_are_quoted() {
+        let report = report();
+        let summary = report.summary_csv();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("a,4,2,0,1,1,0.5000,600.0,1,0.2500,1514.9"), "{}", lines[1]);
+        assert!(lines[2].starts_with("\"b,c\",2,"), "{}", lines[2]);
+        assert!(lines[3].starts_with("\"say \"\"hi\"\"\",2,"), "{}", lines[3]);
+
+        let matrix = report.matrix_csv();
+        assert_eq!(matrix.lines().count(), 1 + 3 * 2);
+        assert!(matrix.contains("\na,\"b,c\",2,2,0,0,0\n"), "{}", matrix);
+        assert!(matrix.contains("\n\"say \"\"hi\"\"\",a,2,0,0,1,1\n"), "{}", matrix);
+    }
+}
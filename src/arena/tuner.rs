@@ -0,0 +1,363 @@
+use std::fs;
+use std::path::Path;
+
+use rand::{Rng, SeedableRng};
+use rand::distributions::StandardNormal;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{
+    ai_profile::AIProfile,
+    difficulty::Difficulty,
+    strategy::StrategyKind,
+};
+
+use super::{
+    entrant::Entrant,
+    match_runner::{self, MatchSettings, Pairing},
+    report::TournamentReport,
+};
+
+/// 調整する AIProfile の項目と、値を探す範囲
+pub struct Parameter {
+    pub name: &'static str,
+    pub min: i32,
+    pub max: i32,
+    pub value: fn(&mut AIProfile) -> &mut i32,
+}
+
+// 調整する項目（search_radius は AI の強さで決めるので調整しない）
+pub const PARAMETERS: [Parameter; 16] = [
+    Parameter { name: "score_of_fire_up_item", min: 0, max: 3000, value: |profile| &mut profile.score_of_fire_up_item },
+    Parameter { name: "score_of_bomb_up_item", min: 0, max: 3000, value: |profile| &mut profile.score_of_bomb_up_item },
+    Parameter { name: "score_of_speed_up_item", min: 0, max: 3000, value: |profile| &mut profile.score_of_speed_up_item },
+    Parameter { name: "score_of_full_fire_item", min: 0, max: 3000, value: |profile| &mut profile.score_of_full_fire_item },
+    Parameter { name: "score_of_power_down_item", min: -3000, max: 0, value: |profile| &mut profile.score_of_power_down_item },
+    Parameter { name: "score_of_kick_item", min: 0, max: 3000, value: |profile| &mut profile.score_of_kick_item },
+    Parameter { name: "score_of_remote_item", min: 0, max: 3000, value: |profile| &mut profile.score_of_remote_item },
+    Parameter { name: "score_of_pierce_item", min: 0, max: 3000, value: |profile| &mut profile.score_of_pierce_item },
+    Parameter { name: "cost_of_power_down_item", min: 0, max: 3000, value: |profile| &mut profile.cost_of_power_down_item },
+    Parameter { name: "score_of_break_wall", min: 0, max: 300, value: |profile| &mut profile.score_of_break_wall },
+    Parameter { name: "score_of_distance", min: 0, max: 50, value: |profile| &mut profile.score_of_distance },
+    Parameter { name: "risk_of_explosion", min: 10, max: 5000, value: |profile| &mut profile.risk_of_explosion },
+    Parameter { name: "risk_of_bomb", min: 10, max: 5000, value: |profile| &mut profile.risk_of_bomb },
+    Parameter { name: "opponent_stress_weight", min: 0, max: 100, value: |profile| &mut profile.opponent_stress_weight },
+    Parameter { name: "remote_bomb_detonation_wait", min: 0, max: 300, value: |profile| &mut profile.remote_bomb_detonation_wait },
+    Parameter { name: "opponent_not_passable_timeout", min: 0, max: 300, value: |profile| &mut profile.opponent_not_passable_timeout },
+];
+
+/// 遺伝的アルゴリズムの設定
+#[derive(Clone)]
+pub struct TunerSettings {
+    pub match_settings: MatchSettings,
+    // 比べる相手（候補はこの参加者と同じ戦略、強さで、性格だけを変える）
+    pub baseline: Entrant,
+    // 1世代の候補の数
+    pub population_size: usize,
+    // 次の世代にそのまま残す上位の候補の数
+    pub elite_count: usize,
+    // 1世代で使うシードの数（候補ごとに、出現位置を入れ替えてシードごとに2試合する）
+    pub seeds_per_generation: u64,
+    // 子の各項目を変化させる確率と、変化の大きさ（範囲に対する標準偏差の割合）
+    pub mutation_rate: f64,
+    pub mutation_scale: f64,
+    // 対戦を進めるスレッドの数
+    pub threads: usize,
+}
+
+impl Default for TunerSettings {
+    fn default() -> Self {
+        TunerSettings {
+            match_settings: MatchSettings::default(),
+            baseline: Entrant::new(StrategyKind::Standard, Difficulty::Expert, AIProfile::default()),
+            population_size: 16,
+            elite_count: 4,
+            seeds_per_generation: 10,
+            mutation_rate: 0.3,
+            mutation_scale: 0.1,
+            threads: 1,
+        }
+    }
+}
+
+/// 1世代の結果
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GenerationSummary {
+    pub generation: u32,
+    // 比べる相手に対する勝率の最大値と平均値
+    pub best_win_rate: f64,
+    pub average_win_rate: f64,
+    pub best_profile: AIProfile,
+}
+
+/// チェックポイントを作ったときの設定（結果が変わる項目だけで、スレッドの数は含めない）
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointSettings {
+    pub baseline_strategy: String,
+    pub baseline_difficulty: String,
+    pub baseline_profile: AIProfile,
+    pub population_size: usize,
+    pub elite_count: usize,
+    pub seeds_per_generation: u64,
+    pub mutation_rate: f64,
+    pub mutation_scale: f64,
+    // ルールとステージは Rule::to_text、Stage::to_text の形式
+    pub rule: String,
+    pub stage: String,
+    pub max_frames: i32,
+}
+
+impl CheckpointSettings {
+    pub fn new(settings: &TunerSettings) -> CheckpointSettings {
+        let match_settings = &settings.match_settings;
+        CheckpointSettings {
+            baseline_strategy: settings.baseline.strategy_kind.name().to_string(),
+            baseline_difficulty: settings.baseline.difficulty.name().to_string(),
+            baseline_profile: settings.baseline.profile,
+            population_size: settings.population_size,
+            elite_count: settings.elite_count,
+            seeds_per_generation: settings.seeds_per_generation,
+            mutation_rate: settings.mutation_rate,
+            mutation_scale: settings.mutation_scale,
+            rule: match_settings.rule.to_text(),
+            stage: match_settings.stage.to_text(),
+            max_frames: match_settings.max_frames,
+        }
+    }
+
+    /// other と違う項目の名前
+    pub fn differences(&self, other: &CheckpointSettings) -> Vec<&'static str> {
+        let differences = [
+            ("baseline", (&self.baseline_strategy, &self.baseline_difficulty, &self.baseline_profile) != (&other.baseline_strategy, &other.baseline_difficulty, &other.baseline_profile)),
+            ("population", self.population_size != other.population_size),
+            ("elites", self.elite_count != other.elite_count),
+            ("seeds", self.seeds_per_generation != other.seeds_per_generation),
+            ("mutation-rate", self.mutation_rate != other.mutation_rate),
+            ("mutation-scale", self.mutation_scale != other.mutation_scale),
+            ("rule", self.rule != other.rule),
+            ("stage", self.stage != other.stage),
+            ("max-frames", self.max_frames != other.max_frames),
+        ];
+        differences.iter().filter(|(_, differs)| *differs).map(|(name, _)| *name).collect()
+    }
+}
+
+/// 途中から再開するための状態
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    // 乱数のシード（世代ごとにこのシードと世代の番号から乱数を作るので、再開しても同じ結果になる）
+    pub seed: u64,
+    // 作ったときの設定（違う設定で再開すると、同じ結果にならない）
+    pub settings: CheckpointSettings,
+    // 次に評価する世代の番号と、その世代の候補
+    pub generation: u32,
+    pub population: Vec<AIProfile>,
+    pub history: Vec<GenerationSummary>,
+}
+
+impl Checkpoint {
+    /// 比べる相手の性格と、それを変化させた候補で始める
+    pub fn new(settings: &TunerSettings, seed: u64) -> Checkpoint {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let base = settings.baseline.profile;
+        let population = (0..settings.population_size.max(1))
+            .map(|i| if i == 0 { base } else { mutate(&base, 1.0, settings.mutation_scale, &mut rng) })
+            .collect();
+        Checkpoint {
+            seed,
+            settings: CheckpointSettings::new(settings),
+            generation: 0,
+            population,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Checkpoint, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read checkpoint {}: {}", path.display(), e))?;
+        serde_json::from_str::<Checkpoint>(&text).map_err(|e| format!("Invalid checkpoint {}: {}", path.display(), e))
+    }
+
+    /// 作ったときと同じ設定とシードで再開しようとしているか確かめる
+    pub fn check_settings(&self, settings: &TunerSettings, seed: u64) -> Result<(), String> {
+        let mut differences = self.settings.differences(&CheckpointSettings::new(settings));
+        if seed != self.seed {
+            differences.push("seed");
+        }
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("The checkpoint was created with different settings: {} (specify the same options as the first run)", differences.join(", ")))
+        }
+    }
+
+    /// これまでの世代で、一番よい候補の勝率が最も高かった世代（同じ勝率なら後の世代）
+    pub fn best(&self) -> Option<&GenerationSummary> {
+        self.history.iter().max_by(|a, b| a.best_win_rate.total_cmp(&b.best_win_rate))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        // 書き込み中に止めてもチェックポイントが壊れないように、別のファイルに書いてから置き換える
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, text).map_err(|e| format!("Failed to write checkpoint {}: {}", temporary.display(), e))?;
+        fs::rename(&temporary, path).map_err(|e| format!("Failed to write checkpoint {}: {}", path.display(), e))
+    }
+
+    /// 今の世代を評価して次の世代を作り、その世代の結果を返す
+    pub fn run_generation(&mut self, settings: &TunerSettings) -> &GenerationSummary {
+        let win_rates = evaluate(settings, &self.population, self.seed.wrapping_add(self.generation as u64 * settings.seeds_per_generation));
+        let mut ranked: Vec<(AIProfile, f64)> = self.population.iter().copied().zip(win_rates).collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        self.history.push(GenerationSummary {
+            generation: self.generation,
+            best_win_rate: ranked[0].1,
+            average_win_rate: ranked.iter().map(|(_, win_rate)| win_rate).sum::<f64>() / ranked.len() as f64,
+            best_profile: ranked[0].0,
+        });
+        let mut rng = StdRng::seed_from_u64(self.seed ^ (self.generation as u64 + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        self.population = next_population(settings, &ranked, &mut rng);
+        self.generation += 1;
+        self.history.last().unwrap()
+    }
+}
+
+/// 各候補を比べる相手と対戦させ、勝率を返す（シードは first_seed から seeds_per_generation 個）
+pub fn evaluate(settings: &TunerSettings, population: &[AIProfile], first_seed: u64) -> Vec<f64> {
+    let baseline = &settings.baseline;
+    let mut entrants = vec![baseline.clone()];
+    entrants.extend(population.iter().enumerate().map(|(i, profile)| Entrant {
+        name: format!("candidate {}", i),
+        profile: *profile,
+        ..baseline.clone()
+    }));
+    let mut pairings = Vec::new();
+    for seed in first_seed..first_seed + settings.seeds_per_generation {
+        for candidate in 1..entrants.len() {
+            pairings.push(Pairing { seed, entrants: vec![0, candidate] });
+            pairings.push(Pairing { seed, entrants: vec![candidate, 0] });
+        }
+    }
+    let results = match_runner::play_matches(&settings.match_settings, &entrants, &pairings, settings.threads);
+    let report = TournamentReport::new(&entrants, &results);
+    report.entrants[1..].iter().map(|summary| summary.win_rate).collect()
+}
+
+/// 勝率の高い順に並べた候補から次の世代を作る（上位はそのまま残し、残りは上位から選んだ2つを交叉して変化させる）
+fn next_population(settings: &TunerSettings, ranked: &[(AIProfile, f64)], rng: &mut StdRng) -> Vec<AIProfile> {
+    let elite_count = settings.elite_count.clamp(1, ranked.len());
+    let mut population: Vec<AIProfile> = ranked[..elite_count].iter().map(|(profile, _)| *profile).collect();
+    while population.len() < settings.population_size.max(1) {
+        let a = select(ranked, rng);
+        let b = select(ranked, rng);
+        let child = crossover(a, b, rng);
+        population.push(mutate(&child, settings.mutation_rate, settings.mutation_scale, rng));
+    }
+    population
+}
+
+/// 2つの候補から勝率の高いほうを選ぶ（トーナメント選択）
+fn select<'a>(ranked: &'a [(AIProfile, f64)], rng: &mut StdRng) -> &'a AIProfile {
+    let a = rng.gen_range(0, ranked.len());
+    let b = rng.gen_range(0, ranked.len());
+    // 勝率の高い順に並んでいるので、番号の小さいほうを選ぶ
+    &ranked[a.min(b)].0
+}
+
+/// 項目ごとにどちらかの親の値を選ぶ
+fn crossover(a: &AIProfile, b: &AIProfile, rng: &mut StdRng) -> AIProfile {
+    let mut child = *a;
+    let mut other = *b;
+    for parameter in PARAMETERS.iter() {
+        if rng.gen_bool(0.5) {
+            *(parameter.value)(&mut child) = *(parameter.value)(&mut other);
+        }
+    }
+    child
+}
+
+/// 各項目を rate の確率で、範囲の scale 倍を標準偏差とする正規分布で変化させる
+fn mutate(profile: &AIProfile, rate: f64, scale: f64, rng: &mut StdRng) -> AIProfile {
+    let mut mutated = *profile;
+    for parameter in PARAMETERS.iter() {
+        if rng.gen_bool(rate.clamp(0.0, 1.0)) {
+            let value = (parameter.value)(&mut mutated);
+            let delta = rng.sample(StandardNormal) * scale * (parameter.max - parameter.min) as f64;
+            *value = (*value as f64 + delta).round().clamp(parameter.min as f64, parameter.max as f64) as i32;
+        }
+    }
+    // 爆発している場所のリスク値は爆弾のリスク値以上でなければならない
+    mutated.risk_of_explosion = mutated.risk_of_explosion.max(mutated.risk_of_bomb);
+    mutated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{stage::Stage, test_support::TEST_STAGE};
+
+    /// 小さなステージで、弱い AI を少ない候補と試合数で調整する設定
+    fn small_settings() -> TunerSettings {
+        TunerSettings {
+            match_settings: MatchSettings { stage: Stage::parse(TEST_STAGE).unwrap(), max_frames: 600, ..MatchSettings::default() },
+            baseline: Entrant::new(StrategyKind::Standard, Difficulty::Easy, AIProfile::default()),
+            population_size: 3,
+            elite_count: 1,
+            seeds_per_generation: 1,
+            ..TunerSettings::default()
+        }
+    }
+
+    fn summary(generation: u32, best_win_rate: f64) -> GenerationSummary {
+        GenerationSummary { generation, best_win_rate, average_win_rate: 0.0, best_profile: AIProfile::default() }
+    }
+
+    #[test]
+    fn resumed_run_gives_the_same_result_as_an_uninterrupted_run() {
+        let settings = small_settings();
+        let mut uninterrupted = Checkpoint::new(&settings, 7);
+        for _ in 0..3 {
+            uninterrupted.run_generation(&settings);
+        }
+        // 弱い AI 同士なので、時間切れにならずに決着がつく試合がある
+        assert!(uninterrupted.history.iter().any(|summary| summary.best_win_rate > 0.0));
+
+        let path = std::env::temp_dir().join(format!("rust-bomber-test-{}.json", std::process::id()));
+        let mut checkpoint = Checkpoint::new(&settings, 7);
+        checkpoint.run_generation(&settings);
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path);
+        fs::remove_file(&path).unwrap();
+        let mut resumed = loaded.unwrap();
+        resumed.check_settings(&TunerSettings { threads: 2, ..settings.clone() }, 7).unwrap();
+        for _ in 0..2 {
+            resumed.run_generation(&settings);
+        }
+
+        assert_eq!(resumed.generation, 3);
+        assert_eq!(serde_json::to_string(&resumed).unwrap(), serde_json::to_string(&uninterrupted).unwrap());
+    }
+
+    #[test]
+    fn resuming_with_different_settings_is_rejected() {
+        let settings = small_settings();
+        let checkpoint = Checkpoint::new(&settings, 7);
+        let changed = TunerSettings {
+            population_size: 4,
+            match_settings: MatchSettings { max_frames: 900, ..settings.match_settings.clone() },
+            ..settings.clone()
+        };
+        assert_eq!(
+            checkpoint.check_settings(&changed, 8).unwrap_err(),
+            "The checkpoint was created with different settings: population, max-frames, seed (specify the same options as the first run)",
+        );
+    }
+
+    #[test]
+    fn best_generation_is_kept_across_the_history() {
+        let mut checkpoint = Checkpoint::new(&small_settings(), 7);
+        assert!(checkpoint.best().is_none());
+        checkpoint.history = vec![summary(0, 0.25), summary(1, 0.75), summary(2, 0.5), summary(3, 0.75), summary(4, 0.25)];
+        assert_eq!(checkpoint.best().unwrap().generation, 3);
+    }
+}
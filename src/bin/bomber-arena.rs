@@ -0,0 +1,122 @@
+// 描画せずに AI 同士を総当たりで対戦させ、成績を集計する
+//
+//   cargo run --release --bin bomber-arena -- standard hunter:hard passive@res/ai_profile/aggressive.json --seeds 0..100 --output arena
+
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Instant;
+
+use rust_bomber::ai::difficulty::Difficulty;
+use rust_bomber::ai::ai_profile::AIProfile;
+use rust_bomber::ai::strategy::StrategyKind;
+use rust_bomber::arena::entrant::Entrant;
+use rust_bomber::arena::match_runner::{self, MatchSettings};
+use rust_bomber::arena::report::TournamentReport;
+use rust_bomber::game::stage::Stage;
+
+// シードを指定しなかったときに使うシードの数
+const DEFAULT_SEED_COUNT: u64 = 10;
+
+/// コマンドライン引数で指定できるオプション
+struct ArenaOptions {
+    // 参加者（"<戦略>[:<強さ>][@<性格ファイル>]"、指定しなければすべての戦略の expert）
+    entrants: Vec<Entrant>,
+
+    // 使うシード（--seeds <開始>..<終了> または --seeds <シード>,<シード>,...）
+    seeds: Vec<u64>,
+
+    // ルール、ステージ、時間切れまでのフレーム数（--rule <名前>=<値>、--stage <ファイル>、--max-frames <フレーム数>）
+    settings: MatchSettings,
+
+    // 対戦を進めるスレッドの数（--threads <数>、デフォルトは CPU の数）
+    threads: usize,
+
+    // 結果を書き出すディレクトリ（--output <ディレクトリ>）
+    output: Option<PathBuf>,
+}
+
+impl ArenaOptions {
+    fn parse() -> Result<ArenaOptions, String> {
+        let mut options = ArenaOptions {
+            entrants: Vec::new(),
+            seeds: (0..DEFAULT_SEED_COUNT).collect(),
+            settings: MatchSettings::default(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            output: None,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} requires a value", arg));
+            match arg.as_str() {
+                "--seeds" => {
+                    options.seeds = Self::parse_seeds(&value()?)?;
+                }
+                "--rule" => {
+                    options.settings.rule.set(&value()?)?;
+                }
+                "--stage" => {
+                    options.settings.stage = Stage::load(Path::new(&value()?))?;
+                }
+                "--max-frames" => {
+                    let value = value()?;
+                    options.settings.max_frames = value.parse::<i32>().ok().filter(|frames| *frames > 0).ok_or(format!("Invalid max frames '{}'", value))?;
+                }
+                "--threads" => {
+                    let value = value()?;
+                    options.threads = value.parse::<usize>().ok().filter(|threads| *threads > 0).ok_or(format!("Invalid thread count '{}'", value))?;
+                }
+                "--output" => {
+                    options.output = Some(PathBuf::from(value()?));
+                }
+                _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+                _ => options.entrants.push(Entrant::parse(&arg)?),
+            }
+        }
+        if options.entrants.is_empty() {
+            options.entrants = StrategyKind::ALL.iter().map(|kind| Entrant::new(*kind, Difficulty::Expert, AIProfile::default())).collect();
+        }
+        if options.entrants.len() < 2 {
+            return Err("At least 2 entrants are required".to_string());
+        }
+        Ok(options)
+    }
+
+    /// "<開始>..<終了>"（終了は含まない）か "<シード>,<シード>,..." の形式のシードの一覧
+    fn parse_seeds(text: &str) -> Result<Vec<u64>, String> {
+        let parse = |seed: &str| seed.trim().parse::<u64>().map_err(|e| format!("Invalid seed '{}': {}", seed, e));
+        let seeds: Vec<u64> = match text.split_once("..") {
+            Some((start, end)) => (parse(start)?..parse(end)?).collect(),
+            None => text.split(',').map(parse).collect::<Result<_, _>>()?,
+        };
+        if seeds.is_empty() {
+            return Err(format!("No seeds in '{}'", text));
+        }
+        Ok(seeds)
+    }
+}
+
+fn main() -> Result<(), String> {
+    let options = ArenaOptions::parse()?;
+
+    let pairings = match_runner::round_robin(options.entrants.len(), &options.seeds);
+    println!("Playing {} matches on {} threads", pairings.len(), options.threads);
+    let start = Instant::now();
+    let results = match_runner::play_matches(&options.settings, &options.entrants, &pairings, options.threads);
+    println!("Finished in {:.1}s", start.elapsed().as_secs_f64());
+
+    let report = TournamentReport::new(&options.entrants, &results);
+    println!("{:<40} {:>6} {:>6} {:>6} {:>6} {:>6} {:>8} {:>10} {:>7}", "ENTRANT", "GAMES", "WIN", "LOSE", "DRAW", "TIME", "WIN%", "FRAMES", "ELO");
+    for summary in report.entrants.iter() {
+        println!(
+            "{:<40} {:>6} {:>6} {:>6} {:>6} {:>6} {:>7.1}% {:>10.1} {:>7.1}  (self kill {:.1}%)",
+            summary.name, summary.games, summary.wins, summary.losses, summary.draws, summary.timeouts,
+            summary.win_rate * 100.0, summary.average_frames, summary.elo, summary.self_kill_rate * 100.0,
+        );
+    }
+
+    if let Some(directory) = &options.output {
+        report.save(directory)?;
+        println!("Wrote report to {}", directory.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,185 @@
+// 描画せずに AI 同士を対戦させ、遺伝的アルゴリズムで AI の性格（AIProfile）の重みを調整する
+//
+//   cargo run --release --bin bomber-tuner -- --baseline standard:expert --generations 30 --output tuner
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Instant;
+
+use rust_bomber::arena::entrant::Entrant;
+use rust_bomber::arena::tuner::{Checkpoint, TunerSettings, PARAMETERS};
+use rust_bomber::game::stage::Stage;
+
+// 世代数と結果を書き出すディレクトリのデフォルト値
+const DEFAULT_GENERATIONS: u32 = 20;
+const DEFAULT_OUTPUT: &str = "tuner";
+
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+const BEST_PROFILE_FILE: &str = "best.toml";
+const HISTORY_FILE: &str = "history.csv";
+
+/// コマンドライン引数で指定できるオプション
+struct TunerOptions {
+    // 遺伝的アルゴリズムと対戦の設定（--baseline <参加者>、--population <数>、--elites <数>、--seeds <数>、
+    // --mutation-rate <確率>、--mutation-scale <割合>、--rule <名前>=<値>、--stage <ファイル>、--max-frames <フレーム数>、--threads <数>）
+    settings: TunerSettings,
+
+    // 何世代目まで進めるか（--generations <数>）
+    generations: u32,
+
+    // 乱数のシード（--seed <シード>）
+    seed: u64,
+
+    // チェックポイントと結果を書き出すディレクトリ（--output <ディレクトリ>）
+    output: PathBuf,
+
+    // チェックポイントから再開するか（--resume）
+    resume: bool,
+}
+
+impl TunerOptions {
+    fn parse() -> Result<TunerOptions, String> {
+        let mut options = TunerOptions {
+            settings: TunerSettings {
+                threads: thread::available_parallelism().map_or(1, |n| n.get()),
+                ..TunerSettings::default()
+            },
+            generations: DEFAULT_GENERATIONS,
+            seed: 0,
+            output: PathBuf::from(DEFAULT_OUTPUT),
+            resume: false,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} requires a value", arg));
+            match arg.as_str() {
+                "--baseline" => {
+                    options.settings.baseline = Entrant::parse(&value()?)?;
+                }
+                "--generations" => {
+                    options.generations = Self::parse_count(&arg, &value()?)? as u32;
+                }
+                "--population" => {
+                    options.settings.population_size = Self::parse_count(&arg, &value()?)?;
+                }
+                "--elites" => {
+                    options.settings.elite_count = Self::parse_count(&arg, &value()?)?;
+                }
+                "--seeds" => {
+                    options.settings.seeds_per_generation = Self::parse_count(&arg, &value()?)? as u64;
+                }
+                "--mutation-rate" => {
+                    options.settings.mutation_rate = Self::parse_fraction(&arg, &value()?)?;
+                }
+                "--mutation-scale" => {
+                    options.settings.mutation_scale = Self::parse_fraction(&arg, &value()?)?;
+                }
+                "--seed" => {
+                    let value = value()?;
+                    options.seed = value.parse::<u64>().map_err(|e| format!("Invalid seed '{}': {}", value, e))?;
+                }
+                "--rule" => {
+                    options.settings.match_settings.rule.set(&value()?)?;
+                }
+                "--stage" => {
+                    options.settings.match_settings.stage = Stage::load(Path::new(&value()?))?;
+                }
+                "--max-frames" => {
+                    let value = value()?;
+                    options.settings.match_settings.max_frames = value.parse::<i32>().ok().filter(|frames| *frames > 0).ok_or(format!("Invalid max frames '{}'", value))?;
+                }
+                "--threads" => {
+                    options.settings.threads = Self::parse_count(&arg, &value()?)?;
+                }
+                "--output" => {
+                    options.output = PathBuf::from(value()?);
+                }
+                "--resume" => {
+                    options.resume = true;
+                }
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+        if options.settings.elite_count > options.settings.population_size {
+            return Err("--elites must not be greater than --population".to_string());
+        }
+        Ok(options)
+    }
+
+    /// 1以上の整数
+    fn parse_count(arg: &str, value: &str) -> Result<usize, String> {
+        value.parse::<usize>().ok().filter(|count| *count > 0).ok_or(format!("Invalid value for {} '{}'", arg, value))
+    }
+
+    /// 0〜1 の値
+    fn parse_fraction(arg: &str, value: &str) -> Result<f64, String> {
+        value.parse::<f64>().ok().filter(|value| (0.0..=1.0).contains(value)).ok_or(format!("Invalid value for {} '{}'", arg, value))
+    }
+}
+
+/// 世代ごとの勝率と一番よい候補の値の CSV
+fn history_csv(checkpoint: &Checkpoint) -> String {
+    let mut csv = String::from("generation,best_win_rate,average_win_rate");
+    for parameter in PARAMETERS.iter() {
+        csv.push(',');
+        csv.push_str(parameter.name);
+    }
+    csv.push('\n');
+    for summary in checkpoint.history.iter() {
+        let mut profile = summary.best_profile;
+        csv.push_str(&format!("{},{:.4},{:.4}", summary.generation, summary.best_win_rate, summary.average_win_rate));
+        for parameter in PARAMETERS.iter() {
+            csv.push_str(&format!(",{}", (parameter.value)(&mut profile)));
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+fn main() -> Result<(), String> {
+    let options = TunerOptions::parse()?;
+    let settings = &options.settings;
+    let checkpoint_path = options.output.join(CHECKPOINT_FILE);
+
+    let mut checkpoint = if options.resume {
+        let checkpoint = Checkpoint::load(&checkpoint_path)?;
+        checkpoint.check_settings(settings, options.seed)?;
+        println!("Resuming from generation {}", checkpoint.generation);
+        checkpoint
+    } else {
+        if checkpoint_path.exists() {
+            return Err(format!("{} already exists (use --resume to continue)", checkpoint_path.display()));
+        }
+        Checkpoint::new(settings, options.seed)
+    };
+    fs::create_dir_all(&options.output).map_err(|e| format!("Failed to create {}: {}", options.output.display(), e))?;
+
+    println!(
+        "Tuning against {} with {} candidates, {} games per candidate, {} threads",
+        settings.baseline.name, settings.population_size, settings.seeds_per_generation * 2, settings.threads,
+    );
+    while checkpoint.generation < options.generations {
+        let start = Instant::now();
+        let summary = checkpoint.run_generation(settings);
+        println!(
+            "Generation {:>3}: best {:>5.1}%  average {:>5.1}%  ({:.1}s)",
+            summary.generation, summary.best_win_rate * 100.0, summary.average_win_rate * 100.0, start.elapsed().as_secs_f64(),
+        );
+        if let Some(best) = checkpoint.best() {
+            best.best_profile.save(&options.output.join(BEST_PROFILE_FILE))?;
+        }
+        let path = options.output.join(HISTORY_FILE);
+        fs::write(&path, history_csv(&checkpoint)).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        checkpoint.save(&checkpoint_path)?;
+    }
+
+    match checkpoint.best() {
+        Some(summary) => println!(
+            "Best profile of generation {} ({:.1}% against {}) is in {}",
+            summary.generation, summary.best_win_rate * 100.0, settings.baseline.name, options.output.join(BEST_PROFILE_FILE).display(),
+        ),
+        None => println!("No generation to run (already at generation {})", checkpoint.generation),
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_bomber::ai::ai_profile::AIProfile;
+use rust_bomber::ai::bot_server::{BotServer, BotServerMode};
+use rust_bomber::ai::difficulty::Difficulty;
+use rust_bomber::ai::neural_network::NeuralNetwork;
+use rust_bomber::ai::strategy::StrategyKind;
+use rust_bomber::game::rule::Rule;
+use rust_bomber::game::world::{MIN_PLAYER_COUNT, MAX_PLAYER_COUNT};
+use crate::game_screen::player_type::{PlayerType, PlayerSlotSettings};
+
+/// コマンドライン引数で指定できるオプション
+pub struct CommandLineOptions {
+    // 乱数のシード（--seed <数値>）
+    pub seed: Option<u64>,
+
+    // 起動直後に再生するリプレイファイル（--replay <ファイル>）
+    pub replay: Option<PathBuf>,
+
+    // タイトル画面で最初に選ばれているルール（--unlimited-bombs、--rule <名前>=<値>）
+    pub rule: Rule,
+
+    // タイトル画面で最初に選ばれている各プレイヤー枠の種類（--player <番号>=<種類>、None は不参加）
+    pub player_slots: [Option<PlayerType>; MAX_PLAYER_COUNT],
+
+    // タイトル画面で最初に選ばれている AI の強さ（--ai-level <強さ>）
+    pub ai_difficulty: Difficulty,
+
+    // 各プレイヤー枠の操作の設定（--ai-profile <番号>=<ファイル>、--bot <番号>=<コマンド>、--bot-timeout <ミリ秒>、--nn-policy <番号>=<ファイル>）
+    pub slot_settings: [PlayerSlotSettings; MAX_PLAYER_COUNT],
+
+    // TCP で接続してくるボットを待ち受けるポートと、ボットを待つ間のゲームの進め方（--bot-server <ポート>、--bot-server-mode <モード>）
+    pub bot_server_port: Option<u16>,
+    pub bot_server_mode: BotServerMode,
+}
+
+impl Default for CommandLineOptions {
+    fn default() -> Self {
+        CommandLineOptions {
+            seed: None,
+            replay: None,
+            rule: Rule::default(),
+            player_slots: [Some(PlayerType::HUMAN), Some(PlayerType::AI(StrategyKind::Standard, Difficulty::Expert)), None, None],
+            ai_difficulty: Difficulty::Expert,
+            slot_settings: Default::default(),
+            bot_server_port: None,
+            bot_server_mode: BotServerMode::Pause,
+        }
+    }
+}
+
+impl CommandLineOptions {
+    pub fn parse() -> Result<CommandLineOptions, String> {
+        let mut options = CommandLineOptions::default();
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} requires a value", arg));
+            match arg.as_str() {
+                "--seed" => {
+                    let value = value()?;
+                    options.seed = Some(value.parse::<u64>().map_err(|e| format!("Invalid seed '{}': {}", value, e))?);
+                }
+                "--replay" => {
+                    options.replay = Some(PathBuf::from(value()?));
+                }
+                "--rule" => {
+                    options.rule.set(&value()?)?;
+                }
+                "--unlimited-bombs" => {
+                    options.rule.unlimited_bombs = true;
+                }
+                "--player" => {
+                    options.set_player_slot(&value()?)?;
+                }
+                "--ai-profile" => {
+                    let value = value()?;
+                    let (slot, file) = Self::parse_player_setting(&value)?;
+                    options.slot_settings[slot].ai_profile = AIProfile::load(Path::new(file))?;
+                }
+                "--bot" => {
+                    let value = value()?;
+                    let (slot, command) = Self::parse_player_setting(&value)?;
+                    if command.trim().is_empty() {
+                        return Err(format!("Bot command for player {} is empty", slot + 1));
+                    }
+                    options.slot_settings[slot].bot_command = Some(command.to_string());
+                    options.player_slots[slot] = Some(PlayerType::EXTERNAL);
+                }
+                "--nn-policy" => {
+                    let value = value()?;
+                    let (slot, file) = Self::parse_player_setting(&value)?;
+                    options.slot_settings[slot].neural_network = Some(Arc::new(NeuralNetwork::load(Path::new(file))?));
+                    options.player_slots[slot] = Some(PlayerType::NEURAL);
+                }
+                "--bot-timeout" => {
+                    let value = value()?;
+                    let millis = value.parse::<u64>().map_err(|e| format!("Invalid bot timeout '{}': {}", value, e))?;
+                    for settings in options.slot_settings.iter_mut() {
+                        settings.bot_timeout = Duration::from_millis(millis);
+                    }
+                }
+                "--bot-server" => {
+                    let value = value()?;
+                    options.bot_server_port = Some(value.parse::<u16>().map_err(|e| format!("Invalid port '{}': {}", value, e))?);
+                }
+                "--bot-server-mode" => {
+                    let value = value()?;
+                    options.bot_server_mode = BotServerMode::from_name(&value).ok_or(format!("Unknown bot server mode '{}'", value))?;
+                }
+                "--ai-level" => {
+                    let value = value()?;
+                    options.ai_difficulty = Difficulty::from_name(&value).ok_or(format!("Unknown AI level '{}'", value))?;
+                }
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+        // AI の強さはすべての AI に共通にする（--player と --ai-level の順番によらない）
+        for player_type in options.player_slots.iter_mut().flatten() {
+            *player_type = player_type.with_difficulty(options.ai_difficulty);
+        }
+        // 参加するプレイヤーは 1P から順に詰めて並んでいる必要がある
+        for slot in 1..MAX_PLAYER_COUNT {
+            if options.player_slots[slot].is_some() && options.player_slots[slot - 1].is_none() {
+                return Err(format!("Player {} cannot join without player {}", slot + 1, slot));
+            }
+        }
+        // TCP で接続してくるボットの待ち受けを開始する
+        if let Some(port) = options.bot_server_port {
+            let bot_server = BotServer::start(port, options.bot_server_mode, options.slot_settings[0].bot_timeout)?;
+            for settings in options.slot_settings.iter_mut() {
+                settings.bot_server = Some(bot_server.clone());
+            }
+        } else if options.player_slots.contains(&Some(PlayerType::REMOTE)) {
+            return Err("Remote players require --bot-server".to_string());
+        }
+        Ok(options)
+    }
+
+    /// "<番号>=<種類>" の形式でプレイヤー枠の種類を設定する（種類は human、none、remote、ai または AI の戦略の名前）
+    fn set_player_slot(&mut self, setting: &str) -> Result<(), String> {
+        let (slot, name) = Self::parse_player_setting(setting)?;
+        self.player_slots[slot] = if name == "none" {
+            if slot < MIN_PLAYER_COUNT {
+                return Err(format!("Player {} must join", slot + 1));
+            }
+            None
+        } else {
+            Some(PlayerType::from_name(name, self.ai_difficulty).ok_or(format!("Unknown player type '{}'", name))?)
+        };
+        Ok(())
+    }
+
+    /// 各プレイヤー枠で選べるボットの種類（外部のボットとニューラルネットワークは指定した枠、TCP のボットはサーバーを起動した場合のみ）
+    pub fn bot_choices(&self) -> [Vec<PlayerType>; MAX_PLAYER_COUNT] {
+        std::array::from_fn(|slot| {
+            let settings = &self.slot_settings[slot];
+            let mut choices = Vec::new();
+            if settings.bot_command.is_some() {
+                choices.push(PlayerType::EXTERNAL);
+            }
+            if settings.bot_server.is_some() {
+                choices.push(PlayerType::REMOTE);
+            }
+            if settings.neural_network.is_some() {
+                choices.push(PlayerType::NEURAL);
+            }
+            choices
+        })
+    }
+
+    /// "<番号>=<値>" の形式の設定をプレイヤー枠の番号（0から）と値に分ける
+    fn parse_player_setting(setting: &str) -> Result<(usize, &str), String> {
+        let (number, value) = setting.split_once('=').ok_or(format!("Invalid player setting '{}' (expected <number>=<value>)", setting))?;
+        match number.parse::<usize>() {
+            Ok(number) if (1..=MAX_PLAYER_COUNT).contains(&number) => Ok((number - 1, value)),
+            _ => Err(format!("Invalid player number '{}'", number)),
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use sdl2::{video::{Window, WindowContext}, render::{Canvas, Texture, TextureCreator}, pixels::Color, rect::Rect, ttf::Sdl2TtfContext, EventPump, keyboard::{Scancode, KeyboardState}, mouse::MouseButton};
+
+use rust_bomber::game::constants::{CHARACTER_SIZE, SCREEN_WIDTH};
+use rust_bomber::game::map_generator::MapStyle;
+use rust_bomber::game::power_up_item::PowerUpKind;
+use rust_bomber::game::rule::Rule;
+use rust_bomber::game::stage::{self, Stage, StageCell};
+use rust_bomber::game::world::MAX_PLAYER_COUNT;
+use crate::game_screen::camera::Camera;
+use crate::game_screen::power_up_item::image_row;
+use crate::game_screen::world_renderer::{Alignment, WorldRenderer, PLAYER_COLORS};
+use crate::screen::{Screen, ScreenEvent};
+
+#[derive(Copy, Clone, PartialEq)]
+enum Tool {
+    // マスの種類を塗る
+    Cell(StageCell),
+    // プレイヤーの出現位置を移す
+    Spawn(usize),
+}
+
+// パレットに並べる道具
+const TOOLS: [Tool; 16] = [
+    Tool::Cell(StageCell::HardWall),
+    Tool::Cell(StageCell::SoftWall),
+    Tool::Cell(StageCell::RandomSoftWall),
+    Tool::Cell(StageCell::Floor),
+    Tool::Spawn(0),
+    Tool::Spawn(1),
+    Tool::Spawn(2),
+    Tool::Spawn(3),
+    Tool::Cell(StageCell::Item(PowerUpKind::FireUp)),
+    Tool::Cell(StageCell::Item(PowerUpKind::BombUp)),
+    Tool::Cell(StageCell::Item(PowerUpKind::SpeedUp)),
+    Tool::Cell(StageCell::Item(PowerUpKind::FullFire)),
+    Tool::Cell(StageCell::Item(PowerUpKind::PowerDown)),
+    Tool::Cell(StageCell::Item(PowerUpKind::Kick)),
+    Tool::Cell(StageCell::Item(PowerUpKind::Remote)),
+    Tool::Cell(StageCell::Item(PowerUpKind::Pierce)),
+];
+
+// マップを表示する範囲
+const MAP_VIEW_Y: i32 = 16;
+const MAP_VIEW_HEIGHT: u32 = 376;
+
+// パレットの位置と間隔
+const PALETTE_X: i32 = 84;
+const PALETTE_Y: i32 = 396;
+const PALETTE_STEP: i32 = 40;
+
+// キーを押し続けたときに繰り返し始めるまでのフレーム数と、繰り返す間隔
+const KEY_REPEAT_DELAY: i32 = 20;
+const KEY_REPEAT_INTERVAL: i32 = 4;
+
+// 壊せる壁の確率を1回に変える量（パーセント）
+const SOFT_WALL_PROBABILITY_STEP: i32 = 5;
+
+// 新しく作るステージの大きさ
+const NEW_STAGE_WIDTH: i32 = 25;
+const NEW_STAGE_HEIGHT: i32 = 15;
+
+// 押し続けているキーのうち、画面が始まる前から押されていたため離すまで無視するものの印
+const IGNORED_KEY: i32 = i32::MIN;
+
+/// キーボードとマウスでステージを作る画面
+pub struct EditorScreen<'a> {
+    // 編集中のステージ
+    stage: Stage,
+
+    // 編集中のステージを保存するファイル（新しいステージの場合はまだ存在しない）
+    path: PathBuf,
+
+    // ステージディレクトリ内のステージファイル
+    stage_paths: Vec<PathBuf>,
+
+    // 保存していない変更がある場合 true
+    is_modified: bool,
+
+    // 変更を捨てる操作をもう一度押すのを待っているキー
+    pending_discard: Option<Scancode>,
+
+    // カーソル位置（マス）
+    cursor_x: i32,
+    cursor_y: i32,
+
+    // 選ばれている道具（TOOLS の番号）
+    tool_index: usize,
+
+    // テストプレイのルール
+    rule: Rule,
+
+    // 画面下部に表示するメッセージ
+    message: String,
+
+    // 押し続けているキーと押し続けているフレーム数
+    held_frames: HashMap<Scancode, i32>,
+
+    // 次のフレームで、押されているキーを離すまで無視する場合 true
+    ignore_held_keys: bool,
+
+    // 前フレームでマウスの左ボタンが押されていた場合 true
+    previous_mouse_left: bool,
+
+    // キャンバスの拡大率（ウィンドウ上のマウスの座標を画面の座標に直すのに使う）
+    canvas_scale: (f32, f32),
+
+    // 描画（テクスチャとフォントを共用する）
+    renderer: WorldRenderer<'a>,
+}
+
+impl EditorScreen<'_> {
+    pub fn new<'a>(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        ttf_context: &'a Sdl2TtfContext,
+        stage_index: usize,
+        rule: Rule,
+    ) -> EditorScreen<'a> {
+        sdl2::mixer::Music::halt();
+        let mut screen = EditorScreen {
+            stage: Stage::new("", NEW_STAGE_WIDTH, NEW_STAGE_HEIGHT),
+            path: PathBuf::new(),
+            stage_paths: Vec::new(),
+            is_modified: false,
+            pending_discard: None,
+            cursor_x: 1,
+            cursor_y: 1,
+            tool_index: 0,
+            rule,
+            message: String::new(),
+            held_frames: HashMap::new(),
+            // タイトル画面で押した決定キーで塗ってしまわないようにする
+            ignore_held_keys: true,
+            previous_mouse_left: false,
+            canvas_scale: (1.0, 1.0),
+            renderer: WorldRenderer::new(texture_creator, ttf_context),
+        };
+        screen.refresh_stage_paths();
+        match screen.stage_paths.get(stage_index).cloned() {
+            Some(path) => screen.open(path),
+            None => screen.new_stage(),
+        }
+        screen
+    }
+
+    fn refresh_stage_paths(&mut self) {
+        match Stage::list_directory(Path::new(stage::STAGE_DIRECTORY)) {
+            Ok(paths) => self.stage_paths = paths,
+            Err(error) => self.message = error,
+        }
+    }
+
+    /// ステージファイルを開く（読み込めない場合は今のステージのままにする）
+    fn open(&mut self, path: PathBuf) {
+        match Stage::load(&path) {
+            Ok(stage) => {
+                self.stage = stage;
+                self.message = format!("OPENED {}", path.display());
+                self.path = path;
+                self.is_modified = false;
+                self.move_cursor(0, 0);
+            }
+            Err(error) => self.message = error,
+        }
+    }
+
+    /// 前後のステージファイルを開く
+    fn open_next(&mut self, step: i32) {
+        if self.stage_paths.is_empty() {
+            return;
+        }
+        let next = match self.stage_paths.iter().position(|path| *path == self.path) {
+            Some(current) => (current as i32 + step).rem_euclid(self.stage_paths.len() as i32) as usize,
+            None => 0,
+        };
+        self.open(self.stage_paths[next].clone());
+    }
+
+    /// まだ使われていないファイル名で新しいステージを作る
+    fn new_stage(&mut self) {
+        let (number, path) = (1..)
+            .map(|n| (n, Path::new(stage::STAGE_DIRECTORY).join(format!("custom_{:02}.{}", n, stage::STAGE_EXTENSION))))
+            .find(|(_, path)| !path.exists())
+            .unwrap();
+        self.stage = Stage::new(&format!("CUSTOM {:02}", number), NEW_STAGE_WIDTH, NEW_STAGE_HEIGHT);
+        self.message = format!("NEW STAGE {}", path.display());
+        self.path = path;
+        self.is_modified = false;
+        self.move_cursor(0, 0);
+    }
+
+    fn save(&mut self) {
+        // Tiled のマップを開いている場合は同じ名前のステージファイルに保存する
+        self.path.set_extension(stage::STAGE_EXTENSION);
+        match self.stage.save(&self.path) {
+            Ok(()) => {
+                self.message = format!("SAVED {}", self.path.display());
+                self.is_modified = false;
+                self.refresh_stage_paths();
+            }
+            Err(error) => self.message = error,
+        }
+    }
+
+    /// 保存していない変更を捨ててよいか確かめる（変更がある場合は同じキーをもう一度押すと捨てる）
+    fn confirm_discard(&mut self, scancode: Scancode) -> bool {
+        if !self.is_modified || self.pending_discard == Some(scancode) {
+            self.pending_discard = None;
+            return true;
+        }
+        self.pending_discard = Some(scancode);
+        self.message = "UNSAVED CHANGES - PRESS AGAIN TO DISCARD".to_string();
+        false
+    }
+
+    /// カーソルを動かす（外周の内側に収める）
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        self.cursor_x = (self.cursor_x + dx).clamp(1, self.stage.width - 2);
+        self.cursor_y = (self.cursor_y + dy).clamp(1, self.stage.height - 2);
+    }
+
+    fn resize(&mut self, dx: i32, dy: i32) {
+        let (width, height) = (self.stage.width, self.stage.height);
+        self.stage.resize(width + dx, height + dy);
+        if (self.stage.width, self.stage.height) != (width, height) {
+            self.is_modified = true;
+            self.move_cursor(0, 0);
+        }
+    }
+
+    /// 指定したマスに道具を使う（外周は壊せない壁のままにする）
+    fn paint(&mut self, x: i32, y: i32, tool: Tool) {
+        if x <= 0 || y <= 0 || x >= self.stage.width - 1 || y >= self.stage.height - 1 {
+            return;
+        }
+        let spawn_here = self.stage.spawn_positions.iter().position(|position| *position == (x, y));
+        match tool {
+            Tool::Cell(cell) => {
+                // プレイヤーの出現位置は床のままにする
+                if spawn_here.is_none() && self.stage.get_cell(x, y) != cell {
+                    self.stage.set_cell(x, y, cell);
+                    self.is_modified = true;
+                }
+            }
+            Tool::Spawn(n) => {
+                if spawn_here != Some(n) {
+                    // 他のプレイヤーの出現位置だった場合は入れ替える
+                    if let Some(other) = spawn_here {
+                        self.stage.spawn_positions[other] = self.stage.spawn_positions[n];
+                    }
+                    self.stage.spawn_positions[n] = (x, y);
+                    self.stage.set_cell(x, y, StageCell::Floor);
+                    self.is_modified = true;
+                }
+            }
+        }
+    }
+
+    fn camera(&self) -> Camera {
+        Camera::new(
+            Rect::new(0, MAP_VIEW_Y, SCREEN_WIDTH as u32, MAP_VIEW_HEIGHT),
+            self.stage.width * CHARACTER_SIZE,
+            self.stage.height * CHARACTER_SIZE,
+            self.cursor_x * CHARACTER_SIZE + CHARACTER_SIZE / 2,
+            self.cursor_y * CHARACTER_SIZE + CHARACTER_SIZE / 2,
+        )
+    }
+
+    fn palette_rect(i: usize) -> Rect {
+        Rect::new(PALETTE_X + PALETTE_STEP * i as i32, PALETTE_Y, CHARACTER_SIZE as u32, CHARACTER_SIZE as u32)
+    }
+
+    fn tool_name(tool: Tool) -> String {
+        match tool {
+            Tool::Cell(StageCell::HardWall) => "HARD WALL".to_string(),
+            Tool::Cell(StageCell::SoftWall) => "SOFT WALL".to_string(),
+            Tool::Cell(StageCell::RandomSoftWall) => "RANDOM SOFT WALL".to_string(),
+            Tool::Cell(StageCell::Floor) => "FLOOR".to_string(),
+            Tool::Cell(StageCell::Item(kind)) => kind.name().to_uppercase().replace('_', " "),
+            Tool::Spawn(n) => format!("PLAYER {}", n + 1),
+        }
+    }
+
+    /// 道具の絵を描く（マップ上のマスとパレットで共用する）
+    fn draw_tool(&mut self, canvas: &mut Canvas<Window>, tool: Tool, target: Rect) {
+        let size = CHARACTER_SIZE as u32;
+        let (texture, source, alpha): (&mut Texture, Option<Rect>, u8) = match tool {
+            Tool::Cell(StageCell::HardWall) => (&mut self.renderer.wall_image, None, 255),
+            Tool::Cell(StageCell::SoftWall) => (&mut self.renderer.breakable_wall_image, None, 255),
+            // 確率で置かれる壁は半透明にする
+            Tool::Cell(StageCell::RandomSoftWall) => (&mut self.renderer.breakable_wall_image, None, 128),
+            Tool::Cell(StageCell::Floor) => {
+                canvas.set_draw_color(Color::RGB(0, 178, 0));
+                if let Err(error) = canvas.fill_rect(target) {
+                    println!("Failed to fill rect: {}", error);
+                }
+                return;
+            }
+            Tool::Cell(StageCell::Item(kind)) => (&mut self.renderer.power_up_item_image, Some(Rect::new(0, image_row(kind) * CHARACTER_SIZE, size, size)), 255),
+            Tool::Spawn(n) => (&mut self.renderer.player_images[n], Some(Rect::new(0, 0, size, size)), 255),
+        };
+        texture.set_color_mod(255, 255, 255);
+        texture.set_alpha_mod(alpha);
+        texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+        if let Err(error) = canvas.copy(texture, source, Some(target)) {
+            println!("Failed to copy texture {}", error);
+        }
+    }
+
+    fn update_held_keys(&mut self, keyboard_state: &KeyboardState) {
+        let pressed = keyboard_state.pressed_scancodes().collect::<Vec<Scancode>>();
+        self.held_frames.retain(|scancode, _| pressed.contains(scancode));
+        for scancode in pressed {
+            let frames = self.held_frames.entry(scancode).or_insert(if self.ignore_held_keys { IGNORED_KEY } else { 0 });
+            *frames += 1;
+        }
+        self.ignore_held_keys = false;
+    }
+
+    fn is_just_pressed(&self, scancode: Scancode) -> bool {
+        self.held_frames.get(&scancode) == Some(&1)
+    }
+
+    /// 押した瞬間と、押し続けている間の一定間隔で true を返す
+    fn is_repeated(&self, scancode: Scancode) -> bool {
+        match self.held_frames.get(&scancode) {
+            Some(&frames) => frames == 1 || (frames > KEY_REPEAT_DELAY && (frames - KEY_REPEAT_DELAY) % KEY_REPEAT_INTERVAL == 0),
+            None => false,
+        }
+    }
+
+    fn is_held(&self, scancode: Scancode) -> bool {
+        self.held_frames.contains_key(&scancode)
+    }
+}
+
+impl Screen for EditorScreen<'_> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>) {
+        self.canvas_scale = canvas.scale();
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+
+        // マップの描画（表示範囲からはみ出さないようにする）
+        let camera = self.camera();
+        canvas.set_clip_rect(Rect::new(0, MAP_VIEW_Y, SCREEN_WIDTH as u32, MAP_VIEW_HEIGHT));
+        for y in 0..self.stage.height {
+            for x in 0..self.stage.width {
+                let target = camera.to_screen(x * CHARACTER_SIZE, y * CHARACTER_SIZE, CHARACTER_SIZE as u32, CHARACTER_SIZE as u32);
+                self.draw_tool(canvas, Tool::Cell(StageCell::Floor), target);
+                // マス目
+                canvas.set_draw_color(Color::RGB(0, 150, 0));
+                if let Err(error) = canvas.draw_rect(target) {
+                    println!("Failed to draw rect: {}", error);
+                }
+                let cell = self.stage.get_cell(x, y);
+                if cell != StageCell::Floor {
+                    self.draw_tool(canvas, Tool::Cell(cell), target);
+                }
+            }
+        }
+        for n in 0..MAX_PLAYER_COUNT {
+            let (x, y) = self.stage.spawn_positions[n];
+            let target = camera.to_screen(x * CHARACTER_SIZE, y * CHARACTER_SIZE, CHARACTER_SIZE as u32, CHARACTER_SIZE as u32);
+            self.draw_tool(canvas, Tool::Spawn(n), target);
+        }
+
+        // カーソルの描画
+        canvas.set_draw_color(Color::RGB(255, 255, 0));
+        let cursor = camera.to_screen(self.cursor_x * CHARACTER_SIZE, self.cursor_y * CHARACTER_SIZE, CHARACTER_SIZE as u32, CHARACTER_SIZE as u32);
+        if let Err(error) = canvas.draw_rect(cursor) {
+            println!("Failed to draw rect: {}", error);
+        }
+
+        canvas.set_clip_rect(None);
+
+        // パレットの描画
+        for (i, tool) in TOOLS.iter().enumerate() {
+            self.draw_tool(canvas, *tool, Self::palette_rect(i));
+        }
+        let selected = Self::palette_rect(self.tool_index);
+        canvas.set_draw_color(Color::RGB(255, 255, 0));
+        if let Err(error) = canvas.draw_rect(Rect::new(selected.x() - 3, selected.y() - 3, selected.width() + 6, selected.height() + 6)) {
+            println!("Failed to draw rect: {}", error);
+        }
+
+        // ステージの情報とメッセージの描画
+        let font = &self.renderer.font16;
+        let header = format!(
+            "{}{}  {}  {}x{}  RANDOM WALL {}%",
+            self.path.file_name().map_or(String::new(), |name| name.to_string_lossy().to_string()),
+            if self.is_modified { "*" } else { "" },
+            self.stage.name,
+            self.stage.width,
+            self.stage.height,
+            self.stage.soft_wall_probability,
+        );
+        self.renderer.draw_text(canvas, Rect::new(0, 0, SCREEN_WIDTH as u32, 16), &header, Color::RGB(255, 255, 255), font, Alignment::Left);
+        let tool = TOOLS[self.tool_index];
+        let tool_color = if let Tool::Spawn(n) = tool { PLAYER_COLORS[n] } else { Color::RGB(255, 255, 255) };
+        self.renderer.draw_text(canvas, Rect::new(0, 0, SCREEN_WIDTH as u32, 16), &Self::tool_name(tool), tool_color, font, Alignment::Right);
+        self.renderer.draw_text(canvas, Rect::new(0, 432, SCREEN_WIDTH as u32, 16), &self.message, Color::RGB(255, 255, 0), font, Alignment::Center);
+        self.renderer.draw_text(canvas, Rect::new(0, 448, SCREEN_WIDTH as u32, 16), "ARROW MOVE  SPACE PAINT  DEL ERASE  Q/E TOOL  SHIFT+ARROW SIZE  -/= RANDOM WALL", Color::RGB(160, 160, 160), font, Alignment::Center);
+        self.renderer.draw_text(canvas, Rect::new(0, 464, SCREEN_WIDTH as u32, 16), "F2 SAVE  F5 TEST PLAY  PGUP/PGDN OPEN  N NEW  ESC TITLE", Color::RGB(160, 160, 160), font, Alignment::Center);
+
+        canvas.present();
+    }
+
+    fn on_next_frame(&mut self, event_pump: &EventPump) -> ScreenEvent {
+        let keyboard_state = event_pump.keyboard_state();
+        self.update_held_keys(&keyboard_state);
+
+        // 変更を捨てる操作以外のキーが押されたら確認を取り消す
+        if self.held_frames.iter().any(|(scancode, frames)| *frames == 1 && Some(*scancode) != self.pending_discard) {
+            self.pending_discard = None;
+        }
+
+        // カーソルの移動とマップの大きさの変更
+        let is_shift_held = self.is_held(Scancode::LShift) || self.is_held(Scancode::RShift);
+        for (scancode, dx, dy) in [(Scancode::Left, -1, 0), (Scancode::Right, 1, 0), (Scancode::Up, 0, -1), (Scancode::Down, 0, 1)] {
+            if self.is_repeated(scancode) {
+                if is_shift_held {
+                    self.resize(dx, dy);
+                } else {
+                    self.move_cursor(dx, dy);
+                }
+            }
+        }
+
+        // 道具の切り替え
+        if self.is_repeated(Scancode::Q) {
+            self.tool_index = (self.tool_index + TOOLS.len() - 1) % TOOLS.len();
+        } else if self.is_repeated(Scancode::E) {
+            self.tool_index = (self.tool_index + 1) % TOOLS.len();
+        }
+
+        // カーソル位置に塗る、消す
+        if self.is_held(Scancode::Space) || self.is_held(Scancode::Return) {
+            self.paint(self.cursor_x, self.cursor_y, TOOLS[self.tool_index]);
+        } else if self.is_held(Scancode::Delete) || self.is_held(Scancode::Backspace) {
+            self.paint(self.cursor_x, self.cursor_y, Tool::Cell(StageCell::Floor));
+        }
+
+        // 壊せる壁の確率の変更
+        if self.is_repeated(Scancode::Minus) || self.is_repeated(Scancode::Equals) {
+            let step = if self.is_held(Scancode::Minus) { -SOFT_WALL_PROBABILITY_STEP } else { SOFT_WALL_PROBABILITY_STEP };
+            self.stage.soft_wall_probability = (self.stage.soft_wall_probability + step).clamp(0, 100);
+            self.is_modified = true;
+        }
+
+        // マウスでパレットを選ぶ、マップに塗る（右ボタンで消す）
+        let mouse_state = event_pump.mouse_state();
+        let mouse_x = (mouse_state.x() as f32 / self.canvas_scale.0) as i32;
+        let mouse_y = (mouse_state.y() as f32 / self.canvas_scale.1) as i32;
+        let is_mouse_left = mouse_state.is_mouse_button_pressed(MouseButton::Left);
+        let is_mouse_right = mouse_state.is_mouse_button_pressed(MouseButton::Right);
+        if is_mouse_left && !self.previous_mouse_left {
+            if let Some(i) = (0..TOOLS.len()).find(|i| Self::palette_rect(*i).contains_point((mouse_x, mouse_y))) {
+                self.tool_index = i;
+            }
+        }
+        if is_mouse_left || is_mouse_right {
+            if let Some((x, y)) = self.camera().to_map(mouse_x, mouse_y) {
+                let tool = if is_mouse_left { TOOLS[self.tool_index] } else { Tool::Cell(StageCell::Floor) };
+                self.paint(x.div_euclid(CHARACTER_SIZE), y.div_euclid(CHARACTER_SIZE), tool);
+            }
+        }
+        self.previous_mouse_left = is_mouse_left;
+
+        // ファイルの操作
+        if self.is_just_pressed(Scancode::F2) {
+            self.save();
+        } else if self.is_just_pressed(Scancode::PageUp) && self.confirm_discard(Scancode::PageUp) {
+            self.open_next(-1);
+        } else if self.is_just_pressed(Scancode::PageDown) && self.confirm_discard(Scancode::PageDown) {
+            self.open_next(1);
+        } else if self.is_just_pressed(Scancode::N) && self.confirm_discard(Scancode::N) {
+            self.new_stage();
+        }
+
+        // テストプレイ
+        if self.is_just_pressed(Scancode::F5) {
+            match self.stage.validate() {
+                Ok(()) => {
+                    // 編集中の地形を確かめるので、マップの生成方法の設定にかかわらずステージの地形をそのまま使う
+                    let rule = Rule { map_style: MapStyle::Stage, ..self.rule };
+                    return ScreenEvent::TestPlayStage(rule, self.stage.clone());
+                }
+                Err(error) => self.message = error,
+            }
+        }
+
+        // タイトル画面に戻る
+        if self.is_just_pressed(Scancode::Escape) && self.confirm_discard(Scancode::Escape) {
+            return ScreenEvent::ReturnToTitleScreen;
+        }
+
+        ScreenEvent::None
+    }
+
+    fn on_resume(&mut self) {
+        // ゲーム画面を抜けるときに押した Esc キーでタイトル画面に戻ってしまわないようにする
+        self.ignore_held_keys = true;
+        self.message = "BACK FROM TEST PLAY".to_string();
+    }
+}
@@ -0,0 +1,16 @@
+pub mod bomb;
+pub mod constants;
+pub mod explosion;
+pub mod light_sprite;
+pub mod map_generator;
+pub mod player_input;
+pub mod player;
+pub mod power_up_item;
+pub mod replay;
+pub mod rule;
+pub mod stage;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod tiled;
+pub mod wall;
+pub mod world;
@@ -0,0 +1,99 @@
+use super::{light_sprite::LightSprite, light_sprite::LightSpriteEvent, constants, player_input::Movement};
+
+// 蹴られた爆弾が1フレームに移動するピクセル数（CHARACTER_SIZE を割り切れる値にする）
+pub const SLIDE_SPEED: i32 = 4;
+
+#[derive(Clone)]
+pub struct Bomb {
+    x: i32,
+    y: i32,
+    pub power: i32,
+    // 爆弾を置いたプレイヤーの番号
+    pub owner: usize,
+    // 蹴られて滑っている方向（止まっている場合は NONE）
+    pub slide: Movement,
+    // リモコン爆弾の場合 true（時間では爆発せず、置いたプレイヤーの操作で爆発する）
+    pub remote: bool,
+    // 貫通する爆弾の場合 true（爆発の範囲内の壊せる壁をすべて壊す）
+    pub pierce: bool,
+    pub move_time: f32,
+    pub remain_time: i32,
+}
+
+impl Bomb {
+    pub fn new(x: i32, y: i32, power: i32, owner: usize) -> Bomb {
+        Bomb {
+            x,
+            y,
+            power,
+            owner,
+            slide: Movement::NONE,
+            remote: false,
+            pierce: false,
+            move_time: 0f32,
+            remain_time: constants::BOMB_TIME,
+        }
+    }
+
+    pub fn is_sliding(&self) -> bool {
+        !matches!(self.slide, Movement::NONE)
+    }
+
+    /// 一番近いマスの座標（滑っている途中でもマス単位の位置を返す）
+    pub fn get_tile_x(&self) -> i32 {
+        (self.x + constants::CHARACTER_SIZE / 2) / constants::CHARACTER_SIZE * constants::CHARACTER_SIZE
+    }
+
+    pub fn get_tile_y(&self) -> i32 {
+        (self.y + constants::CHARACTER_SIZE / 2) / constants::CHARACTER_SIZE * constants::CHARACTER_SIZE
+    }
+
+    /// マスの境目にいる場合 true
+    pub fn is_on_tile(&self) -> bool {
+        self.x % constants::CHARACTER_SIZE == 0 && self.y % constants::CHARACTER_SIZE == 0
+    }
+
+    /// 次のフレームで爆発させる（リモコン爆弾の起爆や誘爆に使う）
+    pub fn explode_soon(&mut self) {
+        self.remote = false;
+        self.remain_time = 1;
+    }
+
+    /// 指定した方向に蹴る
+    pub fn kick(&mut self, direction: Movement) {
+        self.slide = direction;
+    }
+
+    /// 滑るのをやめて一番近いマスに止まる
+    pub fn stop(&mut self) {
+        self.x = self.get_tile_x();
+        self.y = self.get_tile_y();
+        self.slide = Movement::NONE;
+    }
+
+    /// 滑っている方向に1フレーム分移動する
+    pub fn slide_for_next_frame(&mut self) {
+        let (dx, dy) = self.slide.delta();
+        self.x += dx * SLIDE_SPEED;
+        self.y += dy * SLIDE_SPEED;
+    }
+}
+
+impl LightSprite for Bomb {
+    fn get_x(&self) -> i32 { self.x }
+    fn get_y(&self) -> i32 { self.y }
+
+    fn on_next_frame(&mut self) -> LightSpriteEvent {
+        self.move_time += 1.0 / 60.0;
+
+        // タイムアウトしたら爆発する（リモコン爆弾は時間では爆発しない）
+        if !self.remote {
+            self.remain_time -= 1;
+        }
+        if self.remain_time <= 0 {
+            LightSpriteEvent::DeleteMe
+        } else {
+            LightSpriteEvent::None
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub const CHARACTER_SIZE: i32 = 32;
+pub const SCREEN_WIDTH: i32 = 800;
+pub const SCREEN_HEIGHT: i32 = 480;
+pub const BOMB_TIME: i32 = 5 * 60;
+// 爆発してから炎が消えるまでのフレーム数
+pub const EXPLOSION_TIME: i32 = 30;
\ No newline at end of file
@@ -0,0 +1,48 @@
+use super::{constants, light_sprite::LightSprite, light_sprite::LightSpriteEvent};
+
+#[derive(Copy, Clone)]
+pub enum Position {
+    CENTER = 0,
+    VERTICAL,
+    HORIZONTAL,
+    LEFT,
+    TOP,
+    RIGHT,
+    BOTTOM,
+}
+
+#[derive(Clone)]
+pub struct Explosion {
+    x: i32,
+    y: i32,
+    pub position: Position,
+    pub remain_time: i32,
+    // 爆発した爆弾を置いたプレイヤーの番号
+    pub owner: usize,
+}
+
+impl Explosion {
+    pub fn new(x: i32, y: i32, position: Position, owner: usize) -> Explosion {
+        Explosion {
+            x,
+            y,
+            position,
+            remain_time: constants::EXPLOSION_TIME,
+            owner,
+        }
+    }
+}
+
+impl LightSprite for Explosion {
+    fn get_x(&self) -> i32 { self.x }
+    fn get_y(&self) -> i32 { self.y }
+
+    fn on_next_frame(&mut self) -> LightSpriteEvent {
+        self.remain_time -= 1;
+        if self.remain_time <= 0 {
+            LightSpriteEvent::DeleteMe
+        } else {
+            LightSpriteEvent::None
+        }
+    }
+}
@@ -0,0 +1,398 @@
+use std::collections::VecDeque;
+
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use super::rule::Rule;
+use super::stage::{Stage, StageCell};
+
+// 迷路で、行き止まりを減らすために通路どうしの間の壁を追加で壊す確率（パーセント）
+const MAZE_LOOP_PROBABILITY: i32 = 30;
+// 迷路の通路に壊せる壁が置かれる確率のデフォルト値（パーセント）
+const MAZE_SOFT_WALL_DENSITY: i32 = 30;
+
+// アリーナで、偶数座標に壊せない柱を立てる確率（パーセント）
+const ARENA_PILLAR_PROBABILITY: i32 = 35;
+// アリーナで壊せる壁が置かれる確率のデフォルト値（パーセント）
+const ARENA_SOFT_WALL_DENSITY: i32 = 20;
+
+/// マップの地形の作り方
+#[derive(Copy, Clone, PartialEq)]
+pub enum MapStyle {
+    // ステージの地形をそのまま使う
+    Stage,
+    // ステージの大きさで迷路を作る
+    Maze,
+    // ステージの大きさで柱がまばらに立つ広い場所を作る
+    Arena,
+}
+
+impl MapStyle {
+    pub const ALL: [MapStyle; 3] = [MapStyle::Stage, MapStyle::Maze, MapStyle::Arena];
+
+    /// ルールの設定やタイトル画面に使う名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Stage => "stage",
+            Self::Maze => "maze",
+            Self::Arena => "arena",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MapStyle> {
+        Self::ALL.iter().copied().find(|style| style.name() == name)
+    }
+
+    pub fn get_generator(&self) -> Box<dyn MapGenerator> {
+        match self {
+            Self::Stage => Box::new(StageGenerator),
+            Self::Maze => Box::new(MazeGenerator),
+            Self::Arena => Box::new(ArenaGenerator),
+        }
+    }
+}
+
+/// 壊せる壁の配置（と生成する地形）の対称性
+#[derive(Copy, Clone, PartialEq)]
+pub enum Symmetry {
+    // 対称にしない
+    None,
+    // 左右と上下の両方に対称にする
+    Mirror,
+    // 中心に対して点対称（180度回転で重なる）にする
+    Rotational,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 3] = [Symmetry::None, Symmetry::Mirror, Symmetry::Rotational];
+
+    /// ルールの設定やタイトル画面に使う名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Mirror => "mirror",
+            Self::Rotational => "rotational",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Symmetry> {
+        Self::ALL.iter().copied().find(|symmetry| symmetry.name() == name)
+    }
+
+    /// 指定したマスと対称な位置にあるマス（自分自身を含む）を上の行、左の列から順に返す
+    pub fn orbit(&self, x: i32, y: i32, width: i32, height: i32) -> Vec<(i32, i32)> {
+        let (mx, my) = (width - 1 - x, height - 1 - y);
+        let mut cells = match self {
+            Self::None => vec![(x, y)],
+            Self::Mirror => vec![(x, y), (mx, y), (x, my), (mx, my)],
+            Self::Rotational => vec![(x, y), (mx, my)],
+        };
+        cells.sort_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+        cells
+    }
+}
+
+/// 地形を作る方法（MapStyle ごとに1つ実装する）
+pub trait MapGenerator {
+    /// ステージの大きさとプレイヤーの出現位置を元に地形を作る（壊せる壁を置く候補のマスは RandomSoftWall にする）
+    fn generate_layout(&self, stage: &Stage, symmetry: Symmetry, rng: &mut StdRng) -> Stage;
+
+    /// 壊せる壁が置かれる確率のデフォルト値（パーセント）
+    fn default_soft_wall_density(&self, stage: &Stage) -> i32;
+}
+
+/// ステージの地形をそのまま使う
+pub struct StageGenerator;
+
+impl MapGenerator for StageGenerator {
+    fn generate_layout(&self, stage: &Stage, _symmetry: Symmetry, _rng: &mut StdRng) -> Stage {
+        stage.clone()
+    }
+
+    fn default_soft_wall_density(&self, stage: &Stage) -> i32 {
+        stage.soft_wall_probability
+    }
+}
+
+/// 通路の幅が1マスの迷路（ところどころに輪になった道がある）
+pub struct MazeGenerator;
+
+impl MapGenerator for MazeGenerator {
+    fn generate_layout(&self, stage: &Stage, symmetry: Symmetry, rng: &mut StdRng) -> Stage {
+        let mut layout = empty_layout(stage, StageCell::HardWall);
+
+        // 奇数座標のマスを部屋にして、隣の部屋との間の壁を壊しながら深さ優先で全部の部屋をつなぐ
+        let (width, height) = (layout.width, layout.height);
+        let is_room = |x: i32, y: i32| x > 0 && y > 0 && x % 2 == 1 && y % 2 == 1 && x < width - 1 && y < height - 1;
+        let mut visited = vec![false; (width * height) as usize];
+        let mut stack = vec![(1, 1)];
+        visited[(width + 1) as usize] = true;
+        layout.set_cell(1, 1, StageCell::RandomSoftWall);
+        while let Some(&(x, y)) = stack.last() {
+            let neighbors = [(-2, 0), (2, 0), (0, -2), (0, 2)].iter()
+                .map(|(dx, dy)| (x + dx, y + dy))
+                .filter(|&(nx, ny)| is_room(nx, ny) && !visited[(ny * width + nx) as usize])
+                .collect::<Vec<(i32, i32)>>();
+            if neighbors.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let (nx, ny) = neighbors[rng.gen_range(0, neighbors.len())];
+            visited[(ny * width + nx) as usize] = true;
+            layout.set_cell((x + nx) / 2, (y + ny) / 2, StageCell::RandomSoftWall);
+            layout.set_cell(nx, ny, StageCell::RandomSoftWall);
+            stack.push((nx, ny));
+        }
+
+        // 行き止まりばかりにならないように部屋の間の壁をいくつか壊す
+        for y in 1..(height - 1) {
+            for x in 1..(width - 1) {
+                let is_between_rooms = (is_room(x - 1, y) && is_room(x + 1, y)) || (is_room(x, y - 1) && is_room(x, y + 1));
+                if is_between_rooms && layout.get_cell(x, y) == StageCell::HardWall && rng.gen_range(0, 100) < MAZE_LOOP_PROBABILITY {
+                    layout.set_cell(x, y, StageCell::RandomSoftWall);
+                }
+            }
+        }
+
+        symmetrize(&mut layout, symmetry);
+        clear_around_spawns(&mut layout);
+        layout
+    }
+
+    fn default_soft_wall_density(&self, _stage: &Stage) -> i32 {
+        MAZE_SOFT_WALL_DENSITY
+    }
+}
+
+/// 壊せない柱がまばらに立つ広い場所
+pub struct ArenaGenerator;
+
+impl MapGenerator for ArenaGenerator {
+    fn generate_layout(&self, stage: &Stage, symmetry: Symmetry, rng: &mut StdRng) -> Stage {
+        let mut layout = empty_layout(stage, StageCell::RandomSoftWall);
+        for y in (2..(layout.height - 1)).step_by(2) {
+            for x in (2..(layout.width - 1)).step_by(2) {
+                if rng.gen_range(0, 100) < ARENA_PILLAR_PROBABILITY {
+                    layout.set_cell(x, y, StageCell::HardWall);
+                }
+            }
+        }
+        symmetrize(&mut layout, symmetry);
+        clear_around_spawns(&mut layout);
+        layout
+    }
+
+    fn default_soft_wall_density(&self, _stage: &Stage) -> i32 {
+        ARENA_SOFT_WALL_DENSITY
+    }
+}
+
+/// ルールの設定に従ってステージから試合のマップを作る
+///
+/// 地形を作ったあと、プレイヤーの出現位置どうしが壊せない壁で分断されていればつなげてから壊せる壁を置く。
+/// 返すステージには RandomSoftWall のマスは残らない（SoftWall か Floor になる）。
+pub fn generate_map(stage: &Stage, rule: &Rule, player_count: usize, rng: &mut StdRng) -> Stage {
+    let generator = rule.map_style.get_generator();
+    let mut layout = generator.generate_layout(stage, rule.symmetry, rng);
+    connect_spawns(&mut layout, rule.symmetry);
+    let density = rule.soft_wall_density.unwrap_or_else(|| generator.default_soft_wall_density(stage));
+    place_soft_walls(&mut layout, density, rule.symmetry, player_count, rng);
+    layout
+}
+
+/// ステージと同じ大きさとプレイヤーの出現位置で、外周以外を指定したマスで埋めた地形
+fn empty_layout(stage: &Stage, fill: StageCell) -> Stage {
+    let mut layout = Stage::new(&stage.name, stage.width, stage.height);
+    layout.spawn_positions = stage.spawn_positions.clone();
+    for y in 1..(layout.height - 1) {
+        for x in 1..(layout.width - 1) {
+            layout.set_cell(x, y, fill);
+        }
+    }
+    layout
+}
+
+/// 対称な位置にあるマスを、その中で一番左上にあるマスと同じにする
+fn symmetrize(layout: &mut Stage, symmetry: Symmetry) {
+    for y in 0..layout.height {
+        for x in 0..layout.width {
+            let (rx, ry) = symmetry.orbit(x, y, layout.width, layout.height)[0];
+            layout.set_cell(x, y, layout.get_cell(rx, ry));
+        }
+    }
+}
+
+/// プレイヤーの出現位置とその上下左右を壊せない壁でなくす（最初の爆弾から逃げられるようにする）
+fn clear_around_spawns(layout: &mut Stage) {
+    let spawn_positions = layout.spawn_positions.clone();
+    for &(sx, sy) in spawn_positions.iter() {
+        for (x, y) in [(sx, sy), (sx - 1, sy), (sx + 1, sy), (sx, sy - 1), (sx, sy + 1)] {
+            if x > 0 && y > 0 && x < layout.width - 1 && y < layout.height - 1 && layout.get_cell(x, y) == StageCell::HardWall {
+                layout.set_cell(x, y, StageCell::RandomSoftWall);
+            }
+        }
+    }
+    for &(sx, sy) in spawn_positions.iter() {
+        layout.set_cell(sx, sy, StageCell::Floor);
+    }
+}
+
+/// 1P の出現位置から、壊せない壁を壊す数が最も少ない道で他のプレイヤーの出現位置までつなぐ（外周は壊さない）
+fn connect_spawns(layout: &mut Stage, symmetry: Symmetry) {
+    let width = layout.width;
+    let index = |x: i32, y: i32| (y * width + x) as usize;
+    let (start_x, start_y) = layout.spawn_positions[0];
+    let mut costs = vec![i32::MAX; (layout.width * layout.height) as usize];
+    let mut previous: Vec<Option<(i32, i32)>> = vec![None; costs.len()];
+    let mut queue = VecDeque::new();
+    costs[index(start_x, start_y)] = 0;
+    queue.push_back((start_x, start_y));
+    while let Some((x, y)) = queue.pop_front() {
+        for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+            if nx <= 0 || ny <= 0 || nx >= layout.width - 1 || ny >= layout.height - 1 {
+                continue;
+            }
+            let step = if layout.get_cell(nx, ny) == StageCell::HardWall { 1 } else { 0 };
+            let cost = costs[index(x, y)] + step;
+            if cost < costs[index(nx, ny)] {
+                costs[index(nx, ny)] = cost;
+                previous[index(nx, ny)] = Some((x, y));
+                // 0-1 BFS（壁を壊さずに進めるマスを先に調べる）
+                if step == 0 {
+                    queue.push_front((nx, ny));
+                } else {
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+    }
+    for &(sx, sy) in layout.spawn_positions.clone().iter().skip(1) {
+        let mut position = Some((sx, sy));
+        while let Some((x, y)) = position {
+            if layout.get_cell(x, y) == StageCell::HardWall {
+                for (ox, oy) in symmetry.orbit(x, y, layout.width, layout.height) {
+                    if layout.get_cell(ox, oy) == StageCell::HardWall {
+                        layout.set_cell(ox, oy, StageCell::RandomSoftWall);
+                    }
+                }
+            }
+            position = previous[index(x, y)];
+        }
+    }
+}
+
+/// RandomSoftWall のマスに確率で壊せる壁を置く（対称な位置のマスには同じ結果を使う）
+///
+/// 参加しているプレイヤーの出現位置の近くには置かない。
+fn place_soft_walls(layout: &mut Stage, density: i32, symmetry: Symmetry, player_count: usize, rng: &mut StdRng) {
+    let spawn_positions = layout.spawn_positions[..player_count].to_vec();
+    let is_candidate = |layout: &Stage, x: i32, y: i32| {
+        layout.get_cell(x, y) == StageCell::RandomSoftWall &&
+            !spawn_positions.iter().any(|(sx, sy)| (x - sx).abs() <= 1 && (y - sy).abs() <= 1)
+    };
+    let mut has_soft_wall = vec![false; (layout.width * layout.height) as usize];
+    for y in 0..layout.height {
+        for x in 0..layout.width {
+            let orbit = symmetry.orbit(x, y, layout.width, layout.height);
+            if orbit[0] != (x, y) {
+                continue;
+            }
+            let candidates = orbit.iter().filter(|(ox, oy)| is_candidate(layout, *ox, *oy)).collect::<Vec<_>>();
+            if !candidates.is_empty() && rng.gen_range(0, 100) < density {
+                for (ox, oy) in candidates {
+                    has_soft_wall[(oy * layout.width + ox) as usize] = true;
+                }
+            }
+        }
+    }
+    for y in 0..layout.height {
+        for x in 0..layout.width {
+            if layout.get_cell(x, y) == StageCell::RandomSoftWall {
+                let cell = if has_soft_wall[(y * layout.width + x) as usize] { StageCell::SoftWall } else { StageCell::Floor };
+                layout.set_cell(x, y, cell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    /// 壊せない壁を通らずに 1P の出現位置から行けるマス
+    fn reachable(map: &Stage) -> Vec<bool> {
+        let mut visited = vec![false; (map.width * map.height) as usize];
+        let (x, y) = map.spawn_positions[0];
+        let mut stack = vec![(x, y)];
+        visited[(y * map.width + x) as usize] = true;
+        while let Some((x, y)) = stack.pop() {
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                let i = (ny * map.width + nx) as usize;
+                if map.get_cell(nx, ny) != StageCell::HardWall && !visited[i] {
+                    visited[i] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        visited
+    }
+
+    fn generate(stage: &Stage, map_style: MapStyle, symmetry: Symmetry, seed: u64) -> Stage {
+        let rule = Rule { map_style, symmetry, ..Rule::default() };
+        generate_map(stage, &rule, 4, &mut StdRng::seed_from_u64(seed))
+    }
+
+    #[test]
+    fn every_spawn_position_is_reachable() {
+        for stage in [Stage::classic(), Stage::new("even", 16, 10), Stage::new("small", 7, 5)] {
+            for map_style in MapStyle::ALL {
+                for symmetry in Symmetry::ALL {
+                    for seed in 0..20 {
+                        let map = generate(&stage, map_style, symmetry, seed);
+                        let reachable = reachable(&map);
+                        for &(x, y) in map.spawn_positions.iter() {
+                            assert!(reachable[(y * map.width + x) as usize], "{} {} {} seed {}: ({}, {}) is cut off", stage.name, map_style.name(), symmetry.name(), seed, x, y);
+                            assert!(map.get_cell(x, y) == StageCell::Floor);
+                        }
+                        assert!((0..map.height).all(|y| (0..map.width).all(|x| map.get_cell(x, y) != StageCell::RandomSoftWall)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn symmetric_maps_are_symmetric() {
+        let stage = Stage::classic();
+        for map_style in MapStyle::ALL {
+            for symmetry in [Symmetry::Mirror, Symmetry::Rotational] {
+                for seed in 0..10 {
+                    let map = generate(&stage, map_style, symmetry, seed);
+                    for y in 0..map.height {
+                        for x in 0..map.width {
+                            let cell = map.get_cell(x, y);
+                            for (ox, oy) in symmetry.orbit(x, y, map.width, map.height) {
+                                assert!(map.get_cell(ox, oy) == cell, "{} {} seed {}: ({}, {}) and ({}, {}) differ", map_style.name(), symmetry.name(), seed, x, y, ox, oy);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn soft_wall_density_is_applied() {
+        let stage = Stage::classic();
+        let soft_walls = |density: i32| {
+            let rule = Rule { map_style: MapStyle::Arena, soft_wall_density: Some(density), ..Rule::default() };
+            let map = generate_map(&stage, &rule, 4, &mut StdRng::seed_from_u64(0));
+            (0..map.height).flat_map(|y| (0..map.width).map(move |x| (x, y))).filter(|&(x, y)| map.get_cell(x, y) == StageCell::SoftWall).count()
+        };
+        assert_eq!(soft_walls(0), 0);
+        assert!(soft_walls(30) < soft_walls(70));
+    }
+}
@@ -0,0 +1,396 @@
+use super::light_sprite::{LightSprite, LightSpriteEvent};
+use super::world::{World, WorldEvent};
+use super::player_input::PlayerInput;
+use super::wall::Wall;
+use super::player_input::Movement;
+use super::bomb::Bomb;
+use super::power_up_item::PowerUpKind;
+use super::constants;
+
+// 爆発の範囲の最大値（フルファイアを取るとこの値になる）
+pub const MAX_POWER: i32 = 10;
+
+// 移動速度（1フレームあたりの移動量を 1/4 ピクセル単位で表す）
+pub const DEFAULT_SPEED: i32 = 8;
+pub const MAX_SPEED: i32 = 16;
+// スピードアップ1個あたりの移動速度の上昇量
+const SPEED_UP_STEP: i32 = 2;
+// 1回の移動処理で動くピクセル数（移動速度が上がると1フレームに何回か移動処理を行う）
+const STEP_PIXELS: i32 = 2;
+// 次のフレームに持ち越せる移動量の上限（1/4 ピクセル単位、2回分の移動処理に満たない量）
+const MAX_MOVE_REMAINDER: i32 = STEP_PIXELS * 4 * 2 - 1;
+
+#[derive(Copy, Clone)]
+pub enum Direction {
+    DOWN = 0,
+    LEFT,
+    RIGHT,
+    UP,
+}
+
+#[derive(Clone)]
+pub struct Player {
+    pub player_number: usize,
+    x: i32,
+    y: i32,
+    pub pushed_x: i32,
+    pub pushed_y: i32,
+    pub direction: Direction,
+    pub move_time: f32,
+    pub power: i32,
+    // 同時に置ける爆弾の数
+    pub bomb_capacity: i32,
+    // 移動速度（1/4 ピクセル/フレーム）
+    pub speed: i32,
+    // 爆弾を蹴れる場合 true
+    pub can_kick: bool,
+    // リモコン爆弾を持っている場合 true
+    pub has_remote: bool,
+    // 貫通する爆弾を持っている場合 true
+    pub has_pierce: bool,
+    // 前フレームまでに移動しきれなかった移動量（1/4 ピクセル単位）
+    move_remainder: i32,
+    pub death_state: i32,
+    // 死んだ原因の爆発を起こしたプレイヤーの番号（自分の爆発に巻き込まれていれば自分の番号）
+    pub killed_by: Option<usize>,
+    player_input: PlayerInput,
+    // このフレームで移動した場合 true
+    pub is_walking: bool,
+}
+
+impl Player {
+    pub fn new(player_number: usize, x: i32, y: i32) -> Player {
+        Player {
+            player_number,
+            x,
+            y,
+            pushed_x: 0,
+            pushed_y: 0,
+            direction: Direction::DOWN,
+            move_time: 0f32,
+            power: 1,
+            bomb_capacity: 1,
+            speed: DEFAULT_SPEED,
+            can_kick: false,
+            has_remote: false,
+            has_pierce: false,
+            move_remainder: 0,
+            death_state: 0,
+            killed_by: None,
+            player_input: PlayerInput::new(Movement::NONE, false),
+            is_walking: false,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.death_state > 0
+    }
+
+    pub fn move_for_next_frame(world: &mut World, player_number: usize, player_input: PlayerInput) {
+        let player = world.players.get_mut(player_number).unwrap();
+
+        if player.is_dead() {
+            return;
+        }
+
+        // 移動前の位置を保存しておく
+        let old_x = player.x;
+        let old_y = player.y;
+
+        // 移動速度に応じて1フレームに何回か移動処理を行う
+        player.player_input = player_input;
+        let steps = if let Movement::NONE = player_input.movement {
+            player.move_remainder = 0;
+            0
+        } else {
+            player.move_remainder += player.speed;
+            let steps = player.move_remainder / (STEP_PIXELS * 4);
+            player.move_remainder %= STEP_PIXELS * 4;
+            steps
+        };
+        let can_kick = player.can_kick || world.rule.everyone_can_kick;
+        for step in 0..steps {
+            let (step_x, step_y) = (player.x, player.y);
+            player.move_one_step(&world.walls, &mut world.bombs, can_kick);
+            // 壁や爆弾にぶつかって動けなかった場合、残りの移動量は捨てる
+            if player.x == step_x && player.y == step_y {
+                break;
+            }
+            // マスにぴったり合った位置に着いたらそこで止めて、残りの移動量は次のフレームに持ち越す
+            // （曲がり角で曲がれるように、速度が速くてもマスに合った位置を通り過ぎないようにする）
+            if player.x % 32 == 0 && player.y % 32 == 0 {
+                player.move_remainder = (player.move_remainder + (steps - step - 1) * STEP_PIXELS * 4).min(MAX_MOVE_REMAINDER);
+                break;
+            }
+        }
+
+        // 実際に移動させる
+        player.is_walking = player.x != old_x || player.y != old_y;
+        if player.is_walking {
+            player.move_time += 1.0 / 60.0;
+        }
+    }
+
+    /// 入力された方向に STEP_PIXELS だけ移動する（壁や爆弾にぶつかる場合は移動しない）
+    fn move_one_step(&mut self, walls: &[Wall], bombs: &mut [Bomb], can_kick: bool) {
+        // 移動前の位置を保存しておく
+        let old_x = self.x;
+        let old_y = self.y;
+
+        // 移動
+        match self.player_input.movement {
+            Movement::LEFT => {
+                self.direction = Direction::LEFT;
+                self.x -= STEP_PIXELS;
+            }
+            Movement::RIGHT => {
+                self.direction = Direction::RIGHT;
+                self.x += STEP_PIXELS;
+            }
+            Movement::UP => {
+                self.direction = Direction::UP;
+                self.y -= STEP_PIXELS;
+            }
+            Movement::DOWN => {
+                self.direction = Direction::DOWN;
+                self.y += STEP_PIXELS;
+            }
+            _ => ()
+        }
+
+        // 壁との当たり判定
+        let detect_walls = walls.iter().filter(|wall|
+            (wall.get_x() - self.x).abs() < 32 && (wall.get_y() - self.y).abs() < 32
+        ).collect::<Vec<&Wall>>();
+        if !detect_walls.is_empty() {
+            self.x = old_x;
+            self.y = old_y;
+        }
+        if detect_walls.len() == 1 {
+            let wall = detect_walls.first().unwrap();
+            match self.player_input.movement {
+                Movement::LEFT | Movement::RIGHT => {
+                    if self.y < wall.get_y() {
+                        self.y -= STEP_PIXELS;
+                    }
+                    if self.y > wall.get_y() {
+                        self.y += STEP_PIXELS;
+                    }
+                }
+                Movement::DOWN | Movement::UP => {
+                    if self.x < wall.get_x() {
+                        self.x -= STEP_PIXELS;
+                    }
+                    if self.x > wall.get_x() {
+                        self.x += STEP_PIXELS;
+                    }
+                }
+                _ => ()
+            }
+        }
+
+        // 爆弾との当たり判定
+        // 32で割り切れる場所からそうでない場所に移動しようとした場合は、移動先に爆弾があったら動かさない
+        if self.x != old_x && old_x % 32 == 0 {
+            let bx = if self.x > old_x { old_x + 32 } else { old_x - 32 };
+            if self.bump_into_bomb(bombs, bx, self.y, can_kick) { self.x = old_x }
+        } else if self.y != old_y && old_y % 32 == 0 {
+            let by = if self.y > old_y { old_y + 32 } else { old_y - 32 };
+            if self.bump_into_bomb(bombs, self.x, by, can_kick) { self.y = old_y }
+        }
+        // 32で割り切れない場所から移動しようとした場合は、一番近いマス以外のマスに移動しようとしている場合、移動先に爆弾があったら動かさない
+        else if self.x != old_x {
+            let mut bx: Option<i32> = None;
+            if old_x % 32 < 32 / 2 {
+                if self.x > old_x {
+                    bx = Some((old_x / 32 + 1) * 32);
+                }
+            } else if self.x < old_x {
+                bx = Some((old_x / 32) * 32);
+            }
+            if let Some(bx) = bx {
+                if self.bump_into_bomb(bombs, bx, self.y, can_kick) { self.x = old_x }
+            }
+        } else if self.y != old_y {
+            let mut by: Option<i32> = None;
+            if old_y % 32 < 32 / 2 {
+                if self.y > old_y {
+                    by = Some((old_y / 32 + 1) * 32);
+                }
+            } else if self.y < old_y {
+                by = Some((old_y / 32) * 32);
+            }
+            if let Some(by) = by {
+                if self.bump_into_bomb(bombs, self.x, by, can_kick) { self.y = old_y }
+            }
+        }
+    }
+
+    /// 指定した位置のマスに重なる爆弾（滑っている途中のものを含む）があれば true を返す（止まっている爆弾を蹴れる場合は移動方向に蹴る）
+    fn bump_into_bomb(&self, bombs: &mut [Bomb], bx: i32, by: i32, can_kick: bool) -> bool {
+        match bombs.iter_mut().find(|bomb| (bomb.get_x() - bx).abs() < constants::CHARACTER_SIZE && (bomb.get_y() - by).abs() < constants::CHARACTER_SIZE) {
+            Some(bomb) => {
+                if can_kick && !bomb.is_sliding() {
+                    bomb.kick(self.player_input.movement);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn after_next_frame(world: &mut World, player_number: usize, events: &mut Vec<WorldEvent>) {
+        let player = world.players.get_mut(player_number).unwrap();
+
+        if player.is_dead() {
+            return;
+        }
+
+        // パワーアップアイテムとの当たり判定
+        world.power_up_items.retain(|item|
+            if (item.get_x() - player.x).abs() < 32 && (item.get_y() - player.y).abs() < 32 {
+                player.power_up(item.kind);
+                events.push(WorldEvent::PowerUp(item.kind));
+                false
+            } else {
+                true
+            }
+        );
+
+        // 爆弾の設置
+        if player.player_input.fire && (world.rule.unlimited_bombs || Self::count_active_bombs(&world.bombs, player_number) < player.bomb_capacity) {
+            let bx = (player.x + 32 / 2) / 32 * 32;
+            let by = (player.y + 32 / 2) / 32 * 32;
+            if !world.bombs.iter().any(|bomb|
+                bomb.get_tile_x() == bx && bomb.get_tile_y() == by
+            ) {
+                let mut bomb = Bomb::new(bx, by, player.power, player_number);
+                bomb.remote = player.has_remote;
+                bomb.pierce = player.has_pierce;
+                world.bombs.push(bomb);
+                events.push(WorldEvent::SetBomb);
+            }
+        }
+
+        // リモコン爆弾の起爆
+        if player.player_input.detonate {
+            for bomb in world.bombs.iter_mut().filter(|bomb| bomb.owner == player_number && bomb.remote) {
+                bomb.explode_soon();
+            }
+        }
+
+        // 爆発との当たり判定
+        let mut hit_explosions = world.explosions.iter().filter(|explosion|
+            (explosion.get_x() - player.x).abs() < 28 && (explosion.get_y() - player.y).abs() < 28
+        );
+        if let Some(explosion) = hit_explosions.next() {
+            let own_explosion = explosion.owner == player_number || hit_explosions.any(|explosion| explosion.owner == player_number);
+            player.killed_by = Some(if own_explosion { player_number } else { explosion.owner });
+            player.death_state = 1;
+            player.is_walking = false;
+            // 死んだプレイヤーのリモコン爆弾は普通の爆弾に戻す
+            for bomb in world.bombs.iter_mut().filter(|bomb| bomb.owner == player_number) {
+                bomb.remote = false;
+            }
+            events.push(WorldEvent::PlayerDied(player_number));
+        }
+    }
+
+    /// パワーアップアイテムの効果を反映する
+    fn power_up(&mut self, kind: PowerUpKind) {
+        match kind {
+            PowerUpKind::FireUp => self.power = (self.power + 1).min(MAX_POWER),
+            PowerUpKind::BombUp => self.bomb_capacity += 1,
+            PowerUpKind::SpeedUp => self.speed = (self.speed + SPEED_UP_STEP).min(MAX_SPEED),
+            PowerUpKind::FullFire => self.power = MAX_POWER,
+            PowerUpKind::PowerDown => self.power = (self.power - 1).max(1),
+            PowerUpKind::Kick => self.can_kick = true,
+            PowerUpKind::Remote => self.has_remote = true,
+            PowerUpKind::Pierce => self.has_pierce = true,
+        }
+    }
+
+    /// 移動速度の段階（最初は1）
+    pub fn speed_level(&self) -> i32 {
+        (self.speed - DEFAULT_SPEED) / SPEED_UP_STEP + 1
+    }
+
+    /// 1マス移動するのにかかるフレーム数
+    pub fn frames_per_tile(&self) -> i32 {
+        constants::CHARACTER_SIZE * 4 / self.speed
+    }
+
+    /// 指定したプレイヤーが置いた爆弾のうち、まだ爆発していないものの数
+    pub fn count_active_bombs(bombs: &[Bomb], player_number: usize) -> i32 {
+        bombs.iter().filter(|bomb| bomb.owner == player_number).count() as i32
+    }
+
+    pub fn push_position(&mut self) {
+        self.pushed_x = self.x;
+        self.pushed_y = self.y;
+    }
+
+    pub fn pop_position(&mut self) {
+        self.x = self.pushed_x;
+        self.y = self.pushed_y;
+    }
+}
+
+impl LightSprite for Player {
+    fn get_x(&self) -> i32 { self.x }
+    fn get_y(&self) -> i32 { self.y }
+
+    fn on_next_frame(&mut self) -> LightSpriteEvent {
+        if self.is_dead() {
+            self.death_state += 1;
+            return LightSpriteEvent::None;
+        }
+
+        LightSpriteEvent::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_support::{first_player, new_world};
+
+    #[test]
+    fn pushing_against_a_wall_does_not_build_up_speed() {
+        let mut world = new_world();
+        world.players[0].speed = MAX_SPEED;
+        for _ in 0..300 {
+            world.on_next_frame(&first_player(Movement::UP, false));
+        }
+        assert_eq!((world.players[0].get_x(), world.players[0].get_y()), (32, 32));
+
+        // 壁を押し続けた後でも、1フレームの移動量は速度どおりになる
+        for frame in 1..=32 {
+            let old_x = world.players[0].get_x();
+            world.on_next_frame(&first_player(Movement::RIGHT, false));
+            assert_eq!(world.players[0].get_x() - old_x, MAX_SPEED / 4, "frame {}", frame);
+        }
+        assert_eq!(world.players[0].get_x(), 32 + 32 * 4);
+    }
+
+    #[test]
+    fn power_up_items_change_power_and_speed_within_limits() {
+        let mut player = Player::new(0, 32, 32);
+        player.power_up(PowerUpKind::FireUp);
+        assert_eq!(player.power, 2);
+        player.power_up(PowerUpKind::PowerDown);
+        player.power_up(PowerUpKind::PowerDown);
+        assert_eq!(player.power, 1);
+        player.power_up(PowerUpKind::FullFire);
+        player.power_up(PowerUpKind::FireUp);
+        assert_eq!(player.power, MAX_POWER);
+
+        player.power_up(PowerUpKind::SpeedUp);
+        assert_eq!(player.speed_level(), 2);
+        for _ in 0..10 {
+            player.power_up(PowerUpKind::SpeedUp);
+        }
+        assert_eq!(player.speed, MAX_SPEED);
+        assert_eq!(player.frames_per_tile(), 8);
+    }
+}
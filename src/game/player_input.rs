@@ -0,0 +1,52 @@
+#[derive(Copy, Clone, PartialEq)]
+pub enum Movement {
+    NONE, LEFT, UP, RIGHT, DOWN
+}
+
+impl Movement {
+    pub const ALL: [Movement; 5] = [Movement::NONE, Movement::LEFT, Movement::UP, Movement::RIGHT, Movement::DOWN];
+
+    /// 外部のボットとのやりとりなどに使う名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Movement::NONE => "none",
+            Movement::LEFT => "left",
+            Movement::UP => "up",
+            Movement::RIGHT => "right",
+            Movement::DOWN => "down",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Movement> {
+        Self::ALL.iter().copied().find(|movement| movement.name() == name)
+    }
+
+    /// 移動方向の単位ベクトル
+    pub fn delta(&self) -> (i32, i32) {
+        match self {
+            Movement::NONE => (0, 0),
+            Movement::LEFT => (-1, 0),
+            Movement::UP => (0, -1),
+            Movement::RIGHT => (1, 0),
+            Movement::DOWN => (0, 1),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct PlayerInput {
+    pub movement: Movement,
+    pub fire: bool,
+    // リモコン爆弾を爆発させる
+    pub detonate: bool,
+}
+
+impl PlayerInput {
+    pub fn new(movement: Movement, fire: bool) -> PlayerInput {
+        PlayerInput {
+            movement,
+            fire,
+            detonate: false,
+        }
+    }
+}
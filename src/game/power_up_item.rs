@@ -0,0 +1,134 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use super::{light_sprite::LightSprite, light_sprite::LightSpriteEvent, rule::Rule};
+
+pub const POWER_UP_KIND_COUNT: usize = 8;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PowerUpKind {
+    // 爆発の範囲が1マス広がる
+    FireUp,
+    // 同時に置ける爆弾の数が1つ増える
+    BombUp,
+    // 移動速度が上がる
+    SpeedUp,
+    // 爆発の範囲が最大になる
+    FullFire,
+    // 爆発の範囲が1マス狭まる（取らない方がよいアイテム）
+    PowerDown,
+    // 爆弾を蹴れるようになる
+    Kick,
+    // 置いた爆弾が好きなときに爆発させられるリモコン爆弾になる
+    Remote,
+    // 爆発が壊せる壁を貫通するようになる
+    Pierce,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; POWER_UP_KIND_COUNT] = [
+        PowerUpKind::FireUp,
+        PowerUpKind::BombUp,
+        PowerUpKind::SpeedUp,
+        PowerUpKind::FullFire,
+        PowerUpKind::PowerDown,
+        PowerUpKind::Kick,
+        PowerUpKind::Remote,
+        PowerUpKind::Pierce,
+    ];
+
+    /// ルール設定やファイルに書くときの名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerUpKind::FireUp => "fire_up",
+            PowerUpKind::BombUp => "bomb_up",
+            PowerUpKind::SpeedUp => "speed_up",
+            PowerUpKind::FullFire => "full_fire",
+            PowerUpKind::PowerDown => "power_down",
+            PowerUpKind::Kick => "kick",
+            PowerUpKind::Remote => "remote",
+            PowerUpKind::Pierce => "pierce",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PowerUpKind> {
+        Self::ALL.iter().copied().find(|kind| kind.name() == name)
+    }
+
+    /// 壁が壊れたときに出現するアイテムの種類をルールの出現比率に従って決める（出現するものがなければ None）
+    pub fn choose(rng: &mut StdRng, rule: &Rule) -> Option<PowerUpKind> {
+        let total: i32 = Self::ALL.iter().map(|kind| rule.item_weight(*kind)).sum();
+        if total <= 0 {
+            return None;
+        }
+        let mut value = rng.gen_range(0, total);
+        for kind in Self::ALL {
+            let weight = rule.item_weight(kind);
+            if value < weight {
+                return Some(kind);
+            }
+            value -= weight;
+        }
+        None
+    }
+}
+
+#[derive(Clone)]
+pub struct PowerUpItem {
+    x: i32,
+    y: i32,
+    pub kind: PowerUpKind,
+    pub move_time: f32,
+}
+
+impl PowerUpItem {
+    pub fn new(x: i32, y: i32, kind: PowerUpKind) -> PowerUpItem {
+        PowerUpItem {
+            x,
+            y,
+            kind,
+            move_time: 0f32,
+        }
+    }
+}
+
+impl LightSprite for PowerUpItem {
+    fn get_x(&self) -> i32 { self.x }
+    fn get_y(&self) -> i32 { self.y }
+
+    fn on_next_frame(&mut self) -> LightSpriteEvent {
+        self.move_time += 1.0 / 60.0;
+        LightSpriteEvent::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn items_appear_by_the_rule_weights() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut rule = Rule { item_weights: [0; POWER_UP_KIND_COUNT], ..Rule::default() };
+        assert!(PowerUpKind::choose(&mut rng, &rule).is_none());
+
+        rule.item_weights[PowerUpKind::FireUp as usize] = 1;
+        rule.item_weights[PowerUpKind::SpeedUp as usize] = 3;
+        let mut counts = [0; POWER_UP_KIND_COUNT];
+        for _ in 0..4000 {
+            counts[PowerUpKind::choose(&mut rng, &rule).unwrap() as usize] += 1;
+        }
+        assert_eq!(counts[PowerUpKind::FireUp as usize] + counts[PowerUpKind::SpeedUp as usize], 4000);
+        assert!((800..1200).contains(&counts[PowerUpKind::FireUp as usize]), "{:?}", counts);
+    }
+
+    #[test]
+    fn kinds_are_found_by_name() {
+        for kind in PowerUpKind::ALL {
+            assert!(PowerUpKind::from_name(kind.name()) == Some(kind));
+        }
+        assert!(PowerUpKind::from_name("bomb").is_none());
+    }
+}
@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::player_input::{PlayerInput, Movement};
+use super::rule::Rule;
+use super::stage::Stage;
+use super::world::{MIN_PLAYER_COUNT, MAX_PLAYER_COUNT};
+
+const FILE_HEADER: &str = "BOMBER-REPLAY 1";
+
+// リプレイファイルを保存するディレクトリ
+pub const REPLAY_DIRECTORY: &str = "replays";
+
+// リプレイファイルの拡張子
+pub const REPLAY_EXTENSION: &str = "replay";
+
+/// 1試合分の記録（シード、プレイヤータイプ、毎フレームの全プレイヤーの入力）
+///
+/// ファイルはテキスト形式で、1行目がヘッダ、続いて SEED 行、RULES 行、PLAYERS 行と
+/// STAGE 行から END_STAGE 行までのステージ定義（Stage::to_text() の内容）、FRAMES 行以降が1行1フレームの入力（PLAYERS 行に並んだプレイヤーの順）になる。
+/// 入力は1プレイヤーにつき「移動方向の番号（0:なし 1:左 2:上 3:右 4:下）」と
+/// 「爆弾設置の有無（F または -）」の2文字で表し、リモコン爆弾を起爆した場合は末尾に D を付ける。
+pub struct Replay {
+    pub seed: u64,
+    pub rule: Rule,
+    pub stage: Stage,
+    pub player_types: Vec<String>,
+    pub frames: Vec<Vec<PlayerInput>>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, rule: Rule, stage: Stage, player_types: Vec<String>) -> Replay {
+        Replay {
+            seed,
+            rule,
+            stage,
+            player_types,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn push_frame(&mut self, player_inputs: &[PlayerInput]) {
+        self.frames.push(player_inputs.to_vec());
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut text = format!(
+            "{}\nSEED {}\nRULES {}\nPLAYERS {}\nSTAGE\n{}END_STAGE\nFRAMES\n",
+            FILE_HEADER, self.seed, self.rule.to_text(), self.player_types.join(" "), self.stage.to_text(),
+        );
+        for player_inputs in self.frames.iter() {
+            let line = player_inputs.iter().map(Self::encode_input).collect::<Vec<String>>().join(" ");
+            text.push_str(&line);
+            text.push('\n');
+        }
+        fs::write(path, text).map_err(|e| format!("Failed to write replay {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read replay {}: {}", path.display(), e))?;
+        let mut lines = text.lines().enumerate();
+
+        if lines.next().map(|(_, line)| line) != Some(FILE_HEADER) {
+            return Err(format!("{} is not a replay file", path.display()));
+        }
+        let mut seed: Option<u64> = None;
+        let mut rule = Rule::default();
+        let mut stage = Stage::classic();
+        let mut player_types: Vec<String> = Vec::new();
+        while let Some((line_number, line)) = lines.next() {
+            if line == "FRAMES" {
+                break;
+            } else if let Some(value) = line.strip_prefix("SEED ") {
+                seed = Some(value.parse::<u64>().map_err(|e| format!("{}:{}: invalid seed: {}", path.display(), line_number + 1, e))?);
+            } else if let Some(value) = line.strip_prefix("RULES ") {
+                rule = Rule::from_text(value).map_err(|e| format!("{}:{}: {}", path.display(), line_number + 1, e))?;
+            } else if let Some(value) = line.strip_prefix("PLAYERS ") {
+                player_types = value.split_whitespace().map(String::from).collect();
+            } else if line == "STAGE" {
+                let mut stage_text = String::new();
+                for (_, line) in lines.by_ref() {
+                    if line == "END_STAGE" {
+                        break;
+                    }
+                    stage_text.push_str(line);
+                    stage_text.push('\n');
+                }
+                stage = Stage::parse(&stage_text).map_err(|e| format!("{}:{}: invalid stage: {}", path.display(), line_number + 1, e))?;
+            } else {
+                return Err(format!("{}:{}: unknown header line '{}'", path.display(), line_number + 1, line));
+            }
+        }
+        let seed = seed.ok_or(format!("{}: SEED is missing", path.display()))?;
+        if !(MIN_PLAYER_COUNT..=MAX_PLAYER_COUNT).contains(&player_types.len()) {
+            return Err(format!("{}: invalid number of players {}", path.display(), player_types.len()));
+        }
+
+        let player_count = player_types.len();
+        let mut replay = Replay::new(seed, rule, stage, player_types);
+        for (line_number, line) in lines {
+            let tokens = line.split_whitespace().collect::<Vec<&str>>();
+            if tokens.len() != player_count {
+                return Err(format!("{}:{}: expected {} inputs but found {}", path.display(), line_number + 1, player_count, tokens.len()));
+            }
+            let player_inputs = tokens.iter()
+                .map(|token| Self::decode_input(token)
+                    .ok_or(format!("{}:{}: invalid input '{}'", path.display(), line_number + 1, token)))
+                .collect::<Result<Vec<PlayerInput>, String>>()?;
+            replay.frames.push(player_inputs);
+        }
+        Ok(replay)
+    }
+
+    fn encode_input(player_input: &PlayerInput) -> String {
+        let movement = match player_input.movement {
+            Movement::NONE => '0',
+            Movement::LEFT => '1',
+            Movement::UP => '2',
+            Movement::RIGHT => '3',
+            Movement::DOWN => '4',
+        };
+        let fire = if player_input.fire { 'F' } else { '-' };
+        let detonate = if player_input.detonate { "D" } else { "" };
+        format!("{}{}{}", movement, fire, detonate)
+    }
+
+    fn decode_input(token: &str) -> Option<PlayerInput> {
+        let mut chars = token.chars();
+        let movement = match chars.next()? {
+            '0' => Movement::NONE,
+            '1' => Movement::LEFT,
+            '2' => Movement::UP,
+            '3' => Movement::RIGHT,
+            '4' => Movement::DOWN,
+            _ => return None,
+        };
+        let fire = match chars.next()? {
+            'F' => true,
+            '-' => false,
+            _ => return None,
+        };
+        let detonate = match chars.next() {
+            Some('D') => true,
+            None => false,
+            _ => return None,
+        };
+        if chars.next().is_some() {
+            return None;
+        }
+        let mut player_input = PlayerInput::new(movement, fire);
+        player_input.detonate = detonate;
+        Some(player_input)
+    }
+
+    /// リプレイディレクトリ内で最も新しいリプレイファイルを探す
+    pub fn find_latest() -> Option<PathBuf> {
+        fs::read_dir(REPLAY_DIRECTORY).ok()?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION))
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_support::{play_with_ai, snapshot};
+    use crate::game::world::World;
+
+    #[test]
+    fn replay_reproduces_the_recorded_match() {
+        let seed = 7;
+        let mut world = World::new(seed, 2, Rule::default(), Stage::classic());
+        let mut replay = Replay::new(seed, world.rule, world.stage.clone(), vec!["AI".to_string(), "AI".to_string()]);
+        for player_inputs in play_with_ai(&mut world, 1800) {
+            replay.push_frame(&player_inputs);
+        }
+
+        let path = std::env::temp_dir().join(format!("rust-bomber-test-{}.replay", std::process::id()));
+        replay.save(&path).unwrap();
+        let loaded = Replay::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.seed, seed);
+        assert_eq!(loaded.player_types, replay.player_types);
+        assert_eq!(loaded.stage.to_text(), replay.stage.to_text());
+        assert_eq!(loaded.rule.to_text(), replay.rule.to_text());
+        assert_eq!(loaded.frames.len(), replay.frames.len());
+
+        let mut replayed = World::new(loaded.seed, loaded.player_types.len(), loaded.rule, loaded.stage.clone());
+        for player_inputs in loaded.frames.iter() {
+            replayed.on_next_frame(player_inputs);
+        }
+        assert_eq!(snapshot(&replayed), snapshot(&world));
+    }
+
+    #[test]
+    fn input_tokens_round_trip_and_reject_garbage() {
+        for movement in Movement::ALL {
+            for fire in [false, true] {
+                let token = Replay::encode_input(&PlayerInput::new(movement, fire));
+                let decoded = Replay::decode_input(&token).unwrap();
+                assert!(decoded.movement == movement && decoded.fire == fire && !decoded.detonate, "{}", token);
+            }
+        }
+        // リモコン爆弾の起爆は末尾の D
+        let detonate = Replay::decode_input("3FD").unwrap();
+        assert!(detonate.movement == Movement::RIGHT && detonate.fire && detonate.detonate);
+        assert_eq!(Replay::encode_input(&detonate), "3FD");
+
+        for token in ["", "5-", "0", "0X", "0-X", "0-DD"] {
+            assert!(Replay::decode_input(token).is_none(), "{}", token);
+        }
+    }
+}
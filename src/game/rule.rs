@@ -0,0 +1,112 @@
+use super::map_generator::{MapStyle, Symmetry};
+use super::power_up_item::{PowerUpKind, POWER_UP_KIND_COUNT};
+
+// アイテムの種類ごとの出現比率のデフォルト値（PowerUpKind::ALL の順）
+const DEFAULT_ITEM_WEIGHTS: [i32; POWER_UP_KIND_COUNT] = [4, 4, 3, 1, 2, 2, 1, 1];
+
+/// 試合のルール設定
+#[derive(Copy, Clone)]
+pub struct Rule {
+    // true の場合、爆弾を無制限に置ける（設置数の上限とボムアップアイテムがなくなる）
+    pub unlimited_bombs: bool,
+
+    // true の場合、最初から全員が爆弾を蹴れる（キックアイテムは出なくなる）
+    pub everyone_can_kick: bool,
+
+    // 壁を壊したときに出るアイテムの種類ごとの出現比率（PowerUpKind::ALL の順）
+    pub item_weights: [i32; POWER_UP_KIND_COUNT],
+
+    // マップの地形の作り方
+    pub map_style: MapStyle,
+
+    // 壊せる壁の配置と生成する地形の対称性
+    pub symmetry: Symmetry,
+
+    // 壊せる壁が置かれる確率（パーセント、None の場合は地形の作り方ごとのデフォルト値）
+    pub soft_wall_density: Option<i32>,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule {
+            unlimited_bombs: false,
+            everyone_can_kick: false,
+            item_weights: DEFAULT_ITEM_WEIGHTS,
+            map_style: MapStyle::Stage,
+            symmetry: Symmetry::None,
+            soft_wall_density: None,
+        }
+    }
+}
+
+impl Rule {
+    /// アイテムの出現比率（無効になっているアイテムは 0）
+    pub fn item_weight(&self, kind: PowerUpKind) -> i32 {
+        if (self.unlimited_bombs && kind == PowerUpKind::BombUp) || (self.everyone_can_kick && kind == PowerUpKind::Kick) {
+            0
+        } else {
+            self.item_weights[kind as usize]
+        }
+    }
+
+    /// リプレイファイルなどに保存するためのテキスト（"名前=値" をスペース区切りで並べたもの）にする
+    pub fn to_text(&self) -> String {
+        let mut text = format!("unlimited_bombs={} everyone_can_kick={}", self.unlimited_bombs, self.everyone_can_kick);
+        for kind in PowerUpKind::ALL {
+            text.push_str(&format!(" item_weight.{}={}", kind.name(), self.item_weights[kind as usize]));
+        }
+        text.push_str(&format!(
+            " map_style={} symmetry={} soft_wall_density={}",
+            self.map_style.name(),
+            self.symmetry.name(),
+            self.soft_wall_density.map_or("default".to_string(), |density| density.to_string()),
+        ));
+        text
+    }
+
+    /// to_text() で作ったテキストからルールを復元する（書かれていない項目はデフォルト値になる）
+    pub fn from_text(text: &str) -> Result<Rule, String> {
+        let mut rule = Rule::default();
+        for token in text.split_whitespace() {
+            rule.set(token)?;
+        }
+        Ok(rule)
+    }
+
+    /// "名前=値" の形式で1項目を設定する
+    pub fn set(&mut self, token: &str) -> Result<(), String> {
+        let (name, value) = token.split_once('=').ok_or(format!("invalid rule '{}'", token))?;
+        if name == "unlimited_bombs" {
+            self.unlimited_bombs = Self::parse_value(name, value)?;
+        } else if name == "everyone_can_kick" {
+            self.everyone_can_kick = Self::parse_value(name, value)?;
+        } else if let Some(kind) = name.strip_prefix("item_weight.").and_then(PowerUpKind::from_name) {
+            let weight: i32 = Self::parse_value(name, value)?;
+            if weight < 0 {
+                return Err(format!("invalid value '{}' for rule '{}'", value, name));
+            }
+            self.item_weights[kind as usize] = weight;
+        } else if name == "map_style" {
+            self.map_style = MapStyle::from_name(value).ok_or(format!("invalid value '{}' for rule '{}'", value, name))?;
+        } else if name == "symmetry" {
+            self.symmetry = Symmetry::from_name(value).ok_or(format!("invalid value '{}' for rule '{}'", value, name))?;
+        } else if name == "soft_wall_density" {
+            self.soft_wall_density = if value == "default" {
+                None
+            } else {
+                let density: i32 = Self::parse_value(name, value)?;
+                if !(0..=100).contains(&density) {
+                    return Err(format!("invalid value '{}' for rule '{}'", value, name));
+                }
+                Some(density)
+            };
+        } else {
+            return Err(format!("unknown rule '{}'", name));
+        }
+        Ok(())
+    }
+
+    fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+        value.parse::<T>().map_err(|_| format!("invalid value '{}' for rule '{}'", value, name))
+    }
+}
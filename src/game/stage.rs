@@ -0,0 +1,375 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::power_up_item::PowerUpKind;
+use super::tiled;
+use super::world::MAX_PLAYER_COUNT;
+
+// 新しく作ったステージの壊せる壁の確率（パーセント）
+const DEFAULT_SOFT_WALL_PROBABILITY: i32 = 50;
+
+// 同梱のステージファイルを置くディレクトリ
+pub const STAGE_DIRECTORY: &str = "res/stage";
+
+// ステージファイルの拡張子
+pub const STAGE_EXTENSION: &str = "stage";
+
+// 標準のステージ（ステージファイルが読めない場合もこれを使う）
+const CLASSIC_STAGE: &str = include_str!("../../res/stage/01_classic.stage");
+
+// マップの幅と高さの範囲（マス数）
+pub const MIN_MAP_SIZE: i32 = 5;
+pub const MAX_MAP_SIZE: i32 = 99;
+
+// マップに置くアイテムの文字
+const ITEM_SYMBOLS: [(char, PowerUpKind); 8] = [
+    ('f', PowerUpKind::FireUp),
+    ('b', PowerUpKind::BombUp),
+    ('s', PowerUpKind::SpeedUp),
+    ('F', PowerUpKind::FullFire),
+    ('d', PowerUpKind::PowerDown),
+    ('k', PowerUpKind::Kick),
+    ('r', PowerUpKind::Remote),
+    ('p', PowerUpKind::Pierce),
+];
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum StageCell {
+    // 何もない床
+    Floor,
+    // 壊せない壁
+    HardWall,
+    // 必ず置かれる壊せる壁
+    SoftWall,
+    // SOFT_WALL_PROBABILITY の確率で置かれる壊せる壁（プレイヤー出現位置の近くには置かれない）
+    RandomSoftWall,
+    // 最初から置かれているアイテム
+    Item(PowerUpKind),
+}
+
+/// ステージ（マップの地形、プレイヤーの出現位置、最初から置かれているアイテム）の定義
+///
+/// ファイルはテキスト形式で、NAME 行と SOFT_WALL_PROBABILITY 行（省略時は 50）のあと、
+/// MAP 行に続けてマップを1文字1マスで書く。
+/// 文字は「#: 壊せない壁」「+: 壊せる壁」「?: 確率で置かれる壊せる壁」「.: 床」
+/// 「1〜4: プレイヤーの出現位置」と ITEM_SYMBOLS のアイテム。# で始まるヘッダ行はコメントになる。
+#[derive(Clone)]
+pub struct Stage {
+    pub name: String,
+    pub width: i32,
+    pub height: i32,
+    // RandomSoftWall のマスに壊せる壁が置かれる確率（パーセント）
+    pub soft_wall_probability: i32,
+    // プレイヤー番号順の出現位置（マップ上の座標）
+    pub spawn_positions: Vec<(i32, i32)>,
+    cells: Vec<StageCell>,
+}
+
+impl Stage {
+    /// 標準のステージ（外周と偶数座標に壊せない壁、それ以外は 50% の確率で壊せる壁）
+    pub fn classic() -> Stage {
+        Self::parse(CLASSIC_STAGE).expect("the bundled classic stage is invalid")
+    }
+
+    /// 外周だけが壊せない壁で、四隅にプレイヤーの出現位置がある空のステージ（ステージエディタで新しく作る場合に使う）
+    pub fn new(name: &str, width: i32, height: i32) -> Stage {
+        let mut stage = Stage {
+            name: name.to_string(),
+            width: 0,
+            height: 0,
+            soft_wall_probability: DEFAULT_SOFT_WALL_PROBABILITY,
+            spawn_positions: Vec::new(),
+            cells: Vec::new(),
+        };
+        stage.resize(width, height);
+        stage.spawn_positions = vec![(1, 1), (width - 2, height - 2), (width - 2, 1), (1, height - 2)];
+        stage
+    }
+
+    pub fn get_cell(&self, x: i32, y: i32) -> StageCell {
+        self.cells[(y * self.width + x) as usize]
+    }
+
+    pub fn set_cell(&mut self, x: i32, y: i32, cell: StageCell) {
+        self.cells[(y * self.width + x) as usize] = cell;
+    }
+
+    /// マップの大きさを変える（残る部分はそのままにして、新しい外周を壊せない壁にする）
+    ///
+    /// 外に出てしまうプレイヤーの出現位置は外周のすぐ内側に移して、そのマスを床にする。
+    pub fn resize(&mut self, width: i32, height: i32) {
+        let width = width.clamp(MIN_MAP_SIZE, MAX_MAP_SIZE);
+        let height = height.clamp(MIN_MAP_SIZE, MAX_MAP_SIZE);
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                    StageCell::HardWall
+                } else if x < self.width - 1 && y < self.height - 1 {
+                    self.get_cell(x, y)
+                } else {
+                    StageCell::Floor
+                });
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+        for (x, y) in self.spawn_positions.iter_mut() {
+            *x = (*x).clamp(1, width - 2);
+            *y = (*y).clamp(1, height - 2);
+            self.cells[(*y * width + *x) as usize] = StageCell::Floor;
+        }
+    }
+
+    /// ステージファイルを読み込む（拡張子が tmx の場合は Tiled のマップとして読み込む）
+    pub fn load(path: &Path) -> Result<Stage, String> {
+        if path.extension().is_some_and(|ext| ext == tiled::TMX_EXTENSION) {
+            return tiled::load_tmx(path);
+        }
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read stage {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// 正しいステージか確かめてからファイルに書き込む
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        self.validate()?;
+        fs::write(path, self.to_text()).map_err(|e| format!("Failed to write stage {}: {}", path.display(), e))
+    }
+
+    /// ディレクトリ内のステージファイル（Tiled のマップを含む）のパスをファイル名の順に返す
+    pub fn list_directory(directory: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut paths = fs::read_dir(directory)
+            .map_err(|e| format!("Failed to read stage directory {}: {}", directory.display(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == STAGE_EXTENSION || ext == tiled::TMX_EXTENSION))
+            .collect::<Vec<_>>();
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// ディレクトリ内のステージファイルをファイル名の順にすべて読み込む
+    pub fn load_directory(directory: &Path) -> Result<Vec<Stage>, String> {
+        Self::list_directory(directory)?.iter().map(|path| Self::load(path)).collect()
+    }
+
+    pub fn parse(text: &str) -> Result<Stage, String> {
+        let mut lines = text.lines().enumerate();
+        let mut name: Option<String> = None;
+        let mut soft_wall_probability = DEFAULT_SOFT_WALL_PROBABILITY;
+        for (line_number, line) in lines.by_ref() {
+            let line = line.trim_end();
+            if line == "MAP" {
+                break;
+            } else if line.is_empty() || line.starts_with('#') {
+                continue;
+            } else if let Some(value) = line.strip_prefix("NAME ") {
+                name = Some(value.trim().to_string());
+            } else if let Some(value) = line.strip_prefix("SOFT_WALL_PROBABILITY ") {
+                soft_wall_probability = value.trim().parse::<i32>()
+                    .ok()
+                    .filter(|p| (0..=100).contains(p))
+                    .ok_or(format!("line {}: invalid soft wall probability '{}'", line_number + 1, value))?;
+            } else {
+                return Err(format!("line {}: unknown header line '{}'", line_number + 1, line));
+            }
+        }
+        let name = name.ok_or("NAME is missing")?;
+
+        // マップ本体
+        let mut cells: Vec<StageCell> = Vec::new();
+        let mut spawn_positions: Vec<Option<(i32, i32)>> = vec![None; MAX_PLAYER_COUNT];
+        let mut width: Option<i32> = None;
+        let mut height = 0;
+        for (line_number, line) in lines {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            let row = line.chars().collect::<Vec<char>>();
+            if *width.get_or_insert(row.len() as i32) != row.len() as i32 {
+                return Err(format!("line {}: expected {} cells but found {}", line_number + 1, width.unwrap(), row.len()));
+            }
+            for (x, c) in row.iter().enumerate() {
+                let cell = match c {
+                    '#' => StageCell::HardWall,
+                    '+' => StageCell::SoftWall,
+                    '?' => StageCell::RandomSoftWall,
+                    '.' => StageCell::Floor,
+                    '1'..='9' => {
+                        let n = c.to_digit(10).unwrap() as usize - 1;
+                        if n >= MAX_PLAYER_COUNT {
+                            return Err(format!("line {}: invalid player number '{}'", line_number + 1, c));
+                        }
+                        if spawn_positions[n].is_some() {
+                            return Err(format!("line {}: player {} appears more than once", line_number + 1, c));
+                        }
+                        spawn_positions[n] = Some((x as i32, height));
+                        StageCell::Floor
+                    }
+                    _ => match ITEM_SYMBOLS.iter().find(|(symbol, _)| symbol == c) {
+                        Some((_, kind)) => StageCell::Item(*kind),
+                        None => return Err(format!("line {}: unknown cell '{}'", line_number + 1, c)),
+                    },
+                };
+                cells.push(cell);
+            }
+            height += 1;
+        }
+        let width = width.ok_or("MAP is empty")?;
+        let spawn_positions = spawn_positions.iter().enumerate()
+            .map(|(i, position)| position.ok_or(format!("spawn position of player {} is missing", i + 1)))
+            .collect::<Result<Vec<(i32, i32)>, String>>()?;
+
+        let stage = Stage { name, width, height, soft_wall_probability, spawn_positions, cells };
+        stage.validate()?;
+        Ok(stage)
+    }
+
+    /// マップの大きさ、外周、プレイヤーの出現位置が正しいか確かめる
+    pub fn validate(&self) -> Result<(), String> {
+        let size_range = MIN_MAP_SIZE..=MAX_MAP_SIZE;
+        if !size_range.contains(&self.width) || !size_range.contains(&self.height) {
+            return Err(format!("map size must be between {0}x{0} and {1}x{1} but is {2}x{3}", MIN_MAP_SIZE, MAX_MAP_SIZE, self.width, self.height));
+        }
+        // 外周は壊せない壁で囲まれていなければならない
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if (x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1) && self.get_cell(x, y) != StageCell::HardWall {
+                    return Err(format!("cell ({}, {}) on the edge of the map must be a hard wall", x, y));
+                }
+            }
+        }
+        // プレイヤーの出現位置は床で、重なっていてはいけない
+        for (i, &(x, y)) in self.spawn_positions.iter().enumerate() {
+            if x <= 0 || y <= 0 || x >= self.width - 1 || y >= self.height - 1 || self.get_cell(x, y) != StageCell::Floor {
+                return Err(format!("spawn position of player {} must be on the floor", i + 1));
+            }
+            if self.spawn_positions[..i].contains(&(x, y)) {
+                return Err(format!("spawn positions of players overlap at ({}, {})", x, y));
+            }
+        }
+        Ok(())
+    }
+
+    /// parse() で読み込めるテキストにする（リプレイファイルにステージを埋め込むのに使う）
+    pub fn to_text(&self) -> String {
+        let mut text = format!("NAME {}\nSOFT_WALL_PROBABILITY {}\nMAP\n", self.name, self.soft_wall_probability);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let c = match self.spawn_positions.iter().position(|position| *position == (x, y)) {
+                    Some(n) => char::from_digit(n as u32 + 1, 10).unwrap(),
+                    None => match self.get_cell(x, y) {
+                        StageCell::Floor => '.',
+                        StageCell::HardWall => '#',
+                        StageCell::SoftWall => '+',
+                        StageCell::RandomSoftWall => '?',
+                        StageCell::Item(kind) => ITEM_SYMBOLS.iter().find(|(_, k)| *k == kind).unwrap().0,
+                    },
+                };
+                text.push(c);
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // アイテム、必ず置かれる壁と確率で置かれる壁を含むステージ
+    const STAGE: &str = "# コメント
+NAME items
+SOFT_WALL_PROBABILITY 30
+MAP
+#######
+#1.+.3#
+#.#?#.#
+#f.b.k#
+#4.p.2#
+#######
+";
+
+    #[test]
+    fn stage_text_round_trips() {
+        let stage = Stage::parse(STAGE).unwrap();
+        assert_eq!((stage.name.as_str(), stage.width, stage.height, stage.soft_wall_probability), ("items", 7, 6, 30));
+        assert_eq!(stage.spawn_positions, vec![(1, 1), (5, 4), (5, 1), (1, 4)]);
+        assert!(stage.get_cell(3, 1) == StageCell::SoftWall);
+        assert!(stage.get_cell(3, 2) == StageCell::RandomSoftWall);
+        assert!(stage.get_cell(1, 3) == StageCell::Item(PowerUpKind::FireUp));
+        assert!(stage.get_cell(1, 1) == StageCell::Floor);
+
+        let text = stage.to_text();
+        assert_eq!(Stage::parse(&text).unwrap().to_text(), text);
+        assert_eq!(text.lines().skip(3).collect::<Vec<_>>(), STAGE.lines().skip(4).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn invalid_stages_are_rejected_with_the_reason() {
+        let map = |rows: &str| format!("NAME bad\nMAP\n{}", rows);
+        let error = |text: &str| Stage::parse(text).err().unwrap();
+        assert_eq!(error("MAP\n#####\n"), "NAME is missing");
+        assert_eq!(error("NAME a\nSIZE 3\nMAP\n"), "line 2: unknown header line 'SIZE 3'");
+        assert_eq!(error(&map("#####\n#1.3#\n#.x.#\n#4.2#\n#####\n")), "line 5: unknown cell 'x'");
+        assert_eq!(error(&map("#####\n#1.3#\n#...#\n#4.2##\n#####\n")), "line 6: expected 5 cells but found 6");
+        assert_eq!(error(&map("#####\n#1.3#\n#.1.#\n#4.2#\n#####\n")), "line 5: player 1 appears more than once");
+        assert_eq!(error(&map("#####\n#1.3#\n#...#\n#4..#\n#####\n")), "spawn position of player 2 is missing");
+        assert_eq!(error(&map("#####\n#1.3#\n....#\n#4.2#\n#####\n")), "cell (0, 2) on the edge of the map must be a hard wall");
+    }
+
+    #[test]
+    fn map_size_must_be_in_range() {
+        let error = Stage::parse("NAME small\nMAP\n####\n#13#\n#42#\n####\n").err().unwrap();
+        assert_eq!(error, format!("map size must be between {0}x{0} and {1}x{1} but is 4x4", MIN_MAP_SIZE, MAX_MAP_SIZE));
+    }
+
+    #[test]
+    fn resize_keeps_the_inside_and_walls_in_the_new_edge() {
+        let mut stage = Stage::new("resize", 7, 7);
+        stage.set_cell(2, 2, StageCell::SoftWall);
+        stage.resize(11, 9);
+        assert_eq!((stage.width, stage.height), (11, 9));
+        assert!(stage.get_cell(2, 2) == StageCell::SoftWall);
+        // 元の外周だったマスは床になる
+        assert!(stage.get_cell(6, 3) == StageCell::Floor);
+        assert!(stage.get_cell(10, 3) == StageCell::HardWall && stage.get_cell(3, 8) == StageCell::HardWall);
+        assert_eq!(stage.spawn_positions, vec![(1, 1), (5, 5), (5, 1), (1, 5)]);
+        stage.validate().unwrap();
+
+        // 外に出てしまう出現位置は内側に移る
+        stage.set_cell(3, 3, StageCell::HardWall);
+        stage.spawn_positions[1] = (8, 7);
+        stage.resize(5, 5);
+        assert_eq!(stage.spawn_positions[1], (3, 3));
+        assert!(stage.get_cell(3, 3) == StageCell::Floor);
+
+        stage.resize(1, 1000);
+        assert_eq!((stage.width, stage.height), (MIN_MAP_SIZE, MAX_MAP_SIZE));
+    }
+
+    #[test]
+    fn saved_stage_is_loaded_back() {
+        let mut stage = Stage::new("saved", 9, 7);
+        stage.set_cell(4, 3, StageCell::Item(PowerUpKind::BombUp));
+        let path = std::env::temp_dir().join(format!("rust-bomber-test-{}.{}", std::process::id(), STAGE_EXTENSION));
+        stage.save(&path).unwrap();
+        let loaded = Stage::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().to_text(), stage.to_text());
+
+        // 出現位置が壁の上にあるステージは保存しない
+        stage.set_cell(1, 1, StageCell::HardWall);
+        assert_eq!(stage.save(&path).err().unwrap(), "spawn position of player 1 must be on the floor");
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn bundled_stages_are_valid() {
+        let stages = Stage::load_directory(Path::new(STAGE_DIRECTORY)).unwrap();
+        assert!(!stages.is_empty());
+        assert_eq!(Stage::classic().to_text(), stages[0].to_text());
+    }
+}
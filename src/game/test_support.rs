@@ -0,0 +1,71 @@
+// ゲームのルールのテストで共通に使うステージ、入力と試合の進め方
+
+use crate::ai::ai_player::AIPlayer;
+use crate::ai::strategy::{AIStrategy, GameView};
+use super::light_sprite::LightSprite;
+use super::player_input::{PlayerInput, Movement};
+use super::rule::Rule;
+use super::stage::Stage;
+use super::world::World;
+
+// 乱数で置かれる壁もアイテムもない、通路だけのステージ（1P は左上の角、2P はその3マス下）
+pub(crate) const TEST_STAGE: &str = "NAME test
+MAP
+#########
+#1......#
+#.#.#.#.#
+#.......#
+#2.....3#
+#4......#
+#########
+";
+
+/// TEST_STAGE で2人対戦を始める
+pub(crate) fn new_world() -> World {
+    World::new(0, 2, Rule::default(), Stage::parse(TEST_STAGE).unwrap())
+}
+
+/// 2人とも何もしない入力
+pub(crate) fn no_inputs() -> [PlayerInput; 2] {
+    [PlayerInput::new(Movement::NONE, false); 2]
+}
+
+/// 1P だけを操作する入力
+pub(crate) fn first_player(movement: Movement, fire: bool) -> [PlayerInput; 2] {
+    [PlayerInput::new(movement, fire), PlayerInput::new(Movement::NONE, false)]
+}
+
+/// 標準の AI 同士で指定したフレーム数だけ試合を進め、各フレームの入力を返す（壁を壊してアイテムが出るところまで進める）
+pub(crate) fn play_with_ai(world: &mut World, frames: usize) -> Vec<Vec<PlayerInput>> {
+    let mut ai_players: Vec<AIPlayer> = (0..world.player_count).map(|_| AIPlayer::new()).collect();
+    let mut recorded = Vec::new();
+    for _ in 0..frames {
+        let player_inputs: Vec<PlayerInput> = ai_players.iter_mut().enumerate()
+            .map(|(i, ai_player)| AIStrategy::get_player_input(ai_player, &GameView::new(world, i)))
+            .collect();
+        world.on_next_frame(&player_inputs);
+        recorded.push(player_inputs);
+    }
+    recorded
+}
+
+/// 比較のためにゲームの状態を文字列にする
+pub(crate) fn snapshot(world: &World) -> String {
+    let mut text = String::new();
+    for player in world.players.iter() {
+        text.push_str(&format!("P{},{},{},{} ", player.get_x(), player.get_y(), player.death_state, player.power));
+    }
+    for wall in world.walls.iter() {
+        text.push_str(&format!("W{},{},{} ", wall.get_x(), wall.get_y(), wall.melt_state));
+    }
+    for bomb in world.bombs.iter() {
+        text.push_str(&format!("B{},{},{} ", bomb.get_x(), bomb.get_y(), bomb.remain_time));
+    }
+    for explosion in world.explosions.iter() {
+        text.push_str(&format!("E{},{},{} ", explosion.get_x(), explosion.get_y(), explosion.remain_time));
+    }
+    for item in world.power_up_items.iter() {
+        text.push_str(&format!("I{},{},{} ", item.get_x(), item.get_y(), item.kind.name()));
+    }
+    text
+}
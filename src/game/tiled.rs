@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use super::power_up_item::PowerUpKind;
+use super::stage::{Stage, StageCell, MIN_MAP_SIZE, MAX_MAP_SIZE};
+use super::world::MAX_PLAYER_COUNT;
+
+// Tiled のマップファイルの拡張子
+pub const TMX_EXTENSION: &str = "tmx";
+
+// 地形のタイルレイヤーの名前（この名前のレイヤーがなければ最初のタイルレイヤーを使う）
+const TERRAIN_LAYER_NAME: &str = "terrain";
+
+// タイルの gid のうち反転・回転を表すビット
+const GID_FLAG_MASK: u32 = 0xF000_0000;
+
+// 地形のタイルのクラス
+const TERRAIN_CLASSES: [(&str, StageCell); 4] = [
+    ("floor", StageCell::Floor),
+    ("hard_wall", StageCell::HardWall),
+    ("soft_wall", StageCell::SoftWall),
+    ("random_soft_wall", StageCell::RandomSoftWall),
+];
+
+/// タイルセット（firstgid からの番号ごとのタイルのクラス）
+struct Tileset {
+    name: String,
+    first_gid: u32,
+    tile_classes: HashMap<u32, String>,
+}
+
+/// Tiled で作った TMX ファイル（直交マップ）をステージとして読み込む
+///
+/// 地形はタイルレイヤー（"terrain" という名前のレイヤー、なければ最初のタイルレイヤー）から読み、
+/// タイルのクラスが floor / hard_wall / soft_wall / random_soft_wall のものをそれぞれのマスにする（タイルのないマスは床）。
+/// オブジェクトレイヤーのクラスが spawn のオブジェクトは player プロパティ（1〜4）の出現位置に、
+/// クラスが item のオブジェクトは item プロパティ（fire_up など）のアイテムになる。
+/// ステージの名前と壊せる壁の確率はマップの name / soft_wall_probability プロパティで指定できる。
+pub fn load_tmx(path: &Path) -> Result<Stage, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read map {}: {}", path.display(), e))?;
+    let document = Document::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let default_name = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().to_uppercase());
+    parse_map(document.root_element(), path.parent().unwrap_or(Path::new("")), &default_name)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn parse_map(map: Node, directory: &Path, default_name: &str) -> Result<Stage, String> {
+    if !map.has_tag_name("map") {
+        return Err("not a Tiled map".to_string());
+    }
+    if let Some(orientation) = map.attribute("orientation").filter(|orientation| *orientation != "orthogonal") {
+        return Err(format!("unsupported map orientation '{}'", orientation));
+    }
+    if map.attribute("infinite") == Some("1") {
+        return Err("infinite maps are not supported".to_string());
+    }
+    let width = int_attribute(map, "width")?;
+    let height = int_attribute(map, "height")?;
+    let tile_width = int_attribute(map, "tilewidth")? as f32;
+    let tile_height = int_attribute(map, "tileheight")? as f32;
+    let size_range = MIN_MAP_SIZE..=MAX_MAP_SIZE;
+    if !size_range.contains(&width) || !size_range.contains(&height) {
+        return Err(format!("map size must be between {0}x{0} and {1}x{1} but is {2}x{3}", MIN_MAP_SIZE, MAX_MAP_SIZE, width, height));
+    }
+
+    let mut stage = Stage::new(property(map, "name").unwrap_or(default_name), width, height);
+    if let Some(value) = property(map, "soft_wall_probability") {
+        stage.soft_wall_probability = value.trim().parse::<i32>()
+            .ok()
+            .filter(|p| (0..=100).contains(p))
+            .ok_or(format!("invalid soft wall probability '{}'", value))?;
+    }
+
+    // 地形
+    let tilesets = map.children()
+        .filter(|node| node.has_tag_name("tileset"))
+        .map(|node| load_tileset(node, directory))
+        .collect::<Result<Vec<Tileset>, String>>()?;
+    let layers = map.descendants().filter(|node| node.has_tag_name("layer")).collect::<Vec<Node>>();
+    let layer = layers.iter()
+        .find(|layer| layer.attribute("name").is_some_and(|name| name.eq_ignore_ascii_case(TERRAIN_LAYER_NAME)))
+        .or(layers.first())
+        .ok_or("the map has no tile layer")?;
+    let gids = layer_gids(*layer)?;
+    if gids.len() != (width * height) as usize {
+        return Err(format!("tile layer has {} tiles but the map is {}x{}", gids.len(), width, height));
+    }
+    for (i, gid) in gids.iter().enumerate() {
+        let x = i as i32 % width;
+        let y = i as i32 / width;
+        stage.set_cell(x, y, terrain_cell(&tilesets, *gid, x, y)?);
+    }
+
+    // プレイヤーの出現位置とアイテム（タイルセットのタイルに付いた当たり判定のオブジェクトは除く）
+    let mut spawn_positions: Vec<Option<(i32, i32)>> = vec![None; MAX_PLAYER_COUNT];
+    let objects = map.descendants()
+        .filter(|node| node.has_tag_name("objectgroup") && !node.ancestors().any(|ancestor| ancestor.has_tag_name("tileset")))
+        .flat_map(|group| group.children().filter(|node| node.has_tag_name("object")));
+    for object in objects {
+        let id = object.attribute("id").unwrap_or("?");
+        let object_width = float_attribute(object, "width")?.unwrap_or(0.0);
+        let object_height = float_attribute(object, "height")?.unwrap_or(0.0);
+        let mut object_y = float_attribute(object, "y")?.unwrap_or(0.0);
+        // タイルのオブジェクトは左下の座標で表される
+        if object.attribute("gid").is_some() {
+            object_y -= object_height;
+        }
+        let x = ((float_attribute(object, "x")?.unwrap_or(0.0) + object_width / 2.0) / tile_width).floor() as i32;
+        let y = ((object_y + object_height / 2.0) / tile_height).floor() as i32;
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return Err(format!("object {} at ({}, {}) is outside the map", id, x, y));
+        }
+
+        let class = object.attribute("type").or(object.attribute("class")).unwrap_or("");
+        match class {
+            "spawn" => {
+                let player = property(object, "player")
+                    .and_then(|value| value.trim().parse::<usize>().ok())
+                    .filter(|player| (1..=MAX_PLAYER_COUNT).contains(player))
+                    .ok_or(format!("spawn object {} at ({}, {}) needs a player property between 1 and {}", id, x, y, MAX_PLAYER_COUNT))?;
+                if spawn_positions[player - 1].is_some() {
+                    return Err(format!("spawn of player {} appears more than once (object {} at ({}, {}))", player, id, x, y));
+                }
+                if stage.get_cell(x, y) != StageCell::Floor {
+                    return Err(format!("spawn of player {} at ({}, {}) is not on the floor", player, x, y));
+                }
+                spawn_positions[player - 1] = Some((x, y));
+            }
+            "item" => {
+                let name = property(object, "item").unwrap_or("");
+                let kind = PowerUpKind::from_name(name)
+                    .ok_or(format!("item object {} at ({}, {}) has unknown item '{}'", id, x, y, name))?;
+                if stage.get_cell(x, y) != StageCell::Floor {
+                    return Err(format!("item object {} at ({}, {}) is not on the floor", id, x, y));
+                }
+                stage.set_cell(x, y, StageCell::Item(kind));
+            }
+            _ => return Err(format!("object {} at ({}, {}) has unknown class '{}' (expected spawn or item)", id, x, y, class)),
+        }
+    }
+    stage.spawn_positions = spawn_positions.iter().enumerate()
+        .map(|(i, position)| position.ok_or(format!("spawn of player {} is missing", i + 1)))
+        .collect::<Result<Vec<(i32, i32)>, String>>()?;
+
+    stage.validate()?;
+    Ok(stage)
+}
+
+/// タイルセットを読み込む（source がある場合は外部の TSX ファイルを読む）
+fn load_tileset(node: Node, directory: &Path) -> Result<Tileset, String> {
+    let first_gid = node.attribute("firstgid")
+        .and_then(|value| value.parse::<u32>().ok())
+        .ok_or("tileset has no valid firstgid")?;
+    let mut tileset = Tileset { name: String::new(), first_gid, tile_classes: HashMap::new() };
+    match node.attribute("source") {
+        Some(source) => {
+            let path = directory.join(source);
+            let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read tileset {}: {}", path.display(), e))?;
+            let document = Document::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+            read_tiles(document.root_element(), &mut tileset);
+        }
+        None => read_tiles(node, &mut tileset),
+    }
+    Ok(tileset)
+}
+
+fn read_tiles(node: Node, tileset: &mut Tileset) {
+    tileset.name = node.attribute("name").unwrap_or("").to_string();
+    for tile in node.children().filter(|child| child.has_tag_name("tile")) {
+        if let (Some(id), Some(class)) = (
+            tile.attribute("id").and_then(|value| value.parse::<u32>().ok()),
+            tile.attribute("type").or(tile.attribute("class")),
+        ) {
+            tileset.tile_classes.insert(id, class.to_string());
+        }
+    }
+}
+
+/// タイルレイヤーの gid を左上から順に返す（CSV 形式と XML 形式に対応する）
+fn layer_gids(layer: Node) -> Result<Vec<u32>, String> {
+    let data = layer.children().find(|node| node.has_tag_name("data")).ok_or("tile layer has no data")?;
+    if data.attribute("compression").is_some() || data.children().any(|node| node.has_tag_name("chunk")) {
+        return Err("compressed or chunked tile layers are not supported (save the map with the CSV tile layer format)".to_string());
+    }
+    match data.attribute("encoding") {
+        Some("csv") => data.text().unwrap_or("")
+            .split(',')
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<u32>().map_err(|_| format!("invalid tile id '{}' in tile layer", value)))
+            .collect(),
+        None => data.children()
+            .filter(|node| node.has_tag_name("tile"))
+            .map(|tile| tile.attribute("gid").unwrap_or("0").parse::<u32>().map_err(|e| format!("invalid tile id in tile layer: {}", e)))
+            .collect(),
+        Some(encoding) => Err(format!("unsupported tile layer encoding '{}' (save the map with the CSV tile layer format)", encoding)),
+    }
+}
+
+/// gid のタイルのクラスからマスの種類を決める
+fn terrain_cell(tilesets: &[Tileset], gid: u32, x: i32, y: i32) -> Result<StageCell, String> {
+    let gid = gid & !GID_FLAG_MASK;
+    if gid == 0 {
+        return Ok(StageCell::Floor);
+    }
+    let tileset = tilesets.iter()
+        .filter(|tileset| tileset.first_gid <= gid)
+        .max_by_key(|tileset| tileset.first_gid)
+        .ok_or(format!("unknown tile id {} at ({}, {}): no tileset contains it", gid, x, y))?;
+    let local_id = gid - tileset.first_gid;
+    let class = tileset.tile_classes.get(&local_id).map_or("", |class| class.as_str());
+    TERRAIN_CLASSES.iter()
+        .find(|(name, _)| *name == class)
+        .map(|(_, cell)| *cell)
+        .ok_or(format!(
+            "unknown tile id {} (tile {} of tileset '{}', class '{}') at ({}, {}): the class must be one of floor, hard_wall, soft_wall, random_soft_wall",
+            gid, local_id, tileset.name, class, x, y,
+        ))
+}
+
+/// オブジェクトやマップのカスタムプロパティの値
+fn property<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name("properties"))?
+        .children()
+        .find(|child| child.has_tag_name("property") && child.attribute("name") == Some(name))
+        .and_then(|property| property.attribute("value").or(property.text()))
+}
+
+fn int_attribute(node: Node, name: &str) -> Result<i32, String> {
+    node.attribute(name)
+        .and_then(|value| value.parse::<i32>().ok())
+        .ok_or(format!("<{}> has no valid {} attribute", node.tag_name().name(), name))
+}
+
+fn float_attribute(node: Node, name: &str) -> Result<Option<f32>, String> {
+    node.attribute(name)
+        .map(|value| value.parse::<f32>().map_err(|_| format!("<{}> has invalid {} attribute '{}'", node.tag_name().name(), name, value)))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 5x5 のマップ（タイルセットは gid 1 から floor、hard_wall、soft_wall、クラスのないタイルの順）
+    fn parse(terrain: &str, objects: &str) -> Result<Stage, String> {
+        let text = format!(r#"<map orientation="orthogonal" width="5" height="5" tilewidth="32" tileheight="32" infinite="0">
+ <tileset firstgid="1" name="test">
+  <tile id="0" type="floor"/>
+  <tile id="1" type="hard_wall"/>
+  <tile id="2" class="soft_wall"/>
+  <tile id="3"/>
+ </tileset>
+ <layer name="terrain"><data encoding="csv">{}</data></layer>
+ <objectgroup>{}</objectgroup>
+</map>"#, terrain, objects);
+        let document = Document::parse(&text).unwrap();
+        parse_map(document.root_element(), Path::new(""), "TEST")
+    }
+
+    const TERRAIN: &str = "2,2,2,2,2, 2,1,1,1,2, 2,0,3,1,2, 2,1,1,1,2, 2,2,2,2,2";
+
+    fn spawn(player: usize, x: i32, y: i32) -> String {
+        format!(r#"<object id="{0}" type="spawn" x="{1}" y="{2}"><properties><property name="player" value="{0}"/></properties><point/></object>"#, player, x * 32 + 16, y * 32 + 16)
+    }
+
+    fn spawns() -> String {
+        [spawn(1, 1, 1), spawn(2, 3, 3), spawn(3, 3, 1), spawn(4, 1, 3)].concat()
+    }
+
+    #[test]
+    fn map_is_read_from_tiles_and_objects() {
+        let item = r#"<object id="9" class="item" x="64" y="96" width="32" height="32"><properties><property name="item" value="kick"/></properties></object>"#;
+        let stage = parse(TERRAIN, &(spawns() + item)).unwrap();
+        assert_eq!((stage.name.as_str(), stage.width, stage.height), ("TEST", 5, 5));
+        assert_eq!(stage.spawn_positions, vec![(1, 1), (3, 3), (3, 1), (1, 3)]);
+        assert!(stage.get_cell(1, 2) == StageCell::Floor);
+        assert!(stage.get_cell(2, 2) == StageCell::SoftWall);
+        assert!(stage.get_cell(4, 4) == StageCell::HardWall);
+        assert!(stage.get_cell(2, 3) == StageCell::Item(PowerUpKind::Kick));
+    }
+
+    #[test]
+    fn unknown_tile_ids_are_reported_with_their_position() {
+        // タイルセットのタイルの数を超える gid
+        let terrain = "2,2,2,2,2, 2,1,1,1,2, 2,0,3,1,2, 2,1,9,1,2, 2,2,2,2,2";
+        assert_eq!(
+            parse(terrain, &spawns()).err().unwrap(),
+            "unknown tile id 9 (tile 8 of tileset 'test', class '') at (2, 3): the class must be one of floor, hard_wall, soft_wall, random_soft_wall",
+        );
+
+        // クラスのないタイル
+        let terrain = "2,2,2,2,2, 2,1,4,1,2, 2,0,3,1,2, 2,1,1,1,2, 2,2,2,2,2";
+        assert_eq!(
+            parse(terrain, &spawns()).err().unwrap(),
+            "unknown tile id 4 (tile 3 of tileset 'test', class '') at (2, 1): the class must be one of floor, hard_wall, soft_wall, random_soft_wall",
+        );
+    }
+
+    #[test]
+    fn bundled_sample_map_is_loaded_with_its_tileset() {
+        let stage = Stage::load(Path::new("res/stage/07_tiled_sample.tmx")).unwrap();
+        assert_eq!((stage.name.as_str(), stage.width, stage.height, stage.soft_wall_probability), ("TILED SAMPLE", 19, 13, 60));
+    }
+}
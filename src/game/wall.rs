@@ -0,0 +1,50 @@
+use super::{light_sprite::LightSprite, light_sprite::LightSpriteEvent};
+
+pub const TIME_TO_MELT: i32 = 30;
+
+// 壁が壊れたときにパワーアップアイテムが出る確率（パーセント）
+pub const POWER_UP_ITEM_PROBABILITY: i32 = 10;
+
+#[derive(Clone)]
+pub struct Wall {
+    x: i32,
+    y: i32,
+    pub is_breakable: bool,
+    pub melt_state: i32,
+}
+
+impl Wall {
+    pub fn new(x: i32, y: i32, is_breakable: bool) -> Wall {
+        Wall {
+            x,
+            y,
+            is_breakable,
+            melt_state: 0,
+        }
+    }
+
+    pub fn start_melting(&mut self) {
+        if self.melt_state == 0 {
+            self.melt_state = 1;
+        }
+    }
+
+    pub fn is_melting(&self) -> bool {
+        self.melt_state > 0
+    }
+}
+
+impl LightSprite for Wall {
+    fn get_x(&self) -> i32 { self.x }
+    fn get_y(&self) -> i32 { self.y }
+
+    fn on_next_frame(&mut self) -> LightSpriteEvent {
+        if self.melt_state > 0 {
+            self.melt_state += 1;
+            if self.melt_state >= TIME_TO_MELT {
+                return LightSpriteEvent::Melted
+            }
+        }
+        LightSpriteEvent::None
+    }
+}
@@ -0,0 +1,636 @@
+extern crate rand;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use super::bomb::Bomb;
+use super::constants;
+use super::explosion;
+use super::explosion::Explosion;
+use super::light_sprite::{LightSprite, LightSpriteEvent};
+use super::map_generator;
+use super::player::Player;
+use super::player_input::PlayerInput;
+use super::power_up_item::{PowerUpItem, PowerUpKind};
+use super::rule::Rule;
+use super::stage::{Stage, StageCell};
+use super::wall::{self, Wall};
+
+pub const MIN_PLAYER_COUNT: usize = 2;
+pub const MAX_PLAYER_COUNT: usize = 4;
+
+#[derive(Clone)]
+pub enum State {
+    Playing,
+    // 最後まで生き残ったプレイヤーの番号
+    Won(usize),
+    DrawGame,
+}
+
+/// 1フレームの間に起きた出来事（効果音などの演出に使う）
+pub enum WorldEvent {
+    SetBomb,
+    Explosion,
+    PowerUp(PowerUpKind),
+    PlayerDied(usize),
+}
+
+/// 描画や入力デバイスに依存しないゲームのルール本体
+#[derive(Clone)]
+pub struct World {
+    pub state: State,
+
+    // 参加するプレイヤーの人数
+    pub player_count: usize,
+
+    // ルール設定
+    pub rule: Rule,
+
+    // マップの地形とプレイヤーの出現位置
+    pub stage: Stage,
+
+    // マップ生成とアイテム出現に使う乱数のシード
+    pub seed: u64,
+
+    // シードから生成した乱数生成器（ゲーム中の乱数はすべてここから取る）
+    rng: StdRng,
+
+    // スプライトの配列
+    pub players: Vec<Player>,
+    pub walls: Vec<Wall>,
+    pub bombs: Vec<Bomb>,
+    pub explosions: Vec<Explosion>,
+    pub power_up_items: Vec<PowerUpItem>,
+}
+
+impl World {
+    pub fn new(seed: u64, player_count: usize, rule: Rule, stage: Stage) -> World {
+        assert!((MIN_PLAYER_COUNT..=MAX_PLAYER_COUNT).contains(&player_count), "invalid player count: {}", player_count);
+        let mut world = World {
+            state: State::Playing,
+            player_count,
+            rule,
+            stage,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            players: Vec::new(),
+            walls: Vec::new(),
+            bombs: Vec::new(),
+            explosions: Vec::new(),
+            power_up_items: Vec::new(),
+        };
+        world.start_game(seed);
+        world
+    }
+
+    /// 指定したシードでゲームを開始する（同じシードなら同じマップ、同じアイテム出現順になる）
+    pub fn start_game(&mut self, seed: u64) {
+        self.state = State::Playing;
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+
+        // Playerの生成
+        self.players.clear();
+        let spawn_positions = &self.stage.spawn_positions[..self.player_count];
+        for (i, (x, y)) in spawn_positions.iter().enumerate() {
+            self.players.push(Player::new(
+                    i,
+                    x * constants::CHARACTER_SIZE,
+                    y * constants::CHARACTER_SIZE,
+            ));
+        }
+
+        // ルールに従ってステージからマップを作り、壁とアイテムを配置する
+        let map = map_generator::generate_map(&self.stage, &self.rule, self.player_count, &mut self.rng);
+        self.walls.clear();
+        self.power_up_items.clear();
+        for y in 0..map.height {
+            let yf = y * constants::CHARACTER_SIZE;
+            for x in 0..map.width {
+                let xf = x * constants::CHARACTER_SIZE;
+                match map.get_cell(x, y) {
+                    StageCell::Floor | StageCell::RandomSoftWall => (),
+                    StageCell::HardWall => self.walls.push(Wall::new(xf, yf, false)),
+                    StageCell::SoftWall => self.walls.push(Wall::new(xf, yf, true)),
+                    StageCell::Item(kind) => self.power_up_items.push(PowerUpItem::new(xf, yf, kind)),
+                }
+            }
+        }
+
+        // その他のオブジェクトの初期化
+        self.bombs.clear();
+        self.explosions.clear();
+    }
+
+    /// AI が先読みに使う複製（乱数を入れ替えて、この先に出るアイテムがわからないようにする）
+    pub fn clone_for_simulation(&self, seed: u64) -> World {
+        World {
+            rng: StdRng::seed_from_u64(seed),
+            ..self.clone()
+        }
+    }
+
+    /// 各プレイヤーの入力を受け取ってゲームを1フレーム進める
+    pub fn on_next_frame(&mut self, player_inputs: &[PlayerInput]) -> Vec<WorldEvent> {
+        let mut events: Vec<WorldEvent> = Vec::new();
+
+        // プレイヤーの移動処理
+        for (i, player_input) in player_inputs.iter().enumerate() {
+            self.players[i].push_position();
+            Player::move_for_next_frame(self, i, *player_input);
+        }
+
+        // プレイヤー同士の衝突回避
+        self.players_collision_detect();
+
+        // 蹴られた爆弾の移動
+        self.slide_bombs();
+
+        // 燃えている爆発のマスに入った爆弾の誘爆
+        self.ignite_bombs_in_explosions();
+
+        // プレイヤー、パワーアップアイテム、壁、爆発の状態変化
+        let mut new_power_up_items: Vec<PowerUpItem> = Vec::new();
+        Self::sprites_state_transition(&mut self.players, &mut new_power_up_items, &mut self.rng, &self.rule);
+        Self::sprites_state_transition(&mut self.power_up_items, &mut new_power_up_items, &mut self.rng, &self.rule);
+        Self::sprites_state_transition(&mut self.walls, &mut new_power_up_items, &mut self.rng, &self.rule);
+        Self::sprites_state_transition(&mut self.explosions, &mut new_power_up_items, &mut self.rng, &self.rule);
+
+        // パワーアップアイテムの追加
+        self.power_up_items.append(&mut new_power_up_items);
+
+        // 移動後の処理
+        for i in 0..self.players.len() {
+            Player::after_next_frame(self, i, &mut events);
+        }
+
+        // 爆弾の状態変化
+        let mut new_explode_bomb: Vec<Bomb> = Vec::new();
+        self.bombs.retain_mut(|bomb|
+            if let LightSpriteEvent::DeleteMe = bomb.on_next_frame() {
+                // 爆発した爆弾をリストに入れておく（滑っている途中なら一番近いマスで爆発する）
+                bomb.stop();
+                new_explode_bomb.push(bomb.clone());
+                false
+            } else {
+                true
+            }
+        );
+
+        // 爆発の生成
+        if !new_explode_bomb.is_empty() {
+            events.push(WorldEvent::Explosion);
+            for bomb in new_explode_bomb.iter() {
+                self.explosions.push(Explosion::new(bomb.get_x(), bomb.get_y(), explosion::Position::CENTER, bomb.owner));
+                self.expand_explosion(bomb, -1, 0);
+                self.expand_explosion(bomb, 1, 0);
+                self.expand_explosion(bomb, 0, -1);
+                self.expand_explosion(bomb, 0, 1);
+            }
+        }
+
+        // ゲーム状態の変化
+        if let State::Playing = self.state {
+            let mut survivors = self.players.iter().filter(|player| !player.is_dead());
+            match (survivors.next(), survivors.next()) {
+                (None, _) => self.state = State::DrawGame,
+                (Some(player), None) => self.state = State::Won(player.player_number),
+                _ => (),
+            }
+        }
+
+        events
+    }
+
+    fn players_collision_detect(&mut self) {
+        for a in 0..self.players.len() {
+            for b in (a + 1)..self.players.len() {
+                self.players_pair_collision_detect(a, b);
+            }
+        }
+    }
+
+    fn players_pair_collision_detect(&mut self, a: usize, b: usize) {
+        if self.players[a].is_dead() || self.players[b].is_dead() {
+            return;
+        }
+        if (self.players[a].get_x() - self.players[b].get_x()).abs() < constants::CHARACTER_SIZE &&
+            (self.players[a].get_y() - self.players[b].get_y()).abs() < constants::CHARACTER_SIZE {
+            let player_a_is_not_cancelable = (self.players[a].pushed_x - self.players[b].get_x()).abs() < constants::CHARACTER_SIZE &&
+                (self.players[a].pushed_y - self.players[b].get_y()).abs() < constants::CHARACTER_SIZE;
+            let player_b_is_not_cancelable = (self.players[a].get_x() - self.players[b].pushed_x).abs() < constants::CHARACTER_SIZE &&
+                (self.players[a].get_y() - self.players[b].pushed_y).abs() < constants::CHARACTER_SIZE;
+            if !player_a_is_not_cancelable && player_b_is_not_cancelable {
+                self.players[a].pop_position();
+            } else if player_a_is_not_cancelable && !player_b_is_not_cancelable {
+                self.players[b].pop_position();
+            } else {
+                self.players[a].pop_position();
+                self.players[b].pop_position();
+            }
+        }
+    }
+
+    /// 蹴られた爆弾を滑らせる（マスの境目で進む先がふさがっていたら止まる）
+    fn slide_bombs(&mut self) {
+        for i in 0..self.bombs.len() {
+            if !self.bombs[i].is_sliding() {
+                continue;
+            }
+            if self.bombs[i].is_on_tile() {
+                let (dx, dy) = self.bombs[i].slide.delta();
+                let nx = self.bombs[i].get_x() + dx * constants::CHARACTER_SIZE;
+                let ny = self.bombs[i].get_y() + dy * constants::CHARACTER_SIZE;
+                let overlaps = |x: i32, y: i32| (x - nx).abs() < constants::CHARACTER_SIZE && (y - ny).abs() < constants::CHARACTER_SIZE;
+                let is_blocked = self.walls.iter().any(|w| overlaps(w.get_x(), w.get_y())) ||
+                    self.bombs.iter().enumerate().any(|(j, b)| j != i && overlaps(b.get_x(), b.get_y())) ||
+                    self.power_up_items.iter().any(|item| overlaps(item.get_x(), item.get_y())) ||
+                    self.players.iter().any(|p| !p.is_dead() && overlaps(p.get_x(), p.get_y()));
+                if is_blocked {
+                    self.bombs[i].stop();
+                    continue;
+                }
+            }
+            self.bombs[i].slide_for_next_frame();
+        }
+    }
+
+    /// 爆発が燃えているマスにある爆弾を爆発させる（爆発が起きたときにいなかった、滑ってきた爆弾も誘爆させる）
+    fn ignite_bombs_in_explosions(&mut self) {
+        for bomb in self.bombs.iter_mut() {
+            if self.explosions.iter().any(|explosion| explosion.get_x() == bomb.get_tile_x() && explosion.get_y() == bomb.get_tile_y()) {
+                bomb.explode_soon();
+            }
+        }
+    }
+
+    fn expand_explosion(&mut self, bomb: &Bomb, xx: i32, yy: i32) {
+        for n in 1..(bomb.power + 1) {
+            let px = bomb.get_x() + xx * n * constants::CHARACTER_SIZE;
+            let py = bomb.get_y() + yy * n * constants::CHARACTER_SIZE;
+
+            // 壁があるか？
+            if let Some(wall) = self.walls.iter_mut().find(|w| w.get_x() == px && w.get_y() == py) {
+                // 壁の破壊（貫通する爆弾なら壊せる壁の先まで爆発が届く）
+                if wall.is_breakable {
+                    wall.start_melting();
+                    if bomb.pierce {
+                        continue;
+                    }
+                }
+                return;
+            }
+            // 爆弾があったら誘爆する
+            if let Some(b) = self.bombs.iter_mut().find(|b| b.get_tile_x() == px && b.get_tile_y() == py) {
+                b.explode_soon();
+                return;
+            }
+            // パワーアップアイテムがあったら破壊する
+            if let Some(i) = self.power_up_items.iter().position(|item| item.get_x() == px && item.get_y() == py) {
+                self.power_up_items.remove(i);
+                return;
+            }
+            // 新しい爆発を生成する
+            let position: explosion::Position = if xx == 0 {
+                if n == bomb.power {
+                    if yy > 0 { explosion::Position::BOTTOM } else { explosion::Position::TOP }
+                } else {
+                    explosion::Position::VERTICAL
+                }
+            } else if n == bomb.power {
+                if xx > 0 { explosion::Position::RIGHT } else { explosion::Position::LEFT }
+            } else {
+                explosion::Position::HORIZONTAL
+            };
+            self.explosions.push(Explosion::new(px, py, position, bomb.owner));
+        }
+    }
+
+    fn sprites_state_transition<T: LightSprite>(sprites: &mut Vec<T>, new_power_up_items: &mut Vec<PowerUpItem>, rng: &mut StdRng, rule: &Rule) {
+        sprites.retain_mut(|s|
+            match s.on_next_frame() {
+                LightSpriteEvent::DeleteMe => false,
+                LightSpriteEvent::Melted => {
+                    // 一定の確率でパワーアップアイテムが出る
+                    if rng.gen_range(0, 100) < wall::POWER_UP_ITEM_PROBABILITY {
+                        if let Some(kind) = PowerUpKind::choose(rng, rule) {
+                            new_power_up_items.push(PowerUpItem::new(s.get_x(), s.get_y(), kind));
+                        }
+                    }
+                    false
+                }
+                _ => true
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::player_input::Movement;
+    use crate::game::stage::MIN_MAP_SIZE;
+    use crate::game::test_support::{first_player, new_world, no_inputs, play_with_ai, snapshot, TEST_STAGE};
+
+    /// マップ上の座標に置いた爆弾
+    fn bomb_at(x: i32, y: i32, owner: usize) -> Bomb {
+        Bomb::new(x * constants::CHARACTER_SIZE, y * constants::CHARACTER_SIZE, 1, owner)
+    }
+
+    /// 1P を指定した方向に指定したマス数だけ歩かせる
+    fn walk(world: &mut World, movement: Movement, tiles: i32) {
+        for _ in 0..tiles * world.players[0].frames_per_tile() {
+            world.on_next_frame(&first_player(movement, false));
+        }
+    }
+
+    fn has_explosion_at(world: &World, x: i32, y: i32) -> bool {
+        world.explosions.iter().any(|explosion| explosion.get_x() == x * constants::CHARACTER_SIZE && explosion.get_y() == y * constants::CHARACTER_SIZE)
+    }
+
+    #[test]
+    fn explosion_burns_for_explosion_time() {
+        let mut world = new_world();
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        assert_eq!(world.bombs.len(), 1);
+        assert_eq!((world.bombs[0].get_x(), world.bombs[0].get_y()), (constants::CHARACTER_SIZE, constants::CHARACTER_SIZE));
+
+        // 炎が届かない (1, 3) まで逃げて、爆発するのを待つ
+        walk(&mut world, Movement::DOWN, 2);
+        while world.bombs.len() == 1 {
+            world.on_next_frame(&no_inputs());
+        }
+        assert_eq!(world.explosions.len(), 3);
+        assert!(has_explosion_at(&world, 1, 1) && has_explosion_at(&world, 1, 2) && has_explosion_at(&world, 2, 1));
+        assert_eq!(world.explosions[0].remain_time, constants::EXPLOSION_TIME);
+        assert!(!world.players[0].is_dead());
+
+        for _ in 0..constants::EXPLOSION_TIME - 1 {
+            world.on_next_frame(&no_inputs());
+        }
+        assert_eq!(world.explosions.len(), 3);
+        world.on_next_frame(&no_inputs());
+        assert!(world.explosions.is_empty());
+    }
+
+    #[test]
+    fn bomb_explodes_exactly_bomb_time_frames_after_placement() {
+        let mut world = new_world();
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        for _ in 1..constants::BOMB_TIME - 1 {
+            world.on_next_frame(&no_inputs());
+        }
+        assert_eq!(world.bombs.len(), 1);
+        assert!(world.explosions.is_empty());
+        world.on_next_frame(&no_inputs());
+        assert!(world.bombs.is_empty());
+        assert!(has_explosion_at(&world, 1, 1));
+        // 逃げなかったので自分の爆発に巻き込まれる
+        world.on_next_frame(&no_inputs());
+        assert_eq!(world.players[0].killed_by, Some(0));
+        assert!(matches!(world.state, State::Won(1)));
+    }
+
+    #[test]
+    fn four_player_match_lasts_until_one_player_is_left() {
+        let mut world = World::new(0, 4, Rule::default(), Stage::parse(TEST_STAGE).unwrap());
+        for (player, (x, y)) in world.players.iter().zip(world.stage.spawn_positions.iter()) {
+            assert_eq!((player.get_x(), player.get_y()), (x * constants::CHARACTER_SIZE, y * constants::CHARACTER_SIZE));
+        }
+
+        // 2P、3P、4P の順に炎に巻き込み、最後の1人になったところで勝ちが決まる
+        let player_inputs = [PlayerInput::new(Movement::NONE, false); 4];
+        for n in 1..4 {
+            let (x, y) = world.stage.spawn_positions[n];
+            world.explosions.push(Explosion::new(x * constants::CHARACTER_SIZE, y * constants::CHARACTER_SIZE, explosion::Position::CENTER, 0));
+            world.on_next_frame(&player_inputs);
+            assert_eq!(world.players.iter().filter(|player| player.is_dead()).count(), n);
+            if n < 3 {
+                assert!(matches!(world.state, State::Playing));
+            }
+        }
+        assert!(matches!(world.state, State::Won(0)));
+    }
+
+    #[test]
+    fn active_bombs_are_limited_by_capacity() {
+        let mut world = new_world();
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        walk(&mut world, Movement::RIGHT, 1);
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        assert_eq!(world.bombs.len(), 1);
+
+        // ボムアップを取ると、もう1つ置ける
+        world.power_up_items.push(PowerUpItem::new(3 * constants::CHARACTER_SIZE, constants::CHARACTER_SIZE, PowerUpKind::BombUp));
+        walk(&mut world, Movement::RIGHT, 1);
+        assert!(world.power_up_items.is_empty());
+        assert_eq!(world.players[0].bomb_capacity, 2);
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        assert_eq!(world.bombs.len(), 2);
+        walk(&mut world, Movement::RIGHT, 1);
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        assert_eq!(world.bombs.len(), 2);
+
+        // 設置数の制限をなくすルールでは何個でも置ける
+        world.rule.unlimited_bombs = true;
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        assert_eq!(world.bombs.len(), 3);
+    }
+
+    /// 外周だけが壊せない壁で、四隅にプレイヤーの出現位置があり、内側は確率で壊せる壁になるステージ
+    fn open_stage(width: i32, height: i32) -> String {
+        let mut text = String::from("NAME open\nMAP\n");
+        for y in 0..height {
+            for x in 0..width {
+                text.push(match (x, y) {
+                    _ if x == 0 || y == 0 || x == width - 1 || y == height - 1 => '#',
+                    (1, 1) => '1',
+                    _ if (x, y) == (width - 2, height - 2) => '2',
+                    _ if (x, y) == (width - 2, 1) => '3',
+                    _ if (x, y) == (1, height - 2) => '4',
+                    _ => '?',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    #[test]
+    fn match_is_played_on_a_map_of_any_size() {
+        for (width, height) in [(MIN_MAP_SIZE, MIN_MAP_SIZE), (31, 9), (9, 25)] {
+            let mut world = World::new(3, 4, Rule::default(), Stage::parse(&open_stage(width, height)).unwrap());
+            play_with_ai(&mut world, 300);
+            let is_inside = |x: i32, y: i32| (0..width * constants::CHARACTER_SIZE).contains(&x) && (0..height * constants::CHARACTER_SIZE).contains(&y);
+            assert!(world.walls.iter().all(|wall| is_inside(wall.get_x(), wall.get_y())));
+            assert!(world.players.iter().all(|player| is_inside(player.get_x(), player.get_y())));
+            assert!(world.bombs.iter().all(|bomb| is_inside(bomb.get_x(), bomb.get_y())));
+            assert!(world.explosions.iter().all(|explosion| is_inside(explosion.get_x(), explosion.get_y())));
+        }
+    }
+
+    #[test]
+    fn explosion_detonates_bombs_in_range_on_the_next_frame() {
+        let mut world = new_world();
+        let mut first = bomb_at(3, 3, 0);
+        first.power = 2;
+        first.remain_time = 10;
+        world.bombs.push(first);
+        world.bombs.push(bomb_at(5, 3, 1));
+
+        for _ in 0..10 {
+            world.on_next_frame(&no_inputs());
+        }
+        assert_eq!(world.bombs.len(), 1);
+        assert!(has_explosion_at(&world, 4, 3));
+        assert!(!has_explosion_at(&world, 5, 3));
+        world.on_next_frame(&no_inputs());
+        assert!(world.bombs.is_empty());
+        assert!(has_explosion_at(&world, 5, 3) && has_explosion_at(&world, 6, 3));
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_match() {
+        let new_match = |seed: u64| World::new(seed, 4, Rule::default(), Stage::classic());
+        let breakable_walls = |world: &World| world.walls.iter().filter(|wall| wall.is_breakable).count();
+        let mut world = new_match(1);
+        let player_inputs = play_with_ai(&mut world, 1800);
+        assert!(breakable_walls(&world) < breakable_walls(&new_match(1)));
+
+        // 同じシードで同じ入力を与えると、毎フレーム同じ状態になる
+        let mut first = new_match(1);
+        let mut second = new_match(1);
+        for inputs in player_inputs.iter() {
+            first.on_next_frame(inputs);
+            second.on_next_frame(inputs);
+            assert_eq!(snapshot(&first), snapshot(&second));
+        }
+        assert_eq!(snapshot(&first), snapshot(&world));
+
+        // シードが違えばマップも違う
+        assert_ne!(snapshot(&new_match(2)), snapshot(&new_match(1)));
+    }
+
+    #[test]
+    fn remote_bomb_explodes_only_when_detonated() {
+        let mut world = new_world();
+        world.players[0].has_remote = true;
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        assert!(world.bombs[0].remote);
+        walk(&mut world, Movement::DOWN, 2);
+        for _ in 0..constants::BOMB_TIME * 2 {
+            world.on_next_frame(&no_inputs());
+        }
+        assert_eq!(world.bombs.len(), 1);
+
+        let detonate = [PlayerInput { movement: Movement::NONE, fire: false, detonate: true }, PlayerInput::new(Movement::NONE, false)];
+        world.on_next_frame(&detonate);
+        world.on_next_frame(&no_inputs());
+        assert!(world.bombs.is_empty());
+        assert!(has_explosion_at(&world, 1, 1));
+        assert!(!world.players[0].is_dead());
+    }
+
+    #[test]
+    fn remote_bomb_of_a_dead_player_explodes_on_its_timer() {
+        let mut world = new_world();
+        world.players[0].has_remote = true;
+        world.on_next_frame(&first_player(Movement::NONE, true));
+        walk(&mut world, Movement::DOWN, 2);
+        world.explosions.push(Explosion::new(constants::CHARACTER_SIZE, 3 * constants::CHARACTER_SIZE, explosion::Position::CENTER, 1));
+        world.on_next_frame(&no_inputs());
+        assert!(world.players[0].is_dead());
+        assert!(!world.bombs[0].remote);
+        for _ in 0..constants::BOMB_TIME {
+            world.on_next_frame(&no_inputs());
+        }
+        assert!(world.bombs.is_empty());
+    }
+
+    #[test]
+    fn pierce_bomb_burns_through_several_soft_walls() {
+        for pierce in [true, false] {
+            let mut world = new_world();
+            for x in 4..7 {
+                world.walls.push(Wall::new(x * constants::CHARACTER_SIZE, 3 * constants::CHARACTER_SIZE, true));
+            }
+            let mut bomb = bomb_at(3, 3, 0);
+            bomb.power = 4;
+            bomb.pierce = pierce;
+            bomb.remain_time = 1;
+            world.bombs.push(bomb);
+            world.on_next_frame(&no_inputs());
+
+            let melting = world.walls.iter().filter(|wall| wall.is_melting()).count();
+            if pierce {
+                assert_eq!(melting, 3);
+                assert!(has_explosion_at(&world, 7, 3));
+            } else {
+                assert_eq!(melting, 1);
+                assert!(!has_explosion_at(&world, 7, 3));
+            }
+            assert!((4..7).all(|x| !has_explosion_at(&world, x, 3)));
+        }
+    }
+
+    #[test]
+    fn kicked_bomb_slides_until_it_hits_a_wall() {
+        let mut world = new_world();
+        world.players[0].can_kick = true;
+        world.bombs.push(bomb_at(2, 1, 1));
+
+        world.on_next_frame(&first_player(Movement::RIGHT, false));
+        assert!(world.bombs[0].is_sliding());
+        assert_eq!(world.players[0].get_x(), constants::CHARACTER_SIZE);
+        for _ in 0..60 {
+            world.on_next_frame(&no_inputs());
+        }
+        assert!(!world.bombs[0].is_sliding());
+        assert_eq!((world.bombs[0].get_x(), world.bombs[0].get_y()), (7 * constants::CHARACTER_SIZE, constants::CHARACTER_SIZE));
+
+        // 蹴れないプレイヤーは爆弾を押しても動かせない
+        let mut world = new_world();
+        world.bombs.push(bomb_at(2, 1, 1));
+        walk(&mut world, Movement::RIGHT, 1);
+        assert!(!world.bombs[0].is_sliding());
+        assert_eq!(world.bombs[0].get_x(), 2 * constants::CHARACTER_SIZE);
+        assert_eq!(world.players[0].get_x(), constants::CHARACTER_SIZE);
+    }
+
+    #[test]
+    fn kicked_bomb_explodes_when_sliding_into_fire() {
+        let mut world = new_world();
+        let mut bomb = bomb_at(3, 1, 0);
+        bomb.kick(Movement::RIGHT);
+        world.bombs.push(bomb);
+        world.explosions.push(Explosion::new(5 * constants::CHARACTER_SIZE, constants::CHARACTER_SIZE, explosion::Position::CENTER, 1));
+
+        for _ in 0..constants::EXPLOSION_TIME {
+            world.on_next_frame(&no_inputs());
+            if world.bombs.is_empty() {
+                break;
+            }
+            assert!(world.bombs[0].get_tile_x() <= 5 * constants::CHARACTER_SIZE, "the bomb slid through the fire");
+        }
+        assert!(world.bombs.is_empty());
+        assert!(world.explosions.iter().any(|explosion| explosion.get_x() == 6 * constants::CHARACTER_SIZE && explosion.get_y() == constants::CHARACTER_SIZE));
+    }
+
+    #[test]
+    fn sliding_bomb_blocks_players() {
+        let mut world = new_world();
+        world.players[0] = Player::new(0, 2 * constants::CHARACTER_SIZE, constants::CHARACTER_SIZE);
+        let mut bomb = bomb_at(3, 1, 1);
+        bomb.kick(Movement::DOWN);
+        world.bombs.push(bomb);
+
+        // 爆弾が次のマスに移りきるまでは、滑っている途中の爆弾にぶつかって進めない
+        let right = [PlayerInput::new(Movement::RIGHT, false), PlayerInput::new(Movement::NONE, false)];
+        while world.bombs[0].get_y() < 2 * constants::CHARACTER_SIZE {
+            world.on_next_frame(&right);
+            assert_eq!(world.players[0].get_x(), 2 * constants::CHARACTER_SIZE);
+        }
+        world.on_next_frame(&right);
+        assert!(world.players[0].get_x() > 2 * constants::CHARACTER_SIZE);
+    }
+}
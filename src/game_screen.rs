@@ -1,12 +1,13 @@
+pub mod ai_operation;
 pub mod bomb;
-pub mod constants;
+pub mod camera;
+pub mod drawable;
 pub mod explosion;
 pub mod screen;
 pub mod human_operation;
-pub mod light_sprite;
-pub mod player_input;
 pub mod player_operation;
 pub mod player_type;
 pub mod player;
 pub mod power_up_item;
-pub mod wall;
\ No newline at end of file
+pub mod wall;
+pub mod world_renderer;
\ No newline at end of file
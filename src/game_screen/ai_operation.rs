@@ -0,0 +1,15 @@
+use sdl2::keyboard::KeyboardState;
+
+use rust_bomber::ai::strategy::{AIStrategy, GameView};
+use rust_bomber::game::player_input::PlayerInput;
+use super::player_operation::PlayerOperation;
+
+impl PlayerOperation for Box<dyn AIStrategy> {
+    fn is_ready(&mut self, view: &GameView) -> bool {
+        self.as_mut().is_ready(view)
+    }
+
+    fn get_player_input(&mut self, _: &KeyboardState, view: &GameView) -> PlayerInput {
+        self.as_mut().get_player_input(view)
+    }
+}
@@ -0,0 +1,85 @@
+use sdl2::rect::Rect;
+
+use rust_bomber::game::{constants, light_sprite::LightSprite, world::World};
+
+// マップが画面に収まらない場合に縮小する倍率の下限（これ以上大きいマップはスクロールする）
+const MIN_SCALE: f32 = 0.5;
+
+/// マップ上の座標を画面上の座標に変換する
+///
+/// マップが表示範囲より小さい場合は中央に置き、大きい場合は MIN_SCALE まで縮小して表示範囲に収める。
+/// それでも収まらない場合は注目する位置が表示範囲の中央に来るようにスクロールする。
+pub struct Camera {
+    // マップを表示する画面上の範囲
+    view: Rect,
+    // 表示範囲の左上に表示するマップ上の座標
+    offset_x: f32,
+    offset_y: f32,
+    scale: f32,
+}
+
+impl Camera {
+    /// マップの大きさ（ピクセル）と注目する位置（マップ上の座標）からカメラを作る
+    pub fn new(view: Rect, map_width: i32, map_height: i32, center_x: i32, center_y: i32) -> Camera {
+        let map_width = map_width as f32;
+        let map_height = map_height as f32;
+        let view_width = view.width() as f32;
+        let view_height = view.height() as f32;
+        let scale = (view_width / map_width).min(view_height / map_height).clamp(MIN_SCALE, 1.0);
+        Camera {
+            view,
+            offset_x: Self::offset(map_width, view_width / scale, center_x as f32),
+            offset_y: Self::offset(map_height, view_height / scale, center_y as f32),
+            scale,
+        }
+    }
+
+    /// 生きているプレイヤー全員を囲む範囲の中心に注目して、画面全体にマップを表示するカメラを作る
+    pub fn for_world(world: &World) -> Camera {
+        let living_players = world.players.iter().filter(|player| !player.is_dead()).collect::<Vec<_>>();
+        let targets = if living_players.is_empty() { world.players.iter().collect() } else { living_players };
+        let (min_x, max_x, min_y, max_y) = targets.iter().fold(
+            (i32::MAX, i32::MIN, i32::MAX, i32::MIN),
+            |(min_x, max_x, min_y, max_y), player| (
+                min_x.min(player.get_x()), max_x.max(player.get_x()),
+                min_y.min(player.get_y()), max_y.max(player.get_y()),
+            ),
+        );
+        Self::new(
+            Rect::new(0, 0, constants::SCREEN_WIDTH as u32, constants::SCREEN_HEIGHT as u32),
+            world.stage.width * constants::CHARACTER_SIZE,
+            world.stage.height * constants::CHARACTER_SIZE,
+            (min_x + max_x + constants::CHARACTER_SIZE) / 2,
+            (min_y + max_y + constants::CHARACTER_SIZE) / 2,
+        )
+    }
+
+    /// 1方向の表示位置を決める
+    fn offset(map_size: f32, view_size: f32, center: f32) -> f32 {
+        if map_size <= view_size {
+            // 中央に置く
+            (map_size - view_size) / 2.0
+        } else {
+            (center - view_size / 2.0).clamp(0.0, map_size - view_size)
+        }
+    }
+
+    /// マップ上の矩形を画面上の矩形に変換する（隣り合うマスの間に隙間ができないように端を丸める）
+    pub fn to_screen(&self, x: i32, y: i32, width: u32, height: u32) -> Rect {
+        let left = ((x as f32 - self.offset_x) * self.scale).floor() as i32;
+        let top = ((y as f32 - self.offset_y) * self.scale).floor() as i32;
+        let right = (((x + width as i32) as f32 - self.offset_x) * self.scale).floor() as i32;
+        let bottom = (((y + height as i32) as f32 - self.offset_y) * self.scale).floor() as i32;
+        Rect::new(self.view.x() + left, self.view.y() + top, (right - left).max(1) as u32, (bottom - top).max(1) as u32)
+    }
+
+    /// 画面上の座標をマップ上の座標に変換する（表示範囲の外なら None）
+    pub fn to_map(&self, x: i32, y: i32) -> Option<(i32, i32)> {
+        if !self.view.contains_point((x, y)) {
+            return None;
+        }
+        let map_x = (x - self.view.x()) as f32 / self.scale + self.offset_x;
+        let map_y = (y - self.view.y()) as f32 / self.scale + self.offset_y;
+        Some((map_x.floor() as i32, map_y.floor() as i32))
+    }
+}
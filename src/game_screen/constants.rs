@@ -1,4 +0,0 @@
-pub const CHARACTER_SIZE: i32 = 32;
-pub const SCREEN_WIDTH: i32 = 800;
-pub const SCREEN_HEIGHT: i32 = 480;
-pub const BOMB_TIME: i32 = 5 * 60;
\ No newline at end of file
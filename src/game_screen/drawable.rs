@@ -0,0 +1,8 @@
+use sdl2::{render::{Canvas, Texture}, video::Window};
+
+use super::camera::Camera;
+
+/// ゲームのオブジェクトを画面に描画する
+pub trait Drawable {
+    fn draw(&self, texture: &mut Texture, canvas: &mut Canvas<Window>, camera: &Camera);
+}
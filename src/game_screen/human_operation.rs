@@ -1,8 +1,9 @@
 use sdl2::keyboard::{Scancode, KeyboardState};
 
-use crate::ai::ai_player::AIPlayerAdditionalInfo;
+use rust_bomber::ai::strategy::GameView;
+use rust_bomber::game::player_input::{PlayerInput, Movement};
+use rust_bomber::game::world::MAX_PLAYER_COUNT;
 use super::player_operation::PlayerOperation;
-use super::player_input::{PlayerInput, Movement};
 
 struct KeyAssignment {
     left: Scancode,
@@ -10,11 +11,14 @@ struct KeyAssignment {
     right: Scancode,
     down: Scancode,
     fire: Scancode,
+    detonate: Scancode,
 }
 
-const KEY_ASSIGNMENTS: [KeyAssignment; 2] = [
-    KeyAssignment { left: Scancode::A, up: Scancode::W, right: Scancode::D, down: Scancode::S, fire: Scancode::Num1 },
-    KeyAssignment { left: Scancode::Left, up: Scancode::Up, right: Scancode::Right, down: Scancode::Down, fire: Scancode::Slash },
+const KEY_ASSIGNMENTS: [KeyAssignment; MAX_PLAYER_COUNT] = [
+    KeyAssignment { left: Scancode::A, up: Scancode::W, right: Scancode::D, down: Scancode::S, fire: Scancode::Num1, detonate: Scancode::Num2 },
+    KeyAssignment { left: Scancode::Left, up: Scancode::Up, right: Scancode::Right, down: Scancode::Down, fire: Scancode::Slash, detonate: Scancode::Period },
+    KeyAssignment { left: Scancode::J, up: Scancode::I, right: Scancode::L, down: Scancode::K, fire: Scancode::U, detonate: Scancode::O },
+    KeyAssignment { left: Scancode::Kp4, up: Scancode::Kp8, right: Scancode::Kp6, down: Scancode::Kp5, fire: Scancode::Kp0, detonate: Scancode::KpPeriod },
 ];
 
 pub struct HumanOperation {
@@ -22,7 +26,7 @@ pub struct HumanOperation {
 }
 
 impl PlayerOperation for HumanOperation {
-    fn get_player_input(&mut self, keyboard_state: &KeyboardState, _: Option<AIPlayerAdditionalInfo>) -> PlayerInput {
+    fn get_player_input(&mut self, keyboard_state: &KeyboardState, _: &GameView) -> PlayerInput {
         let movement = if keyboard_state.is_scancode_pressed(KEY_ASSIGNMENTS[self.player_number].left) {
             Movement::LEFT
         } else if keyboard_state.is_scancode_pressed(KEY_ASSIGNMENTS[self.player_number].up) {
@@ -35,6 +39,7 @@ impl PlayerOperation for HumanOperation {
             Movement::NONE
         };
         let fire = keyboard_state.is_scancode_pressed(KEY_ASSIGNMENTS[self.player_number].fire);
-        PlayerInput { movement, fire }
+        let detonate = keyboard_state.is_scancode_pressed(KEY_ASSIGNMENTS[self.player_number].detonate);
+        PlayerInput { movement, fire, detonate }
     }
 }
\ No newline at end of file
@@ -1,8 +1,13 @@
 use sdl2::keyboard::KeyboardState;
 
-use super::player_input::PlayerInput;
-use crate::ai::ai_player::AIPlayerAdditionalInfo;
+use rust_bomber::ai::strategy::GameView;
+use rust_bomber::game::player_input::PlayerInput;
 
 pub trait PlayerOperation {
-    fn get_player_input(&mut self, keyboard_state: &KeyboardState, ai_additional_info: Option<AIPlayerAdditionalInfo>) -> PlayerInput;
-}
\ No newline at end of file
+    /// 入力を決める準備ができているか（false の間、ゲームは進まずに待つ）
+    fn is_ready(&mut self, _view: &GameView) -> bool {
+        true
+    }
+
+    fn get_player_input(&mut self, keyboard_state: &KeyboardState, view: &GameView) -> PlayerInput;
+}
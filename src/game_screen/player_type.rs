@@ -1,25 +1,107 @@
-use crate::ai::ai_player::{AIPlayer, AIPlayerAdditionalInfo};
-use super::screen::GameScreen;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rust_bomber::ai::ai_profile::AIProfile;
+use rust_bomber::ai::bot_server::BotServer;
+use rust_bomber::ai::difficulty::Difficulty;
+use rust_bomber::ai::external_player::{self, ExternalPlayer};
+use rust_bomber::ai::neural_network::NeuralNetwork;
+use rust_bomber::ai::neural_player::NeuralPlayer;
+use rust_bomber::ai::strategy::{AIStrategy, StrategyKind};
 use super::player_operation::PlayerOperation;
 use super::human_operation::HumanOperation;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub enum PlayerType {
-    HUMAN, AI
+    HUMAN, AI(StrategyKind, Difficulty),
+    // 別のプロセスで動くボット（起動するコマンドは PlayerSlotSettings で指定する）
+    EXTERNAL,
+    // TCP で接続してきたボット（PlayerSlotSettings の bot_server で待ち受ける）
+    REMOTE,
+    // 学習済みのニューラルネットワーク（PlayerSlotSettings の neural_network を使う）
+    NEURAL,
+}
+
+/// プレイヤー枠ごとの操作の設定
+#[derive(Clone)]
+pub struct PlayerSlotSettings {
+    // AI の性格
+    pub ai_profile: AIProfile,
+    // 外部のボットとして起動するコマンド
+    pub bot_command: Option<String>,
+    // 外部のボットの行動を待つ時間（間に合わなければそのフレームは何もしない）
+    pub bot_timeout: Duration,
+    // TCP で接続してくるボットを待ち受けるサーバー（すべてのプレイヤー枠で共通）
+    pub bot_server: Option<BotServer>,
+    // ニューラルネットワークの AI が使う重み
+    pub neural_network: Option<Arc<NeuralNetwork>>,
+}
+
+impl Default for PlayerSlotSettings {
+    fn default() -> Self {
+        PlayerSlotSettings {
+            ai_profile: AIProfile::default(),
+            bot_command: None,
+            bot_timeout: Duration::from_millis(external_player::DEFAULT_TIMEOUT_MILLIS),
+            bot_server: None,
+            neural_network: None,
+        }
+    }
 }
 
 impl PlayerType {
-    pub fn get_player_operation(&self, player_number: usize) -> Box<dyn PlayerOperation> {
+    /// リプレイファイルなどに記録する名前
+    pub fn name(&self) -> &'static str {
         match self {
-            Self::HUMAN => Box::new(HumanOperation { player_number }),
-            Self::AI => Box::new(AIPlayer::new()),
+            Self::HUMAN => "HUMAN",
+            Self::AI(StrategyKind::Standard, _) => "AI",
+            Self::AI(StrategyKind::Passive, _) => "AI_PASSIVE",
+            Self::AI(StrategyKind::Hunter, _) => "AI_HUNTER",
+            Self::AI(StrategyKind::RandomWalk, _) => "AI_RANDOM_WALK",
+            Self::AI(StrategyKind::MonteCarlo, _) => "AI_MONTE_CARLO",
+            Self::EXTERNAL => "EXTERNAL",
+            Self::REMOTE => "REMOTE",
+            Self::NEURAL => "NEURAL",
         }
     }
 
-    pub fn get_ai_additional_info(&self, game_screen: &GameScreen, player_number: usize) -> Option<AIPlayerAdditionalInfo> {
+    /// コマンドラインで指定する名前（human、remote、ai または AI の戦略の名前）から作る（外部のボットは --bot、ニューラルネットワークは --nn-policy で指定する）
+    pub fn from_name(name: &str, difficulty: Difficulty) -> Option<PlayerType> {
+        match name {
+            "human" => Some(Self::HUMAN),
+            "remote" => Some(Self::REMOTE),
+            "ai" => Some(Self::AI(StrategyKind::Standard, difficulty)),
+            _ => StrategyKind::from_name(name).map(|strategy_kind| Self::AI(strategy_kind, difficulty)),
+        }
+    }
+
+    /// AI なら強さを変えたものを返す
+    pub fn with_difficulty(&self, difficulty: Difficulty) -> PlayerType {
+        match self {
+            Self::AI(strategy_kind, _) => Self::AI(*strategy_kind, difficulty),
+            _ => *self,
+        }
+    }
+
+    pub fn get_player_operation(&self, player_number: usize, settings: &PlayerSlotSettings) -> Box<dyn PlayerOperation> {
         match self {
-            Self::HUMAN => None,
-            Self::AI => Some(AIPlayerAdditionalInfo::new(game_screen, player_number)),
+            Self::HUMAN => Box::new(HumanOperation { player_number }),
+            Self::AI(strategy_kind, difficulty) => Box::new(strategy_kind.create(*difficulty, &settings.ai_profile)),
+            Self::EXTERNAL => {
+                let command = settings.bot_command.as_deref().unwrap_or_default();
+                let strategy: Box<dyn AIStrategy> = Box::new(ExternalPlayer::new(command, settings.bot_timeout));
+                Box::new(strategy)
+            }
+            Self::REMOTE => {
+                let bot_server = settings.bot_server.as_ref().expect("REMOTE player requires a bot server");
+                let strategy: Box<dyn AIStrategy> = Box::new(bot_server.remote_player(player_number));
+                Box::new(strategy)
+            }
+            Self::NEURAL => {
+                let network = settings.neural_network.clone().expect("NEURAL player requires a neural network");
+                let strategy: Box<dyn AIStrategy> = Box::new(NeuralPlayer::new(network));
+                Box::new(strategy)
+            }
         }
     }
-}
\ No newline at end of file
+}
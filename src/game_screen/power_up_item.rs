@@ -1,42 +1,36 @@
 use sdl2::{video::Window, render::{Canvas, Texture}, rect::Rect};
 
-use super::{light_sprite::LightSprite, light_sprite::LightSpriteEvent};
+use rust_bomber::game::{light_sprite::LightSprite, power_up_item::{PowerUpItem, PowerUpKind}};
+use super::camera::Camera;
+use super::drawable::Drawable;
 
-pub struct PowerUpItem {
-    x: i32,
-    y: i32,
-    move_time: f32,
-}
-
-impl PowerUpItem {
-    pub fn new(x: i32, y: i32) -> PowerUpItem {
-        PowerUpItem {
-            x,
-            y,
-            move_time: 0f32,
-        }
+// アイテムの種類ごとの画像の行
+// （0: たき火、1: たいまつ、2: ろうそく、3: ランタン、4: 青いたき火、5: 緑のランタン、6: 紫のたいまつ、7: 水色のろうそく）
+pub fn image_row(kind: PowerUpKind) -> i32 {
+    match kind {
+        PowerUpKind::FireUp => 0,
+        PowerUpKind::SpeedUp => 1,
+        PowerUpKind::PowerDown => 2,
+        PowerUpKind::BombUp => 3,
+        PowerUpKind::FullFire => 4,
+        PowerUpKind::Kick => 5,
+        PowerUpKind::Remote => 6,
+        PowerUpKind::Pierce => 7,
     }
 }
 
-impl LightSprite for PowerUpItem {
-    fn get_x(&self) -> i32 { self.x }
-    fn get_y(&self) -> i32 { self.y }
-
-    fn draw(&self, texture: &mut Texture, canvas: &mut Canvas<Window>) {
+impl Drawable for PowerUpItem {
+    fn draw(&self, texture: &mut Texture, canvas: &mut Canvas<Window>, camera: &Camera) {
         let step = (self.move_time / 0.2f32) as i32 % 3;
         let width = self.get_width();
         let height = self.get_height();
         let src_x = step * width as i32;
+        let src_y = image_row(self.kind) * height as i32;
 
         canvas.copy(
             texture,
-            Some(Rect::new(src_x, 0, width, height)),
-            Some(Rect::new(self.x, self.y, width, height)),
+            Some(Rect::new(src_x, src_y, width, height)),
+            Some(camera.to_screen(self.get_x(), self.get_y(), width, height)),
         ).expect("Failure to draw canvas");
     }
-
-    fn on_next_frame(&mut self) -> LightSpriteEvent {
-        self.move_time += 1.0 / 60.0;
-        LightSpriteEvent::None
-    }
-}
\ No newline at end of file
+}
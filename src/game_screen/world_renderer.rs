@@ -0,0 +1,142 @@
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture, TextureCreator, TextureQuery};
+use sdl2::ttf::{Sdl2TtfContext, Font};
+use sdl2::video::{Window, WindowContext};
+use sdl2::pixels::Color;
+use sdl2::image::LoadTexture;
+use std::path::Path;
+
+use rust_bomber::game::constants::{CHARACTER_SIZE, SCREEN_WIDTH, SCREEN_HEIGHT};
+use rust_bomber::game::world::{State, World, MAX_PLAYER_COUNT};
+
+use super::camera::Camera;
+use super::drawable::Drawable;
+
+pub enum Alignment {
+    Left, Center, Right
+}
+
+// プレイヤーごとのキャラクター画像
+const PLAYER_IMAGE_FILES: [&str; MAX_PLAYER_COUNT] = [
+    "res/image/pipo-charachip018b.png",
+    "res/image/pipo-charachip018a.png",
+    "res/image/pipo-charachip018c.png",
+    "res/image/pipo-charachip018d.png",
+];
+
+// プレイヤーごとの文字色
+pub const PLAYER_COLORS: [Color; MAX_PLAYER_COUNT] = [
+    Color::RGB(178, 0, 0),
+    Color::RGB(0, 0, 255),
+    Color::RGB(0, 160, 0),
+    Color::RGB(200, 180, 0),
+];
+
+/// World の状態を画面に描画する（GameScreen と ReplayScreen で共用する）
+pub struct WorldRenderer<'a> {
+    // テクスチャ
+    pub wall_image: Texture<'a>,
+    pub breakable_wall_image: Texture<'a>,
+    pub bomb_image: Texture<'a>,
+    pub player_images: Vec<Texture<'a>>,
+    pub explosion_image: Texture<'a>,
+    pub power_up_item_image: Texture<'a>,
+
+    // フォント
+    pub font16: Font<'a, 'a>,
+    pub font32: Font<'a, 'a>,
+
+    // テクスチャ生成器
+    texture_creator: &'a TextureCreator<WindowContext>,
+}
+
+impl WorldRenderer<'_> {
+    pub fn new<'a>(texture_creator: &'a TextureCreator<WindowContext>, ttf_context: &'a Sdl2TtfContext) -> WorldRenderer<'a> {
+        WorldRenderer {
+            wall_image: texture_creator.load_texture(Path::new("res/image/wall.png")).unwrap(),
+            breakable_wall_image: texture_creator.load_texture(Path::new("res/image/breakable_wall.png")).unwrap(),
+            bomb_image: texture_creator.load_texture(Path::new("res/image/pipo-simpleenemy01b.png")).unwrap(),
+            player_images: PLAYER_IMAGE_FILES.iter().map(|file| texture_creator.load_texture(Path::new(file)).unwrap()).collect(),
+            explosion_image: texture_creator.load_texture(Path::new("res/image/explosion.png")).unwrap(),
+            power_up_item_image: texture_creator.load_texture(Path::new("res/image/pipo-etcchara003.png")).unwrap(),
+            font16: ttf_context.load_font(Path::new("res/font/m12.ttf"), 16).unwrap(),
+            font32: ttf_context.load_font(Path::new("res/font/m12.ttf"), 32).unwrap(),
+            texture_creator,
+        }
+    }
+
+    /// ゲーム画面を描画する（canvas.present() は呼び出し側で行う）
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>, world: &World) {
+        // マップが画面より小さい場合は周りを黒く塗る
+        let camera = Camera::for_world(world);
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+        canvas.set_draw_color(Color::RGB(0, 178, 0));
+        let map_rect = camera.to_screen(0, 0, (world.stage.width * CHARACTER_SIZE) as u32, (world.stage.height * CHARACTER_SIZE) as u32);
+        if let Err(error) = canvas.fill_rect(map_rect) {
+            println!("Failure to draw map: {}", error);
+        }
+
+        // 各種オブジェクトの描画
+        for sprite in &world.walls { sprite.draw( if sprite.is_breakable { &mut self.breakable_wall_image } else { &mut self.wall_image } , canvas, &camera); }
+        for sprite in &world.bombs { sprite.draw(&mut self.bomb_image, canvas, &camera); }
+        for sprite in &world.power_up_items { sprite.draw(&mut self.power_up_item_image, canvas, &camera); }
+        for sprite in &world.explosions { sprite.draw(&mut self.explosion_image, canvas, &camera); }
+        for sprite in &world.players { sprite.draw(&mut self.player_images[sprite.player_number], canvas, &camera); }
+        // ゲーム終了時の描画
+        match world.state {
+            State::Playing => (),
+            State::Won(player_number) => {
+                self.draw_text(canvas, Rect::new(0, 0, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32), &format!("PLAYER {} WIN", player_number + 1), PLAYER_COLORS[player_number], &self.font32, Alignment::Center);
+            }
+            State::DrawGame => {
+                self.draw_text(canvas, Rect::new(0, 0, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32), "DRAW GAME", Color::RGB(255, 255, 255), &self.font32, Alignment::Center);
+            }
+        }
+        // 画面の上下左右の端に表示する各プレイヤーの状態描画
+        // （1P: 左上、2P: 右上、3P: 左下、4P: 右下）
+        for player in &world.players {
+            let n = player.player_number;
+            let target = Rect::new(0, if n < 2 { 0 } else { SCREEN_HEIGHT - 16 }, SCREEN_WIDTH as u32, 16);
+            let alignment = if n % 2 == 0 { Alignment::Left } else { Alignment::Right };
+            let mut status = if world.rule.unlimited_bombs {
+                format!("PLAYER {} POWER {} SPEED {}", n + 1, player.power, player.speed_level())
+            } else {
+                format!("PLAYER {} POWER {} BOMB {} SPEED {}", n + 1, player.power, player.bomb_capacity, player.speed_level())
+            };
+            if player.can_kick && !world.rule.everyone_can_kick {
+                status.push_str(" KICK");
+            }
+            if player.has_remote {
+                status.push_str(" REMOTE");
+            }
+            if player.has_pierce {
+                status.push_str(" PIERCE");
+            }
+            self.draw_text(canvas, target, &status, PLAYER_COLORS[n], &self.font16, alignment);
+        }
+        // 画面下部中央に乱数のシードを表示する
+        self.draw_text(canvas, Rect::new(0, SCREEN_HEIGHT - 16, SCREEN_WIDTH as u32, 16), &format!("SEED {}", world.seed), Color::RGB(255, 255, 255), &self.font16, Alignment::Center);
+    }
+
+    pub fn draw_text(&self, canvas: &mut Canvas<Window>, target: Rect, text: &str, color: Color, font: &Font, alignment: Alignment) {
+        let surface = font
+            .render(text)
+            .blended(color)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let texture = self.texture_creator
+            .create_texture_from_surface(&surface)
+            .map_err(|e| e.to_string())
+            .unwrap();
+        let TextureQuery { width, height, .. } = texture.query();
+        let dst_rect = match alignment {
+            Alignment::Left   => Some(Rect::new(target.x, target.y, width, height)),
+            Alignment::Center => Some(Rect::new(target.x + (target.w - width as i32) / 2, target.y + (target.h - height as i32) / 2, width, height)),
+            Alignment::Right  => Some(Rect::new(target.right() - width as i32, target.y, width, height)),
+        };
+        if let Err(error) = canvas.copy(&texture, None, dst_rect) {
+            println!("Failure to draw text: {}", error);
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod action;
+pub mod environment;
+pub mod observation;
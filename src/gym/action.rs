@@ -0,0 +1,15 @@
+use crate::game::player_input::{PlayerInput, Movement};
+
+// 行動の数（Movement の5通り × 爆弾を置くかどうか）
+pub const ACTION_COUNT: usize = Movement::ALL.len() * 2;
+
+/// 行動の番号を入力にする（番号は Movement::ALL の順に、爆弾を置かないもの、置くものの順に並ぶ）
+pub fn action_to_input(action: usize) -> PlayerInput {
+    assert!(action < ACTION_COUNT, "invalid action: {}", action);
+    PlayerInput::new(Movement::ALL[action / 2], action % 2 == 1)
+}
+
+/// 入力を行動の番号にする（AI の入力をお手本として学習するときに使う。リモコン爆弾の起爆は含まない）
+pub fn input_to_action(player_input: &PlayerInput) -> usize {
+    player_input.movement as usize * 2 + player_input.fire as usize
+}
@@ -0,0 +1,295 @@
+use crate::ai::{
+    ai_profile::AIProfile,
+    difficulty::Difficulty,
+    field::Field,
+    strategy::{AIStrategy, GameView, StrategyKind},
+};
+use crate::arena::{
+    entrant::Entrant,
+    match_runner::MatchSettings,
+};
+use crate::game::{
+    player_input::{PlayerInput, Movement},
+    power_up_item::PowerUpKind,
+    world::{State, World, MIN_PLAYER_COUNT, MAX_PLAYER_COUNT},
+};
+
+use super::{
+    action::{self, ACTION_COUNT},
+    observation::{Observation, CHANNEL_COUNT},
+};
+
+// 勝ったときと死んだときの報酬
+pub const WIN_REWARD: f32 = 1.0;
+pub const DEATH_REWARD: f32 = -1.0;
+
+/// プレイヤーを誰が操作するか
+#[derive(Clone)]
+pub enum Controller {
+    // step に渡す行動で操作する
+    Agent,
+    // 組み込みの AI が操作する
+    AI(Entrant),
+}
+
+/// 強化学習の環境の設定
+#[derive(Clone)]
+pub struct EnvironmentConfig {
+    // ルール、ステージ、時間切れまでのフレーム数
+    pub settings: MatchSettings,
+    // プレイヤー番号の順に、各プレイヤーを誰が操作するか
+    pub controllers: Vec<Controller>,
+    // 1回の step で進めるフレーム数（同じ移動を続け、爆弾は最初のフレームでだけ置く）
+    pub frame_skip: u32,
+}
+
+impl Default for EnvironmentConfig {
+    /// エージェント1人と標準の AI の1対1
+    fn default() -> Self {
+        let mut settings = MatchSettings::default();
+        // 行動にリモコン爆弾の起爆がないので、リモコンアイテムは出さない
+        settings.rule.item_weights[PowerUpKind::Remote as usize] = 0;
+        EnvironmentConfig {
+            settings,
+            controllers: vec![
+                Controller::Agent,
+                Controller::AI(Entrant::new(StrategyKind::Standard, Difficulty::Expert, AIProfile::default())),
+            ],
+            frame_skip: 1,
+        }
+    }
+}
+
+/// step の結果の補足情報
+#[derive(Clone, Debug)]
+pub struct StepInfo {
+    // 試合開始からのフレーム数
+    pub frame: i32,
+    // 勝ったプレイヤーの番号（決着がついていない、引き分け、時間切れの場合は None）
+    pub winner: Option<usize>,
+    pub timed_out: bool,
+    // プレイヤー番号の順に、生きているか
+    pub alive: Vec<bool>,
+}
+
+/// ゲームのルールを描画なしで1ステップずつ進める強化学習用の環境
+///
+/// 観測、報酬、行動はエージェント（Controller::Agent のプレイヤー）ごとに、プレイヤー番号の順に並べる。
+pub struct Environment {
+    config: EnvironmentConfig,
+    world: World,
+    // プレイヤー番号の順に、AI が操作する場合はその AI
+    ai_players: Vec<Option<Box<dyn AIStrategy>>>,
+    // エージェントのプレイヤー番号
+    agents: Vec<usize>,
+    frame: i32,
+}
+
+impl Environment {
+    pub fn new(config: EnvironmentConfig) -> Result<Environment, String> {
+        let player_count = config.controllers.len();
+        if !(MIN_PLAYER_COUNT..=MAX_PLAYER_COUNT).contains(&player_count) {
+            return Err(format!("Player count must be {} to {} (got {})", MIN_PLAYER_COUNT, MAX_PLAYER_COUNT, player_count));
+        }
+        if config.settings.stage.spawn_positions.len() < player_count {
+            return Err(format!("Stage {} has only {} spawn positions", config.settings.stage.name, config.settings.stage.spawn_positions.len()));
+        }
+        if config.frame_skip == 0 {
+            return Err("frame_skip must be 1 or more".to_string());
+        }
+        let agents: Vec<usize> = config.controllers.iter().enumerate()
+            .filter(|(_, controller)| matches!(controller, Controller::Agent))
+            .map(|(i, _)| i)
+            .collect();
+        if agents.is_empty() {
+            return Err("At least one player must be controlled by an agent".to_string());
+        }
+        let world = World::new(0, player_count, config.settings.rule, config.settings.stage.clone());
+        let mut environment = Environment {
+            config,
+            world,
+            ai_players: Vec::new(),
+            agents,
+            frame: 0,
+        };
+        environment.reset_players();
+        Ok(environment)
+    }
+
+    /// エージェントの数
+    pub fn agent_count(&self) -> usize {
+        self.agents.len()
+    }
+
+    /// 行動の数
+    pub fn action_count(&self) -> usize {
+        ACTION_COUNT
+    }
+
+    /// Observation::grid の形（チャンネル数、高さ、幅）
+    pub fn observation_shape(&self) -> (usize, usize, usize) {
+        (CHANNEL_COUNT, self.world.stage.height as usize, self.world.stage.width as usize)
+    }
+
+    /// 進めているゲームの状態
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// 指定したシードで試合をやり直し、各エージェントの観測を返す
+    pub fn reset(&mut self, seed: u64) -> Vec<Observation> {
+        self.world.start_game(seed);
+        self.reset_players();
+        self.observe()
+    }
+
+    /// 作ったマップで試合を始められるように、AI と経過フレーム数を初期化する
+    fn reset_players(&mut self) {
+        self.ai_players = self.config.controllers.iter().map(|controller| match controller {
+            Controller::Agent => None,
+            Controller::AI(entrant) => Some(entrant.create_strategy()),
+        }).collect();
+        self.frame = 0;
+    }
+
+    /// 各エージェントの行動（0〜ACTION_COUNT-1）で試合を進め、観測、報酬、試合が終わったか、補足情報を返す
+    pub fn step(&mut self, actions: &[usize]) -> (Vec<Observation>, Vec<f32>, bool, StepInfo) {
+        assert_eq!(actions.len(), self.agents.len(), "actions must be given for each agent");
+        let mut rewards = vec![0.0; self.agents.len()];
+        let was_alive: Vec<bool> = self.agents.iter().map(|i| !self.world.players[*i].is_dead()).collect();
+
+        for skip in 0..self.config.frame_skip {
+            if self.is_done() {
+                break;
+            }
+            let mut player_inputs = vec![PlayerInput::new(Movement::NONE, false); self.world.players.len()];
+            for (agent, action) in self.agents.iter().zip(actions) {
+                let mut player_input = action::action_to_input(*action);
+                player_input.fire &= skip == 0;
+                player_inputs[*agent] = player_input;
+            }
+            for (i, ai_player) in self.ai_players.iter_mut().enumerate() {
+                if let Some(ai_player) = ai_player {
+                    if !self.world.players[i].is_dead() {
+                        player_inputs[i] = ai_player.get_player_input(&GameView::new(&self.world, i));
+                    }
+                }
+            }
+            self.world.on_next_frame(&player_inputs);
+            self.frame += 1;
+        }
+
+        for (reward, (agent, was_alive)) in rewards.iter_mut().zip(self.agents.iter().zip(was_alive)) {
+            if was_alive && self.world.players[*agent].is_dead() {
+                *reward += DEATH_REWARD;
+            }
+            if let State::Won(winner) = self.world.state {
+                if winner == *agent && was_alive {
+                    *reward += WIN_REWARD;
+                }
+            }
+        }
+
+        let info = StepInfo {
+            frame: self.frame,
+            winner: match self.world.state {
+                State::Won(winner) => Some(winner),
+                _ => None,
+            },
+            timed_out: matches!(self.world.state, State::Playing) && self.frame >= self.config.settings.max_frames,
+            alive: self.world.players.iter().map(|player| !player.is_dead()).collect(),
+        };
+        (self.observe(), rewards, self.is_done(), info)
+    }
+
+    /// 試合が終わったか（決着がついたか、時間切れ、またはエージェントが全員死んだ）
+    fn is_done(&self) -> bool {
+        !matches!(self.world.state, State::Playing) ||
+            self.frame >= self.config.settings.max_frames ||
+            self.agents.iter().all(|i| self.world.players[*i].is_dead())
+    }
+
+    fn observe(&self) -> Vec<Observation> {
+        let field = Field::from_world(&self.world, &AIProfile::default());
+        self.agents.iter().map(|i| Observation::new(&self.world, &field, *i)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{stage::Stage, test_support::TEST_STAGE};
+
+    // 何もしない行動と、その場に爆弾を置く行動
+    const STAY: usize = 0;
+    const FIRE: usize = 1;
+
+    /// TEST_STAGE でエージェント同士を対戦させる環境
+    fn agents_only(max_frames: i32, frame_skip: u32) -> Environment {
+        let mut config = EnvironmentConfig::default();
+        config.settings.stage = Stage::parse(TEST_STAGE).unwrap();
+        config.settings.max_frames = max_frames;
+        config.controllers = vec![Controller::Agent, Controller::Agent];
+        config.frame_skip = frame_skip;
+        Environment::new(config).unwrap()
+    }
+
+    #[test]
+    fn death_and_win_are_rewarded_once() {
+        let mut environment = agents_only(1000, 1);
+        let observations = environment.reset(0);
+        assert_eq!(observations.len(), 2);
+
+        let (_, rewards, done, _) = environment.step(&[FIRE, STAY]);
+        assert_eq!(rewards, vec![0.0, 0.0]);
+        assert!(!done);
+        // 1P は自分の爆弾で死に、2P の勝ちになる
+        let mut total = vec![0.0, 0.0];
+        let mut info = None;
+        for _ in 0..1000 {
+            let (_, rewards, done, step_info) = environment.step(&[STAY, STAY]);
+            total[0] += rewards[0];
+            total[1] += rewards[1];
+            if done {
+                info = Some(step_info);
+                break;
+            }
+        }
+        let info = info.expect("the match did not end");
+        assert_eq!(total, vec![DEATH_REWARD, WIN_REWARD]);
+        assert_eq!(info.winner, Some(1));
+        assert!(!info.timed_out);
+        assert_eq!(info.alive, vec![false, true]);
+    }
+
+    #[test]
+    fn match_times_out_after_max_frames() {
+        let mut environment = agents_only(6, 4);
+        environment.reset(0);
+
+        let (_, rewards, done, info) = environment.step(&[STAY, STAY]);
+        assert_eq!(rewards, vec![0.0, 0.0]);
+        assert!(!done);
+        assert_eq!(info.frame, 4);
+        assert!(!info.timed_out);
+
+        // 時間切れのフレームで止まり、それ以上は進まない
+        let (_, rewards, done, info) = environment.step(&[STAY, STAY]);
+        assert_eq!(rewards, vec![0.0, 0.0]);
+        assert!(done);
+        assert_eq!((info.frame, info.winner, info.timed_out), (6, None, true));
+        assert_eq!(environment.step(&[STAY, STAY]).3.frame, 6);
+
+        environment.reset(1);
+        assert_eq!(environment.step(&[STAY, STAY]).3.frame, 4);
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        let ai = Controller::AI(Entrant::new(StrategyKind::RandomWalk, Difficulty::Expert, AIProfile::default()));
+        assert!(Environment::new(EnvironmentConfig { controllers: vec![ai.clone(), ai], ..EnvironmentConfig::default() }).is_err());
+        assert!(Environment::new(EnvironmentConfig { frame_skip: 0, ..EnvironmentConfig::default() }).is_err());
+        let config = EnvironmentConfig { controllers: vec![Controller::Agent], ..EnvironmentConfig::default() };
+        assert!(Environment::new(config).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::ai::{
+    field::Field,
+    strategy::GameView,
+};
+use crate::game::{
+    constants,
+    light_sprite::LightSprite,
+    player::{Player, MAX_POWER, MAX_SPEED},
+    power_up_item::PowerUpKind,
+    world::{World, MAX_PLAYER_COUNT},
+};
+
+// マスごとの値の種類（チャンネル）
+pub const CHANNEL_HARD_WALL: usize = 0;
+pub const CHANNEL_SOFT_WALL: usize = 1;
+// 爆弾が爆発するまでの残り時間の割合（置いた直後が 1.0）
+pub const CHANNEL_BOMB_TIMER: usize = 2;
+// AI が使うリスク値（爆発しているマスが 1.0、爆弾の炎が届くマスは爆発が近いほど大きい）
+pub const CHANNEL_BLAST_RISK: usize = 3;
+// アイテム（パワーダウンは -1.0、それ以外は 1.0）
+pub const CHANNEL_ITEM: usize = 4;
+// 生きているプレイヤーのいるマス（観測するプレイヤー自身、対戦相手をプレイヤー番号の順に並べる）
+pub const CHANNEL_PLAYERS: usize = 5;
+pub const CHANNEL_COUNT: usize = CHANNEL_PLAYERS + MAX_PLAYER_COUNT;
+
+// プレイヤーごとの値の数（生きているか、マスからのずれ x/y、爆発の範囲、爆弾の数、速度、キック、リモコン、ピアス）
+pub const PLAYER_FEATURE_COUNT: usize = 9;
+pub const FEATURE_COUNT: usize = PLAYER_FEATURE_COUNT * MAX_PLAYER_COUNT;
+
+// 爆弾の数を 0〜1 にするときの上限
+const BOMB_CAPACITY_SCALE: f32 = 10.0;
+
+/// 1人のプレイヤーから見たゲームの状態（大きさの決まった数値の配列）
+#[derive(Clone)]
+pub struct Observation {
+    pub width: usize,
+    pub height: usize,
+    // [チャンネル][y][x] の順に並べたマスごとの値（CHANNEL_COUNT × height × width）
+    pub grid: Vec<f32>,
+    // プレイヤーごとの値（観測するプレイヤー自身、対戦相手の順、参加していないプレイヤーは 0）
+    pub features: Vec<f32>,
+}
+
+impl Observation {
+    /// field は Field::from_world で作ったもの（全員の観測で共用する）
+    pub fn new(world: &World, field: &Field, player_number: usize) -> Observation {
+        let width = world.stage.width as usize;
+        let height = world.stage.height as usize;
+        let mut observation = Observation {
+            width,
+            height,
+            grid: vec![0.0; CHANNEL_COUNT * width * height],
+            features: vec![0.0; FEATURE_COUNT],
+        };
+        let tile = |x: i32| x / constants::CHARACTER_SIZE;
+
+        for wall in world.walls.iter() {
+            let channel = if wall.is_breakable { CHANNEL_SOFT_WALL } else { CHANNEL_HARD_WALL };
+            observation.set(channel, tile(wall.get_x()), tile(wall.get_y()), 1.0);
+        }
+        for bomb in world.bombs.iter() {
+            observation.set(CHANNEL_BOMB_TIMER, tile(bomb.get_tile_x()), tile(bomb.get_tile_y()), bomb.remain_time as f32 / constants::BOMB_TIME as f32);
+        }
+        for element in field.elements.iter() {
+            let risk = (element.risk as f32 / field.risk_of_explosion as f32).min(1.0);
+            observation.set(CHANNEL_BLAST_RISK, element.x, element.y, risk);
+        }
+        for item in world.power_up_items.iter() {
+            let value = if item.kind == PowerUpKind::PowerDown { -1.0 } else { 1.0 };
+            observation.set(CHANNEL_ITEM, tile(item.get_x()), tile(item.get_y()), value);
+        }
+
+        for (i, player) in Self::players_in_order(world, player_number).enumerate() {
+            let features = &mut observation.features[i * PLAYER_FEATURE_COUNT..(i + 1) * PLAYER_FEATURE_COUNT];
+            features.copy_from_slice(&[
+                if player.is_dead() { 0.0 } else { 1.0 },
+                (player.get_x() % constants::CHARACTER_SIZE) as f32 / constants::CHARACTER_SIZE as f32,
+                (player.get_y() % constants::CHARACTER_SIZE) as f32 / constants::CHARACTER_SIZE as f32,
+                player.power as f32 / MAX_POWER as f32,
+                (player.bomb_capacity as f32 / BOMB_CAPACITY_SCALE).min(1.0),
+                player.speed as f32 / MAX_SPEED as f32,
+                if player.can_kick { 1.0 } else { 0.0 },
+                if player.has_remote { 1.0 } else { 0.0 },
+                if player.has_pierce { 1.0 } else { 0.0 },
+            ]);
+            if !player.is_dead() {
+                let (x, y) = GameView::tile_of(player);
+                observation.set(CHANNEL_PLAYERS + i, x, y, 1.0);
+            }
+        }
+        observation
+    }
+
+    /// 観測するプレイヤー自身を先頭に、対戦相手をプレイヤー番号の順に並べる
+    fn players_in_order(world: &World, player_number: usize) -> impl Iterator<Item = &Player> {
+        let me = &world.players[player_number];
+        std::iter::once(me).chain(world.players.iter().filter(move |player| player.player_number != player_number))
+    }
+
+    fn set(&mut self, channel: usize, x: i32, y: i32, value: f32) {
+        if (0..self.width as i32).contains(&x) && (0..self.height as i32).contains(&y) {
+            let index = (channel * self.height + y as usize) * self.width + x as usize;
+            self.grid[index] = value;
+        }
+    }
+
+    pub fn get(&self, channel: usize, x: usize, y: usize) -> f32 {
+        self.grid[(channel * self.height + y) * self.width + x]
+    }
+}
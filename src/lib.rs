@@ -0,0 +1,6 @@
+#![allow(clippy::upper_case_acronyms)]
+
+pub mod ai;
+pub mod arena;
+pub mod game;
+pub mod gym;
@@ -1,3 +1,5 @@
+#![allow(clippy::upper_case_acronyms)]
+
 extern crate sdl2;
 
 use sdl2::event::{Event, WindowEvent};
@@ -5,24 +7,36 @@ use sdl2::mixer::{InitFlag, AUDIO_S16LSB, DEFAULT_CHANNELS};
 use title_screen::screen::TitleScreen;
 use chrono::Utc;
 
+mod command_line;
+mod editor_screen;
 mod screen;
 mod game_screen;
+mod replay_screen;
 mod title_screen;
-mod ai;
 
+use rust_bomber::ai::difficulty::Difficulty;
+use rust_bomber::ai::strategy::StrategyKind;
+use rust_bomber::game::constants::{SCREEN_WIDTH, SCREEN_HEIGHT};
+use rust_bomber::game::replay::Replay;
+use crate::command_line::CommandLineOptions;
+use crate::editor_screen::screen::EditorScreen;
 use crate::screen::{Screen, ScreenEvent};
+use crate::game_screen::player_type::PlayerType;
 use crate::game_screen::screen::GameScreen;
+use crate::replay_screen::screen::ReplayScreen;
 
 const FRAME_RATE: i64 = 60;
 const FRAME_TIME: i64 = 1_000_000_000 / FRAME_RATE;
 
 pub fn main() -> Result<(), String> {
+    let options = CommandLineOptions::parse()?;
+
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
     let ttf_context = sdl2::ttf::init().map_err(|e| e.to_string())?;
 
     let window = video_subsystem
-        .window("Bomber mates", 800, 480)
+        .window("Bomber mates", SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)
         .position_centered()
         .resizable()
         .opengl()
@@ -46,7 +60,13 @@ pub fn main() -> Result<(), String> {
 
     let mut event_pump = sdl_context.event_pump()?;
 
-    let mut screen: Box<dyn Screen> = Box::new(TitleScreen::new(&texture_creator, &ttf_context));
+    let mut screen: Box<dyn Screen> = Box::new(TitleScreen::new(&texture_creator, &ttf_context, options.rule, options.player_slots, options.bot_choices(), options.ai_difficulty));
+    if let Some(path) = &options.replay {
+        screen = Box::new(ReplayScreen::new(&texture_creator, &ttf_context, path)?);
+    }
+
+    // テストプレイ中に残しておく元の画面
+    let mut suspended_screen: Option<Box<dyn Screen>> = None;
 
     let mut frame_timing = Utc::now().timestamp_nanos();
 
@@ -54,12 +74,10 @@ pub fn main() -> Result<(), String> {
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. } => break 'running,
-                Event::Window { win_event, .. } => {
-                    if let WindowEvent::Resized(w, h) = win_event {
-                        // ウィンドウサイズが変更された
-                        if let Err(error) = canvas.set_scale(w as f32 / 800f32, h as f32 / 480f32) {
-                            println!("Failed to resize window: {}", error);
-                        }
+                Event::Window { win_event: WindowEvent::Resized(w, h), .. } => {
+                    // ウィンドウサイズが変更された
+                    if let Err(error) = canvas.set_scale(w as f32 / SCREEN_WIDTH as f32, h as f32 / SCREEN_HEIGHT as f32) {
+                        println!("Failed to resize window: {}", error);
                     }
                 }
                 _ => {}
@@ -70,11 +88,45 @@ pub fn main() -> Result<(), String> {
         screen.draw(&mut canvas);
         match screen.on_next_frame(&event_pump) {
             ScreenEvent::None => (),
-            ScreenEvent::GoToGameScreen(player_type1, player_type2) => {
-                screen = Box::new(GameScreen::new(&texture_creator, &ttf_context, player_type1, player_type2));
+            ScreenEvent::GoToGameScreen(player_types, rule, stage) => {
+                screen = Box::new(GameScreen::new(&texture_creator, &ttf_context, player_types, rule, stage, options.seed, options.slot_settings.clone()));
+            }
+            ScreenEvent::GoToReplayScreen(path) => {
+                match path.or_else(Replay::find_latest) {
+                    Some(path) => match ReplayScreen::new(&texture_creator, &ttf_context, &path) {
+                        Ok(replay_screen) => screen = Box::new(replay_screen),
+                        Err(error) => {
+                            println!("{}", error);
+                            screen = Box::new(TitleScreen::new(&texture_creator, &ttf_context, options.rule, options.player_slots, options.bot_choices(), options.ai_difficulty));
+                        }
+                    },
+                    None => {
+                        println!("No replay file found");
+                        screen = Box::new(TitleScreen::new(&texture_creator, &ttf_context, options.rule, options.player_slots, options.bot_choices(), options.ai_difficulty));
+                    }
+                }
+            }
+            ScreenEvent::GoToEditorScreen(stage_index, rule) => {
+                screen = Box::new(EditorScreen::new(&texture_creator, &ttf_context, stage_index, rule));
+            }
+            ScreenEvent::TestPlayStage(rule, stage) => {
+                // 1P を人間、残りを AI にして全員の出現位置を試す
+                let mut player_types = vec![PlayerType::AI(StrategyKind::Standard, Difficulty::Expert); stage.spawn_positions.len()];
+                player_types[0] = PlayerType::HUMAN;
+                let game_screen = GameScreen::new(&texture_creator, &ttf_context, player_types, rule, stage, options.seed, options.slot_settings.clone());
+                suspended_screen = Some(std::mem::replace(&mut screen, Box::new(game_screen)));
             }
             ScreenEvent::ReturnToTitleScreen => {
-                screen = Box::new(TitleScreen::new(&texture_creator, &ttf_context));
+                match suspended_screen.take() {
+                    Some(suspended) => {
+                        // テストプレイを終えて元の画面に戻る
+                        sdl2::mixer::Music::halt();
+                        sdl2::mixer::Channel::all().halt();
+                        screen = suspended;
+                        screen.on_resume();
+                    }
+                    None => screen = Box::new(TitleScreen::new(&texture_creator, &ttf_context, options.rule, options.player_slots, options.bot_choices(), options.ai_difficulty)),
+                }
             }
         }
 
@@ -0,0 +1 @@
+pub mod screen;
\ No newline at end of file
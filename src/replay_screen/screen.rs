@@ -0,0 +1,288 @@
+use std::path::Path;
+
+use sdl2::{video::{Window, WindowContext}, render::{Canvas, TextureCreator}, pixels::Color, rect::Rect, ttf::Sdl2TtfContext, EventPump, keyboard::{Scancode, KeyboardState}, mouse::MouseButton};
+
+use rust_bomber::game::constants::SCREEN_WIDTH;
+use rust_bomber::game::player_input::{PlayerInput, Movement};
+use rust_bomber::game::replay::Replay;
+use rust_bomber::game::world::World;
+use crate::game_screen::world_renderer::{Alignment, WorldRenderer};
+use crate::screen::{Screen, ScreenEvent};
+
+// 記録の最後のフレームの後、死亡演出などを見せるために進めるフレーム数
+const FRAMES_AFTER_END: usize = 90;
+
+// 左右キーでシークするフレーム数
+const SEEK_STEP: usize = 5 * 60;
+
+// 押した瞬間を検出するキー
+const CONTROL_KEYS: [Scancode; 22] = [
+    Scancode::Space, Scancode::Period, Scancode::Num1, Scancode::Num2, Scancode::Num4,
+    Scancode::Left, Scancode::Right, Scancode::Home, Scancode::End,
+    Scancode::G, Scancode::Return, Scancode::Backspace, Scancode::Escape,
+    Scancode::Num0, Scancode::Num3, Scancode::Num5, Scancode::Num6, Scancode::Num7, Scancode::Num8, Scancode::Num9,
+    Scancode::KpEnter, Scancode::Delete,
+];
+
+// フレーム番号の入力に使う数字キー（0〜9 の順）
+const DIGIT_KEYS: [Scancode; 10] = [
+    Scancode::Num0, Scancode::Num1, Scancode::Num2, Scancode::Num3, Scancode::Num4,
+    Scancode::Num5, Scancode::Num6, Scancode::Num7, Scancode::Num8, Scancode::Num9,
+];
+
+// 入力できるフレーム番号の桁数
+const MAX_FRAME_DIGITS: usize = 6;
+
+// クリックやドラッグでシークするタイムライン
+const TIMELINE_X: i32 = 40;
+const TIMELINE_Y: i32 = 20;
+const TIMELINE_WIDTH: u32 = SCREEN_WIDTH as u32 - 80;
+const TIMELINE_HEIGHT: u32 = 8;
+
+pub struct ReplayScreen<'a> {
+    // 再生中のリプレイ
+    replay: Replay,
+
+    // リプレイの入力を流し込むゲームのルール本体
+    world: World,
+
+    // 再生済みのフレーム数
+    frame: usize,
+
+    // 一時停止中なら true
+    paused: bool,
+
+    // 再生速度（1フレームあたりに進めるフレーム数）
+    speed: usize,
+
+    // 前フレームで押されていたキー
+    previous_keys: Vec<Scancode>,
+
+    // 移動先のフレーム番号を入力中なら、入力済みの数字
+    frame_input: Option<String>,
+
+    // タイムラインをドラッグ中なら true
+    dragging_timeline: bool,
+
+    // マウスの座標を画面の座標に直すための描画の拡大率
+    canvas_scale: (f32, f32),
+
+    // 描画
+    renderer: WorldRenderer<'a>,
+}
+
+impl ReplayScreen<'_> {
+    pub fn new<'a>(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        ttf_context: &'a Sdl2TtfContext,
+        path: &Path,
+    ) -> Result<ReplayScreen<'a>, String> {
+        let replay = Replay::load(path)?;
+        let world = World::new(replay.seed, replay.player_types.len(), replay.rule, replay.stage.clone());
+        sdl2::mixer::Music::halt();
+        Ok(ReplayScreen {
+            replay,
+            world,
+            frame: 0,
+            paused: false,
+            speed: 1,
+            previous_keys: Vec::new(),
+            frame_input: None,
+            dragging_timeline: false,
+            canvas_scale: (1.0, 1.0),
+            renderer: WorldRenderer::new(texture_creator, ttf_context),
+        })
+    }
+
+    fn total_frames(&self) -> usize {
+        self.replay.frames.len() + FRAMES_AFTER_END
+    }
+
+    /// 記録された入力で1フレーム進める
+    fn advance(&mut self) {
+        if self.frame >= self.total_frames() {
+            return;
+        }
+        let player_inputs = self.replay.frames.get(self.frame).cloned()
+            .unwrap_or_else(|| vec![PlayerInput::new(Movement::NONE, false); self.replay.player_types.len()]);
+        self.world.on_next_frame(&player_inputs);
+        self.frame += 1;
+    }
+
+    /// 指定フレームまで再生した状態にする（戻る場合は最初から再生し直す）
+    fn seek_to_frame(&mut self, frame: usize) {
+        let frame = frame.min(self.total_frames());
+        if frame < self.frame {
+            self.world.start_game(self.replay.seed);
+            self.frame = 0;
+        }
+        while self.frame < frame {
+            self.advance();
+        }
+    }
+
+    fn timeline_rect() -> Rect {
+        Rect::new(TIMELINE_X, TIMELINE_Y, TIMELINE_WIDTH, TIMELINE_HEIGHT)
+    }
+
+    /// タイムライン上の横位置に対応するフレーム
+    fn frame_at_timeline(&self, x: i32) -> usize {
+        let offset = (x - TIMELINE_X).max(0).min(TIMELINE_WIDTH as i32) as usize;
+        offset * self.total_frames() / TIMELINE_WIDTH as usize
+    }
+
+    /// フレーム番号の入力中のキー操作（Enter で移動、Esc で取り消し）
+    fn handle_frame_input(&mut self, keyboard_state: &KeyboardState) {
+        let just_pressed: Vec<Scancode> = CONTROL_KEYS.iter().copied()
+            .filter(|scancode| self.is_just_pressed(keyboard_state, *scancode))
+            .collect();
+        let input = match &mut self.frame_input {
+            Some(input) => input,
+            None => return,
+        };
+        for scancode in just_pressed {
+            if let Some(digit) = DIGIT_KEYS.iter().position(|key| *key == scancode) {
+                if input.len() < MAX_FRAME_DIGITS {
+                    input.push_str(&digit.to_string());
+                }
+            } else if scancode == Scancode::Backspace || scancode == Scancode::Delete {
+                input.pop();
+            } else if scancode == Scancode::Return || scancode == Scancode::KpEnter {
+                let frame = input.parse::<usize>().ok();
+                self.frame_input = None;
+                if let Some(frame) = frame {
+                    self.seek_to_frame(frame);
+                }
+                return;
+            } else if scancode == Scancode::Escape {
+                self.frame_input = None;
+                return;
+            }
+        }
+    }
+
+    fn is_just_pressed(&self, keyboard_state: &KeyboardState, scancode: Scancode) -> bool {
+        keyboard_state.is_scancode_pressed(scancode) && !self.previous_keys.contains(&scancode)
+    }
+}
+
+impl Screen for ReplayScreen<'_> {
+    fn draw(&mut self, canvas: &mut Canvas<Window>) {
+        self.renderer.draw(canvas, &self.world);
+
+        // 再生状態の描画
+        let status = format!(
+            "{} x{}  FRAME {} / {}",
+            if self.paused { "PAUSE" } else { "REPLAY" },
+            self.speed,
+            self.frame,
+            self.replay.frames.len(),
+        );
+        self.renderer.draw_text(canvas, Rect::new(0, 0, SCREEN_WIDTH as u32, 16), &status, Color::RGB(255, 255, 0), &self.renderer.font16, Alignment::Center);
+
+        // タイムラインの描画（再生済みの部分を塗る）
+        let timeline = Self::timeline_rect();
+        let played_width = (self.frame * TIMELINE_WIDTH as usize / self.total_frames()) as u32;
+        canvas.set_draw_color(Color::RGB(64, 64, 64));
+        if let Err(error) = canvas.fill_rect(timeline) {
+            println!("Failure to draw timeline: {}", error);
+        }
+        if played_width > 0 {
+            canvas.set_draw_color(Color::RGB(255, 255, 0));
+            if let Err(error) = canvas.fill_rect(Rect::new(TIMELINE_X, TIMELINE_Y, played_width, TIMELINE_HEIGHT)) {
+                println!("Failure to draw timeline: {}", error);
+            }
+        }
+
+        // 移動先のフレーム番号の入力欄
+        if let Some(input) = &self.frame_input {
+            let text = format!("GO TO FRAME {}_", input);
+            self.renderer.draw_text(canvas, Rect::new(0, TIMELINE_Y + TIMELINE_HEIGHT as i32 + 4, SCREEN_WIDTH as u32, 16), &text, Color::RGB(255, 255, 255), &self.renderer.font16, Alignment::Center);
+        }
+
+        self.canvas_scale = canvas.scale();
+
+        canvas.present();
+    }
+
+    fn on_next_frame(&mut self, event_pump: &EventPump) -> ScreenEvent {
+        let keyboard_state = event_pump.keyboard_state();
+
+        // フレーム番号の入力中は、他のキー操作を受け付けない
+        if self.frame_input.is_some() {
+            self.handle_frame_input(&keyboard_state);
+            self.previous_keys = CONTROL_KEYS.iter().copied()
+                .filter(|scancode| keyboard_state.is_scancode_pressed(*scancode))
+                .collect();
+            return ScreenEvent::None;
+        }
+        // 移動先のフレーム番号の入力を始める
+        if self.is_just_pressed(&keyboard_state, Scancode::G) {
+            self.frame_input = Some(String::new());
+        }
+
+        // 一時停止と再開
+        if self.is_just_pressed(&keyboard_state, Scancode::Space) {
+            self.paused = !self.paused;
+        }
+        // コマ送り
+        if self.is_just_pressed(&keyboard_state, Scancode::Period) {
+            self.paused = true;
+            self.advance();
+        }
+        // 再生速度の変更
+        if self.is_just_pressed(&keyboard_state, Scancode::Num1) {
+            self.speed = 1;
+        } else if self.is_just_pressed(&keyboard_state, Scancode::Num2) {
+            self.speed = 2;
+        } else if self.is_just_pressed(&keyboard_state, Scancode::Num4) {
+            self.speed = 4;
+        }
+        // シーク
+        if self.is_just_pressed(&keyboard_state, Scancode::Left) {
+            self.seek_to_frame(self.frame.saturating_sub(SEEK_STEP));
+        } else if self.is_just_pressed(&keyboard_state, Scancode::Right) {
+            self.seek_to_frame(self.frame + SEEK_STEP);
+        } else if self.is_just_pressed(&keyboard_state, Scancode::Home) {
+            self.seek_to_frame(0);
+        } else if self.is_just_pressed(&keyboard_state, Scancode::End) {
+            self.seek_to_frame(self.total_frames());
+        }
+
+        // タイムラインのクリックとドラッグでシーク
+        let mouse_state = event_pump.mouse_state();
+        let mouse_x = (mouse_state.x() as f32 / self.canvas_scale.0) as i32;
+        let mouse_y = (mouse_state.y() as f32 / self.canvas_scale.1) as i32;
+        if !mouse_state.is_mouse_button_pressed(MouseButton::Left) {
+            self.dragging_timeline = false;
+        } else if !self.dragging_timeline && Self::timeline_rect().contains_point((mouse_x, mouse_y)) {
+            self.dragging_timeline = true;
+        }
+        if self.dragging_timeline {
+            let frame = self.frame_at_timeline(mouse_x);
+            if frame != self.frame {
+                self.seek_to_frame(frame);
+            }
+        }
+
+        // タイトル画面に戻る（フレーム番号の入力を Esc で取り消したときは戻らない）
+        let is_returning_to_title = self.is_just_pressed(&keyboard_state, Scancode::Escape);
+
+        self.previous_keys = CONTROL_KEYS.iter().copied()
+            .filter(|scancode| keyboard_state.is_scancode_pressed(*scancode))
+            .collect();
+
+        // 再生（タイムラインをドラッグ中は止めておく）
+        if !self.paused && !self.dragging_timeline {
+            for _ in 0..self.speed {
+                self.advance();
+            }
+        }
+
+        if is_returning_to_title {
+            return ScreenEvent::ReturnToTitleScreen;
+        }
+
+        ScreenEvent::None
+    }
+}
@@ -1,14 +1,27 @@
+use std::path::PathBuf;
+
 use sdl2::{EventPump, video::Window, render::Canvas};
 
+use rust_bomber::game::rule::Rule;
+use rust_bomber::game::stage::Stage;
 use crate::game_screen::player_type::PlayerType;
 
 pub enum ScreenEvent {
     None,
-    GoToGameScreen(PlayerType, PlayerType),
+    GoToGameScreen(Vec<PlayerType>, Rule, Stage),
+    // None の場合は最新のリプレイを再生する
+    GoToReplayScreen(Option<PathBuf>),
+    // ステージ番号のステージを開いたステージエディタ（試合のルールはテストプレイに使う）
+    GoToEditorScreen(usize, Rule),
+    // 現在の画面を残したままステージをテストプレイする（ゲーム画面を抜けると元の画面に戻る）
+    TestPlayStage(Rule, Stage),
     ReturnToTitleScreen,
 }
 
 pub trait Screen {
     fn draw(&mut self, canvas: &mut Canvas<Window>);
     fn on_next_frame(&mut self, event_pump: &EventPump) -> ScreenEvent;
+
+    /// テストプレイから戻ってきたときに呼ばれる
+    fn on_resume(&mut self) {}
 }
\ No newline at end of file
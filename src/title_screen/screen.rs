@@ -3,24 +3,75 @@ use std::path::Path;
 use sdl2::{video::{Window, WindowContext}, render::{Canvas, Texture, TextureCreator, TextureQuery}, pixels::Color, rect::Rect, ttf::{Font, Sdl2TtfContext}, EventPump, keyboard::Scancode, mixer::{Chunk, Music}};
 use sdl2::image::LoadTexture;
 
+use rust_bomber::ai::difficulty::Difficulty;
+use rust_bomber::ai::strategy::StrategyKind;
+use rust_bomber::game::constants::{SCREEN_WIDTH, SCREEN_HEIGHT};
+use rust_bomber::game::map_generator::{MapStyle, Symmetry};
+use rust_bomber::game::rule::Rule;
+use rust_bomber::game::stage::{self, Stage};
+use rust_bomber::game::world::{MIN_PLAYER_COUNT, MAX_PLAYER_COUNT};
 use crate::game_screen::player_type::PlayerType;
 use crate::screen::{Screen, ScreenEvent};
 
-struct MenuItem<'a> {
-    text: &'a str,
-    player_type_1: PlayerType,
-    player_type_2: PlayerType,
+#[derive(Copy, Clone)]
+enum MenuItem {
+    // 左右キーで参加するプレイヤーの種類を切り替える
+    PlayerSlot(usize),
+    // 左右キーで AI の強さを切り替える（すべての AI に共通）
+    AILevel,
+    // 左右キーでステージを切り替える
+    Stage,
+    // 左右キーでマップの生成方法を切り替える
+    MapStyle,
+    // 左右キーで壊せる壁の配置の対称性を切り替える
+    Symmetry,
+    // 左右キーで壊せる壁の密度を切り替える
+    WallDensity,
+    // 左右キーで爆弾の設置数制限の有無を切り替える
+    BombLimit,
+    StartGame,
+    PlayReplay,
+    // 選ばれているステージをステージエディタで開く
+    EditStage,
 }
 
-const MENU_ITEMS: [MenuItem; 4] = [
-    MenuItem { text: "HUMAN VS HUMAN", player_type_1: PlayerType::HUMAN, player_type_2: PlayerType::HUMAN },
-    MenuItem { text: "HUMAN VS AI", player_type_1: PlayerType::HUMAN, player_type_2: PlayerType::AI },
-    MenuItem { text: "AI VS HUMAN", player_type_1: PlayerType::AI, player_type_2: PlayerType::HUMAN },
-    MenuItem { text: "AI VS AI", player_type_1: PlayerType::AI, player_type_2: PlayerType::AI },
+const MENU_ITEMS: [MenuItem; 13] = [
+    MenuItem::PlayerSlot(0),
+    MenuItem::PlayerSlot(1),
+    MenuItem::PlayerSlot(2),
+    MenuItem::PlayerSlot(3),
+    MenuItem::AILevel,
+    MenuItem::Stage,
+    MenuItem::MapStyle,
+    MenuItem::Symmetry,
+    MenuItem::WallDensity,
+    MenuItem::BombLimit,
+    MenuItem::StartGame,
+    MenuItem::PlayReplay,
+    MenuItem::EditStage,
 ];
+
+// プレイヤー枠の選択肢（None は不参加。AI の強さはタイトル画面で選んでいるものに置き換える。
+// EXTERNAL、REMOTE、NEURAL はコマンドラインでボットを指定した場合のみ選べる）
+const PLAYER_SLOT_CHOICES: [Option<PlayerType>; 10] = [
+    None,
+    Some(PlayerType::HUMAN),
+    Some(PlayerType::AI(StrategyKind::Standard, Difficulty::Expert)),
+    Some(PlayerType::AI(StrategyKind::Passive, Difficulty::Expert)),
+    Some(PlayerType::AI(StrategyKind::Hunter, Difficulty::Expert)),
+    Some(PlayerType::AI(StrategyKind::RandomWalk, Difficulty::Expert)),
+    Some(PlayerType::AI(StrategyKind::MonteCarlo, Difficulty::Expert)),
+    Some(PlayerType::NEURAL),
+    Some(PlayerType::EXTERNAL),
+    Some(PlayerType::REMOTE),
+];
+
+// 壊せる壁の密度の選択肢の刻み（パーセント、None の次は 0 から 100 まで）
+const WALL_DENSITY_STEP: i32 = 10;
+
 const MENU_ITEM_X: i32 = 300;
-const MENU_ITEM_Y_START: i32 = 260;
-const MENU_ITEM_Y_STEP: i32 = 40;
+const MENU_ITEM_Y_START: i32 = 212;
+const MENU_ITEM_Y_STEP: i32 = 20;
 const JINGLE_TIME: i32 = 190;
 
 pub struct TitleScreen<'a> {
@@ -37,7 +88,23 @@ pub struct TitleScreen<'a> {
     // カーソル位置
     cursor: usize,
 
-    // 前フレームでのカーソルの移動
+    // 各プレイヤー枠の種類（None は不参加）
+    player_slots: [Option<PlayerType>; MAX_PLAYER_COUNT],
+
+    // 各プレイヤー枠で選べるボットの種類
+    bot_choices: [Vec<PlayerType>; MAX_PLAYER_COUNT],
+
+    // AI の強さ
+    ai_difficulty: Difficulty,
+
+    // 試合のルール
+    rule: Rule,
+
+    // 選べるステージと選ばれているステージの番号
+    stages: Vec<Stage>,
+    stage_index: usize,
+
+    // 前フレームでのカーソルの移動（上下は -1/1、左右は -2/2）
     previous_move: i32,
 
     // ゲーム画面に遷移しているときの状態
@@ -51,13 +118,20 @@ pub struct TitleScreen<'a> {
 }
 
 impl TitleScreen<'_> {
-    pub fn new<'a>(texture_creator: &'a TextureCreator<WindowContext>, ttf_context: &'a Sdl2TtfContext) -> TitleScreen<'a> {
+    pub fn new<'a>(texture_creator: &'a TextureCreator<WindowContext>, ttf_context: &'a Sdl2TtfContext, rule: Rule, player_slots: [Option<PlayerType>; MAX_PLAYER_COUNT], bot_choices: [Vec<PlayerType>; MAX_PLAYER_COUNT], ai_difficulty: Difficulty) -> TitleScreen<'a> {
         let screen = TitleScreen {
             logo_image: texture_creator.load_texture(Path::new("res/image/logo.png")).unwrap(),
             cursor_image: texture_creator.load_texture(Path::new("res/image/pipo-charachip018b.png")).unwrap(),
             font16: ttf_context.load_font(Path::new("res/font/m12.ttf"), 16).unwrap(),
             texture_creator,
-            cursor: 0,
+            // 最初は START にカーソルを合わせておく
+            cursor: 10,
+            player_slots,
+            bot_choices,
+            ai_difficulty,
+            rule,
+            stages: Self::load_stages(),
+            stage_index: 0,
             previous_move: 0,
             going_to_game_screen_state: -1,
             start_game_sound: Chunk::from_file(Path::new("res/sound/start_game.mp3")).ok(),
@@ -75,6 +149,97 @@ impl TitleScreen<'_> {
         screen
     }
 
+    /// 同梱のステージを読み込む（読み込めない場合は標準のステージだけにする）
+    fn load_stages() -> Vec<Stage> {
+        match Stage::load_directory(Path::new(stage::STAGE_DIRECTORY)) {
+            Ok(stages) if !stages.is_empty() => stages,
+            Ok(_) => vec![Stage::classic()],
+            Err(error) => {
+                println!("{}", error);
+                vec![Stage::classic()]
+            }
+        }
+    }
+
+    fn menu_item_text(&self, menu_item: MenuItem) -> String {
+        match menu_item {
+            MenuItem::PlayerSlot(slot) => format!(
+                "PLAYER {}   {}",
+                slot + 1,
+                self.player_slots[slot].map_or("-----", |player_type| player_type.name()),
+            ),
+            MenuItem::AILevel => format!("AI LEVEL   {}", self.ai_difficulty.name().to_uppercase()),
+            MenuItem::Stage => format!("STAGE      {}", self.stages[self.stage_index].name),
+            MenuItem::MapStyle => format!("MAP STYLE  {}", self.rule.map_style.name().to_uppercase()),
+            MenuItem::Symmetry => format!("SYMMETRY   {}", self.rule.symmetry.name().to_uppercase()),
+            MenuItem::WallDensity => format!(
+                "WALL DENSITY {}",
+                self.rule.soft_wall_density.map_or("DEFAULT".to_string(), |density| format!("{}%", density)),
+            ),
+            MenuItem::BombLimit => format!("BOMB LIMIT {}", if self.rule.unlimited_bombs { "OFF" } else { "ON" }),
+            MenuItem::StartGame => "START".to_string(),
+            MenuItem::PlayReplay => "REPLAY".to_string(),
+            MenuItem::EditStage => "EDIT STAGE".to_string(),
+        }
+    }
+
+    /// プレイヤー枠の種類を切り替える
+    fn change_player_slot(&mut self, slot: usize, step: i32) {
+        // 3人目以降は前のプレイヤーが参加している場合のみ変更できる
+        if slot >= MIN_PLAYER_COUNT && self.player_slots[slot - 1].is_none() {
+            return;
+        }
+        let choices: Vec<Option<PlayerType>> = PLAYER_SLOT_CHOICES.iter()
+            .filter(|choice| choice.is_some() || slot >= MIN_PLAYER_COUNT)
+            .filter(|choice| match choice {
+                Some(bot @ (PlayerType::EXTERNAL | PlayerType::REMOTE | PlayerType::NEURAL)) => self.bot_choices[slot].contains(bot),
+                _ => true,
+            })
+            .copied()
+            .collect();
+        let with_difficulty = |choice: &Option<PlayerType>| choice.map(|player_type| player_type.with_difficulty(self.ai_difficulty));
+        let current = choices.iter().position(|choice| with_difficulty(choice) == self.player_slots[slot]).unwrap_or(0);
+        let next = (current as i32 + step).rem_euclid(choices.len() as i32) as usize;
+        self.player_slots[slot] = with_difficulty(&choices[next]);
+        // 不参加にした場合はそれより後ろのプレイヤーも不参加にする
+        if self.player_slots[slot].is_none() {
+            for player_slot in self.player_slots.iter_mut().skip(slot) {
+                *player_slot = None;
+            }
+        }
+    }
+
+    /// AI の強さを切り替える（参加している AI すべてに反映する）
+    fn change_ai_difficulty(&mut self, step: i32) {
+        let current = Difficulty::ALL.iter().position(|difficulty| *difficulty == self.ai_difficulty).unwrap_or(0);
+        self.ai_difficulty = Difficulty::ALL[(current as i32 + step).rem_euclid(Difficulty::ALL.len() as i32) as usize];
+        for player_type in self.player_slots.iter_mut().flatten() {
+            *player_type = player_type.with_difficulty(self.ai_difficulty);
+        }
+    }
+
+    /// マップの生成方法、壊せる壁の対称性と密度を切り替える
+    fn change_map_rule(&mut self, menu_item: MenuItem, step: i32) {
+        match menu_item {
+            MenuItem::MapStyle => {
+                let current = MapStyle::ALL.iter().position(|style| *style == self.rule.map_style).unwrap_or(0);
+                self.rule.map_style = MapStyle::ALL[(current as i32 + step).rem_euclid(MapStyle::ALL.len() as i32) as usize];
+            }
+            MenuItem::Symmetry => {
+                let current = Symmetry::ALL.iter().position(|symmetry| *symmetry == self.rule.symmetry).unwrap_or(0);
+                self.rule.symmetry = Symmetry::ALL[(current as i32 + step).rem_euclid(Symmetry::ALL.len() as i32) as usize];
+            }
+            MenuItem::WallDensity => {
+                // DEFAULT, 0%, 10%, ... 100% の順に切り替える（コマンドラインで刻みに合わない値を指定した場合は切り捨てた値から数える）
+                let choice_count = 100 / WALL_DENSITY_STEP + 2;
+                let current = self.rule.soft_wall_density.map_or(0, |density| density / WALL_DENSITY_STEP + 1);
+                let next = (current + step).rem_euclid(choice_count);
+                self.rule.soft_wall_density = if next == 0 { None } else { Some((next - 1) * WALL_DENSITY_STEP) };
+            }
+            _ => (),
+        }
+    }
+
     fn draw_text(&self, canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color: Color) {
         let surface = self.font16
             .render(text)
@@ -111,7 +276,7 @@ impl Screen for TitleScreen<'_> {
         }
 
         // 選択肢の描画
-        for i in 0..MENU_ITEMS.len() {
+        for (i, menu_item) in MENU_ITEMS.iter().enumerate() {
             let y = MENU_ITEM_Y_START + MENU_ITEM_Y_STEP * i as i32;
             let color = if i == self.cursor {
                 if self.going_to_game_screen_state >= 0 && self.going_to_game_screen_state % 12 < 6 {
@@ -122,7 +287,7 @@ impl Screen for TitleScreen<'_> {
             } else {
                 Color::RGB(160, 160, 160)
             };
-            self.draw_text(canvas, MENU_ITEM_X, y, MENU_ITEMS[i].text, color);
+            self.draw_text(canvas, MENU_ITEM_X, y, &self.menu_item_text(*menu_item), color);
         }
 
         // カーソルの描画
@@ -138,7 +303,7 @@ impl Screen for TitleScreen<'_> {
         if self.going_to_game_screen_state > JINGLE_TIME - 15 {
             canvas.set_blend_mode(sdl2::render::BlendMode::Blend);
             canvas.set_draw_color(Color::RGBA(0, 0, 0, (255 * (self.going_to_game_screen_state - (JINGLE_TIME - 15)) / 15) as u8));
-            if let Err(error) = canvas.fill_rect(Rect::new(0, 0, 800, 480)) {
+            if let Err(error) = canvas.fill_rect(Rect::new(0, 0, SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32)) {
                 println!("Failed to fill rect: {}", error);
             }
         }
@@ -151,8 +316,14 @@ impl Screen for TitleScreen<'_> {
         if self.going_to_game_screen_state >= 0 {
             self.going_to_game_screen_state += 1;
             if self.going_to_game_screen_state >= JINGLE_TIME {
-                let item = &MENU_ITEMS[self.cursor];
-                return ScreenEvent::GoToGameScreen(item.player_type_1, item.player_type_2);
+                return match MENU_ITEMS[self.cursor] {
+                    MenuItem::PlayReplay => ScreenEvent::GoToReplayScreen(None),
+                    _ => ScreenEvent::GoToGameScreen(
+                        self.player_slots.iter().flatten().copied().collect(),
+                        self.rule,
+                        self.stages[self.stage_index].clone(),
+                    ),
+                };
             }
             return ScreenEvent::None;
         }
@@ -177,11 +348,41 @@ impl Screen for TitleScreen<'_> {
                     self.cursor += 1;
                 }
             }
+        } else if keyboard_state.is_scancode_pressed(Scancode::Left) || keyboard_state.is_scancode_pressed(Scancode::A) {
+            if self.previous_move != -2 {
+                self.previous_move = -2;
+                match MENU_ITEMS[self.cursor] {
+                    MenuItem::PlayerSlot(slot) => self.change_player_slot(slot, -1),
+                    MenuItem::AILevel => self.change_ai_difficulty(-1),
+                    MenuItem::Stage => self.stage_index = (self.stage_index as i32 + -1).rem_euclid(self.stages.len() as i32) as usize,
+                    MenuItem::MapStyle | MenuItem::Symmetry | MenuItem::WallDensity => self.change_map_rule(MENU_ITEMS[self.cursor], -1),
+                    MenuItem::BombLimit => self.rule.unlimited_bombs = !self.rule.unlimited_bombs,
+                    _ => (),
+                }
+            }
+        } else if keyboard_state.is_scancode_pressed(Scancode::Right) || keyboard_state.is_scancode_pressed(Scancode::D) {
+            if self.previous_move != 2 {
+                self.previous_move = 2;
+                match MENU_ITEMS[self.cursor] {
+                    MenuItem::PlayerSlot(slot) => self.change_player_slot(slot, 1),
+                    MenuItem::AILevel => self.change_ai_difficulty(1),
+                    MenuItem::Stage => self.stage_index = (self.stage_index as i32 + 1).rem_euclid(self.stages.len() as i32) as usize,
+                    MenuItem::MapStyle | MenuItem::Symmetry | MenuItem::WallDensity => self.change_map_rule(MENU_ITEMS[self.cursor], 1),
+                    MenuItem::BombLimit => self.rule.unlimited_bombs = !self.rule.unlimited_bombs,
+                    _ => (),
+                }
+            }
         } else {
             self.previous_move = 0;
 
-            // 決定キー
-            if keyboard_state.is_scancode_pressed(Scancode::Space) || keyboard_state.is_scancode_pressed(Scancode::Num1) || keyboard_state.is_scancode_pressed(Scancode::Slash) {
+            // 決定キー（プレイヤー枠、ステージ、ルールの上では無効）
+            let is_decide_key_pressed = keyboard_state.is_scancode_pressed(Scancode::Space) || keyboard_state.is_scancode_pressed(Scancode::Num1) || keyboard_state.is_scancode_pressed(Scancode::Slash);
+            let is_decidable = matches!(MENU_ITEMS[self.cursor], MenuItem::StartGame | MenuItem::PlayReplay);
+            if is_decide_key_pressed && matches!(MENU_ITEMS[self.cursor], MenuItem::EditStage) {
+                // ステージエディタへはジングルを鳴らさずにすぐ移る
+                return ScreenEvent::GoToEditorScreen(self.stage_index, self.rule);
+            }
+            if is_decidable && is_decide_key_pressed {
                 // BGM停止
                 sdl2::mixer::Music::halt();
                 // ジングル再生